@@ -0,0 +1,426 @@
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+const BLUEPRINT_PATH: &str = "src/scripts/plutus.json";
+
+/// Validators hibiki builds scripts from, by `<validator>.<purpose>` title, and their
+/// `(group, field)` in the `SCRIPTS` table
+const VALIDATORS: &[(&str, Option<(&str, &str)>)] = &[
+    ("oracle_nft.mint", Some(("dex_order_book", "mint"))),
+    ("dex_order_book.spend", Some(("dex_order_book", "spend"))),
+    (
+        "hydra_account.spend",
+        Some(("hydra_account_balance", "spend")),
+    ),
+    (
+        "hydra_account.withdraw",
+        Some(("hydra_account_balance", "withdrawal")),
+    ),
+    (
+        "hydra_order_book.spend",
+        Some(("hydra_order_book", "spend")),
+    ),
+    (
+        "hydra_order_book.withdraw",
+        Some(("hydra_order_book", "withdrawal")),
+    ),
+    ("hydra_order_book_publish.withdraw", None),
+    ("hydra_tokens.mint", Some(("hydra_token", "mint"))),
+    (
+        "hydra_user_intent.spend",
+        Some(("hydra_user_intent", "spend")),
+    ),
+    (
+        "hydra_user_intent.mint",
+        Some(("hydra_user_intent", "mint")),
+    ),
+];
+
+/// Blueprint types provided by `whisky::data`, by definition name
+const WHISKY_TYPES: &[(&str, &str)] = &[
+    ("Int", "Int"),
+    ("ByteArray", "ByteArray"),
+    ("Bool", "Bool"),
+    ("Data", "PlutusData"),
+    ("PolicyId", "PolicyId"),
+    ("AssetName", "AssetName"),
+    ("Address", "Address"),
+    ("Credential", "Credential"),
+    ("OutputReference", "OutputReference"),
+    ("ScriptHash", "ScriptHash"),
+    ("VerificationKeyHash", "VerificationKeyHash"),
+];
+
+/// Aliases written by hand in `scripts/bar.rs`, which the blueprint inlines
+const HAND_WRITTEN_TYPES: &[&str] = &["MValue"];
+
+/// Larger tuples lack the trait impls, so their fields go through `ConstrFields`
+const MAX_TUPLE_FIELDS: usize = 12;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::compile_protos("proto/hibiki_ext.proto")?;
+
+    println!("cargo:rerun-if-changed={}", BLUEPRINT_PATH);
+    let blueprint: Value = serde_json::from_str(&fs::read_to_string(BLUEPRINT_PATH)?)?;
+    let code = generate_scripts(&blueprint)?;
+    fs::write(Path::new(&env::var("OUT_DIR")?).join("bar.rs"), code)?;
+    Ok(())
+}
+
+/// Rust types and script constructors of the blueprint, included by `scripts/bar.rs`
+fn generate_scripts(blueprint: &Value) -> Result<String, String> {
+    let empty = Map::new();
+    let definitions = blueprint["definitions"].as_object().unwrap_or(&empty);
+    let mut types = TypeGenerator {
+        definitions,
+        items: BTreeMap::new(),
+    };
+
+    let mut code = String::from(
+        "// Generated by build.rs from src/scripts/plutus.json, do not edit\n\n\
+         #[allow(unused_imports)]\n\
+         use whisky::{\n    \
+         BuilderDataType, ConstrEnum, ImplConstr, MintingBlueprint, SpendingBlueprint,\n    \
+         WithdrawalBlueprint,\n};\n\
+         #[allow(unused_imports)]\n\
+         use whisky::data::*;\n\n",
+    );
+    let mut expected = String::new();
+    let mut groups: Vec<(&str, Vec<ScriptField>)> = Vec::new();
+
+    for (title, slot) in VALIDATORS {
+        let validator = find_validator(blueprint, title)?;
+        let (name, purpose) = title
+            .rsplit_once('.')
+            .ok_or_else(|| format!("{} is not <validator>.<purpose>", title))?;
+        let constant = title.replace('.', "_").to_uppercase();
+        code.push_str(&format!("pub const {}: &str = \"{}\";\n", constant, title));
+
+        let parameters = validator["parameters"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let mut parameter_types = Vec::new();
+        let mut parameter_names = Vec::new();
+        for parameter in &parameters {
+            parameter_types.push(types.resolve(&parameter["schema"])?);
+            parameter_names.push(format!("\"{}\"", schema_name(&parameter["schema"])));
+        }
+        expected.push_str(&format!(
+            "    ({}, &[{}]),\n",
+            constant,
+            parameter_names.join(", ")
+        ));
+
+        let redeemer = types.resolve(&validator["redeemer"]["schema"])?;
+        let (blueprint_type, kind, new_args, generics) = match purpose {
+            "mint" => (
+                "MintingBlueprint",
+                "minting",
+                "app_config.plutus_version",
+                vec![redeemer],
+            ),
+            "spend" => (
+                "SpendingBlueprint",
+                "spending",
+                "app_config.plutus_version, app_config.network_id, None",
+                vec![redeemer, types.resolve(&validator["datum"]["schema"])?],
+            ),
+            "withdraw" => (
+                "WithdrawalBlueprint",
+                "withdrawal",
+                "app_config.plutus_version, app_config.network_id",
+                vec![redeemer],
+            ),
+            other => return Err(format!("{} has unsupported purpose {}", title, other)),
+        };
+        let (params_type, params_arg, param_strs) = match parameter_types.as_slice() {
+            [single] => (
+                single.clone(),
+                format!("&{}", single),
+                "params.to_json_string()".to_string(),
+            ),
+            many => {
+                let tuple = format!("({})", many.join(", "));
+                let strs: Vec<String> = (0..many.len())
+                    .map(|index| format!("params.{}.to_json_string()", index))
+                    .collect();
+                (tuple.clone(), tuple, strs.join(", "))
+            }
+        };
+        let full_type = format!(
+            "{}<{}, {}>",
+            blueprint_type,
+            params_type,
+            generics.join(", ")
+        );
+        let function = format!("{}_{}_{}_blueprint", name, purpose, kind).replace('.', "_");
+        let count = parameter_types.len();
+        code.push_str(&format!(
+            "\npub fn {function}(params: {params_arg}, network_id: u8) -> {full_type} {{\n    \
+             let app_config = ScriptConfig::new(network_id);\n    \
+             let mut blueprint = {blueprint_type}::new({new_args});\n    \
+             let param_strs: [String; {count}] = [{param_strs}];\n    \
+             let param_refs: Vec<&str> = param_strs.iter().map(|s| s.as_str()).collect();\n    \
+             blueprint\n        \
+             .param_script(validator_code({constant}), &param_refs, BuilderDataType::JSON)\n        \
+             .unwrap();\n    \
+             blueprint\n}}\n\n",
+        ));
+
+        if let Some((group, field)) = slot {
+            let script = ScriptField {
+                field,
+                signature: format!("fn({}, u8) -> {}", params_arg, full_type),
+                function,
+            };
+            match groups.iter_mut().find(|(name, _)| name == group) {
+                Some((_, fields)) => fields.push(script),
+                None => groups.push((group, vec![script])),
+            }
+        }
+    }
+
+    // Every named type, not only those of the validators above
+    for key in definitions.keys() {
+        if !key.contains('$') {
+            types.resolve_key(key)?;
+        }
+    }
+
+    code.push_str(
+        "/// Every validator hibiki builds, with the types of its parameters\n\
+         pub const EXPECTED_VALIDATORS: &[(&str, &[&str])] = &[\n",
+    );
+    code.push_str(&expected);
+    code.push_str("];\n\n");
+
+    code.push_str("/// Script blueprints organized by function\npub struct Scripts {\n");
+    for (group, _) in &groups {
+        code.push_str(&format!(
+            "    pub {}: {}Scripts,\n",
+            group,
+            camel_case(group)
+        ));
+    }
+    code.push_str("}\n\n");
+    for (group, fields) in &groups {
+        code.push_str(&format!("pub struct {}Scripts {{\n", camel_case(group)));
+        for script in fields {
+            code.push_str(&format!(
+                "    pub {}: {},\n",
+                script.field, script.signature
+            ));
+        }
+        code.push_str("}\n\n");
+    }
+    code.push_str("pub const SCRIPTS: Scripts = Scripts {\n");
+    for (group, fields) in &groups {
+        code.push_str(&format!("    {}: {}Scripts {{\n", group, camel_case(group)));
+        for script in fields {
+            code.push_str(&format!("        {}: {},\n", script.field, script.function));
+        }
+        code.push_str("    },\n");
+    }
+    code.push_str("};\n");
+
+    for item in types.items.values() {
+        code.push('\n');
+        code.push_str(item);
+    }
+    Ok(code)
+}
+
+/// A `SCRIPTS` table entry
+struct ScriptField {
+    field: &'static str,
+    signature: String,
+    function: String,
+}
+
+/// The one validator titled `title`, or `<module>.<title>`
+fn find_validator<'a>(blueprint: &'a Value, title: &str) -> Result<&'a Value, String> {
+    let matches: Vec<&Value> = blueprint["validators"]
+        .as_array()
+        .map(|validators| {
+            validators
+                .iter()
+                .filter(|validator| {
+                    let full = validator["title"].as_str().unwrap_or_default();
+                    full == title
+                        || full
+                            .strip_suffix(title)
+                            .is_some_and(|module| module.ends_with('.'))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    match matches.as_slice() {
+        [validator] => Ok(validator),
+        [] => Err(format!("{} is not in {}", title, BLUEPRINT_PATH)),
+        _ => Err(format!("{} is ambiguous in {}", title, BLUEPRINT_PATH)),
+    }
+}
+
+/// Definition key of a `{"$ref": "#/definitions/..."}` schema, `~1` decoded to `/`
+fn schema_key(schema: &Value) -> Option<String> {
+    let schema_ref = schema["$ref"].as_str()?;
+    let key = schema_ref.strip_prefix("#/definitions/")?;
+    Some(key.replace("~1", "/"))
+}
+
+/// Last path segment of a schema's definition, e.g. `PolicyId`
+fn schema_name(schema: &Value) -> String {
+    schema_key(schema)
+        .map(|key| key.rsplit('/').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+fn camel_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+struct TypeGenerator<'a> {
+    definitions: &'a Map<String, Value>,
+    /// Generated items by type name
+    items: BTreeMap<String, String>,
+}
+
+impl TypeGenerator<'_> {
+    /// Rust type of a schema, generating the named types it refers to
+    fn resolve(&mut self, schema: &Value) -> Result<String, String> {
+        match schema_key(schema) {
+            Some(key) => self.resolve_key(&key),
+            // Inline or missing schemas are opaque data
+            None => Ok("PlutusData".to_string()),
+        }
+    }
+
+    fn resolve_key(&mut self, key: &str) -> Result<String, String> {
+        let definition = self
+            .definitions
+            .get(key)
+            .ok_or_else(|| format!("{} has no definition", key))?;
+
+        if let Some((generic, _)) = key.split_once('$') {
+            return match definition["dataType"].as_str() {
+                Some("list") if definition["items"].is_array() => Ok("Tuple".to_string()),
+                Some("list") => Ok(format!("List<{}>", self.resolve(&definition["items"])?)),
+                Some("map") => Ok(format!(
+                    "Map<{}, {}>",
+                    self.resolve(&definition["keys"])?,
+                    self.resolve(&definition["values"])?
+                )),
+                _ if generic == "Tuple" || generic == "Pair" => Ok("Tuple".to_string()),
+                _ => Ok("PlutusData".to_string()),
+            };
+        }
+
+        let name = key.rsplit('/').next().unwrap_or_default().to_string();
+        if let Some((_, whisky_type)) = WHISKY_TYPES
+            .iter()
+            .find(|(blueprint, _)| *blueprint == name)
+        {
+            return Ok(whisky_type.to_string());
+        }
+        if HAND_WRITTEN_TYPES.contains(&name.as_str()) || self.items.contains_key(&name) {
+            return Ok(name);
+        }
+        // Reserve the name first, recursive types refer back to it
+        self.items.insert(name.clone(), String::new());
+        let item = self.generate_definition(&name, definition)?;
+        self.insert(&name, item)?;
+        Ok(name)
+    }
+
+    fn generate_definition(&mut self, name: &str, definition: &Value) -> Result<String, String> {
+        let Some(constructors) = definition["anyOf"].as_array() else {
+            let aliased = match definition["dataType"].as_str() {
+                Some("integer") => "Int".to_string(),
+                Some("bytes") => "ByteArray".to_string(),
+                Some("list") => format!("List<{}>", self.resolve(&definition["items"])?),
+                Some("map") => format!(
+                    "Map<{}, {}>",
+                    self.resolve(&definition["keys"])?,
+                    self.resolve(&definition["values"])?
+                ),
+                _ => "PlutusData".to_string(),
+            };
+            return Ok(format!("pub type {} = {};\n", name, aliased));
+        };
+
+        if let [constructor] = constructors.as_slice() {
+            return self.generate_constructor(name, constructor);
+        }
+        let mut variants = String::new();
+        for constructor in constructors {
+            let variant = constructor["title"]
+                .as_str()
+                .ok_or_else(|| format!("{} has an untitled constructor", name))?
+                .to_string();
+            let has_fields = constructor["fields"]
+                .as_array()
+                .is_some_and(|fields| !fields.is_empty());
+            variants.push_str(&match has_fields {
+                true => format!("    {}({}),\n", variant, variant),
+                false => format!("    {},\n", variant),
+            });
+            let item = self.generate_constructor(&variant, constructor)?;
+            self.insert(&variant, item)?;
+        }
+        Ok(format!(
+            "#[derive(Debug, Clone, ConstrEnum)]\npub enum {} {{\n{}}}\n",
+            name, variants
+        ))
+    }
+
+    /// A struct wrapping `Constr<index>` of the fields, or an alias when there are none
+    fn generate_constructor(&mut self, name: &str, constructor: &Value) -> Result<String, String> {
+        let index = constructor["index"]
+            .as_u64()
+            .ok_or_else(|| format!("{} has no constructor index", name))?;
+        let mut fields = Vec::new();
+        for field in constructor["fields"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+        {
+            fields.push(self.resolve(&field)?);
+        }
+        let inner = match fields.as_slice() {
+            [] => return Ok(format!("pub type {} = Constr{}<()>;\n", name, index)),
+            [single] => single.clone(),
+            many if many.len() > MAX_TUPLE_FIELDS => {
+                format!("ConstrFields<({})>", many.join(", "))
+            }
+            many => format!("({})", many.join(", ")),
+        };
+        Ok(format!(
+            "#[derive(Debug, Clone, ImplConstr)]\npub struct {}(pub Constr{}<Box<{}>>);\n",
+            name, index, inner
+        ))
+    }
+
+    /// Keep a generated item, failing if another type already took its name
+    fn insert(&mut self, name: &str, item: String) -> Result<(), String> {
+        match self.items.get(name) {
+            Some(existing) if !existing.is_empty() && *existing != item => Err(format!(
+                "Two blueprint types are named {}, rename one in the contracts",
+                name
+            )),
+            _ => {
+                self.items.insert(name.to_string(), item);
+                Ok(())
+            }
+        }
+    }
+}
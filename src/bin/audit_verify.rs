@@ -0,0 +1,33 @@
+use std::{env, process};
+
+use hibiki::utils::audit_log::verify_file;
+
+/// Verify the hash chain of a signing audit log
+///
+/// Usage: hibiki-audit-verify [path], defaulting to `SIGNING_AUDIT_LOG_PATH`
+fn main() {
+    dotenv::dotenv().ok();
+    let path = match env::args()
+        .nth(1)
+        .or_else(|| env::var("SIGNING_AUDIT_LOG_PATH").ok())
+    {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: hibiki-audit-verify <audit log path>");
+            process::exit(2);
+        }
+    };
+
+    match verify_file(&path) {
+        Ok((count, last_hash)) => {
+            println!(
+                "{}: {} records, chain intact, head {}",
+                path, count, last_hash
+            );
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
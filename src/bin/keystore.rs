@@ -0,0 +1,153 @@
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process,
+};
+
+use hibiki::{
+    config::signing_keys::{Derivation, MasterKeyScheme},
+    signer::{
+        keystore::{Argon2Params, ScryptParams},
+        Kdf, KeySigner, Keystore, Signer,
+    },
+    utils::hd_key::{derive_signing_key, root_key_from_mnemonic},
+};
+use whisky::WError;
+
+const USAGE: &str = "Usage:
+  hibiki-keystore create <path> [--kdf scrypt|argon2id] [--path <m/1852'/1815'/a'/r/i>]
+                         [--master-key icarus|ledger]
+  hibiki-keystore re-encrypt <path> [--kdf scrypt|argon2id]
+  hibiki-keystore inspect <path> [--verify]
+
+The mnemonic and passphrases are read from stdin, one per line.";
+
+/// Create, re-encrypt and inspect encrypted signing keystores
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, path) = match (args.first(), args.get(1)) {
+        (Some(command), Some(path)) => (command.as_str(), path.as_str()),
+        _ => usage(),
+    };
+    let options = &args[2..];
+
+    let result = match command {
+        "create" => create(path, options).await,
+        "re-encrypt" => reencrypt(path, options),
+        "inspect" => inspect(path, options),
+        _ => usage(),
+    };
+    if let Err(e) = result {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+async fn create(path: &str, options: &[String]) -> Result<(), WError> {
+    let kdf = kdf_option(options)?;
+    let mut derivation = Derivation::default();
+    if let Some(scheme) = option_value(options, "--master-key") {
+        derivation.master_key = scheme
+            .parse::<MasterKeyScheme>()
+            .map_err(WError::from_err("create - master key"))?;
+    }
+    if let Some(derivation_path) = option_value(options, "--path") {
+        derivation = derivation
+            .with_path(derivation_path)
+            .map_err(WError::from_err("create - path"))?;
+    }
+
+    let mnemonic = prompt("Mnemonic")?.replace(',', " ");
+    let bip39_passphrase = prompt("BIP39 passphrase (empty for none)")?;
+    let root = root_key_from_mnemonic(&mnemonic, &bip39_passphrase, derivation.master_key)?;
+    let key_hash = KeySigner::new(derive_signing_key(&root, derivation))
+        .key_hash()
+        .await?;
+    let passphrase = new_passphrase()?;
+
+    Keystore::encrypt(&mnemonic, &passphrase, &kdf, Some(&key_hash))?.write(path)?;
+    println!("{}: created for key hash {}", path, key_hash);
+    Ok(())
+}
+
+fn reencrypt(path: &str, options: &[String]) -> Result<(), WError> {
+    let kdf = kdf_option(options)?;
+    let keystore = Keystore::read(path)?;
+    let passphrase = prompt("Current passphrase")?;
+    keystore.decrypt(&passphrase)?;
+    let new_passphrase = new_passphrase()?;
+
+    keystore
+        .reencrypt(&passphrase, &new_passphrase, &kdf)?
+        .write(path)?;
+    println!("{}: re-encrypted", path);
+    Ok(())
+}
+
+fn inspect(path: &str, options: &[String]) -> Result<(), WError> {
+    let keystore = Keystore::read(path)?;
+    println!("version:  {}", keystore.version);
+    println!("cipher:   {}", keystore.cipher);
+    println!("kdf:      {:?}", keystore.kdf);
+    println!(
+        "key hash: {}",
+        keystore.key_hash.as_deref().unwrap_or("(not recorded)")
+    );
+
+    if options.iter().any(|option| option == "--verify") {
+        keystore.decrypt(&prompt("Passphrase")?)?;
+        println!("passphrase ok");
+    }
+    Ok(())
+}
+
+fn kdf_option(options: &[String]) -> Result<Kdf, WError> {
+    match option_value(options, "--kdf") {
+        None | Some("scrypt") => Ok(Kdf::Scrypt(ScryptParams::default())),
+        Some("argon2id") => Ok(Kdf::Argon2id(Argon2Params::default())),
+        Some(other) => Err(WError::new(
+            "kdf_option",
+            &format!("Unknown kdf {} (expected scrypt or argon2id)", other),
+        )),
+    }
+}
+
+fn option_value<'a>(options: &'a [String], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .position(|option| option == name)
+        .and_then(|index| options.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+fn new_passphrase() -> Result<String, WError> {
+    let passphrase = prompt("New passphrase")?;
+    if passphrase.is_empty() {
+        return Err(WError::new(
+            "new_passphrase",
+            "Passphrase must not be empty",
+        ));
+    }
+    if prompt("Repeat passphrase")? != passphrase {
+        return Err(WError::new("new_passphrase", "Passphrases do not match"));
+    }
+    Ok(passphrase)
+}
+
+fn prompt(label: &str) -> Result<String, WError> {
+    eprint!("{}: ", label);
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(WError::from_err("prompt"))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+use crate::utils::token_registry::{check_new_token, TokenEntry};
+
+/// Id of the deployment described by the top-level settings, unless `deployment_id` is set
+pub const DEFAULT_DEPLOYMENT_ID: &str = "default";
+
+/// One DEX deployment: its oracle NFT, which parameterizes every script, and its keys
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeploymentConfig {
+    pub id: String,
+    pub dex_oracle_nft: String,
+    pub app_owner_vkey: String,
+    /// Load this deployment's signers from `<PREFIX>_APP_OWNER_*` and
+    /// `<PREFIX>_FEE_COLLECTOR_*`; the default deployment's signers are shared when unset
+    pub key_prefix: Option<String>,
+    pub oracle_datum_cbor: Option<String>,
+    pub token_registry_path: Option<String>,
+    /// The top-level `tokens` are used when empty
+    pub tokens: Vec<TokenEntry>,
+}
+
+impl DeploymentConfig {
+    /// Every problem with this deployment's settings
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            errors.push(format!(
+                "deployment id \"{}\" must be letters, digits, - or _",
+                self.id
+            ));
+        }
+        if !is_hex_of_len(&self.app_owner_vkey, 28) {
+            errors.push("app_owner_vkey must be a 28 byte hex key hash".to_string());
+        }
+        if !is_hex_of_len(&self.dex_oracle_nft, 28) {
+            errors.push("dex_oracle_nft must be a 28 byte hex policy id".to_string());
+        }
+        if self
+            .key_prefix
+            .as_ref()
+            .is_some_and(|prefix| prefix.is_empty())
+        {
+            errors.push("key_prefix must not be empty".to_string());
+        }
+        for (index, token) in self.tokens.iter().enumerate() {
+            if let Err(e) = check_new_token(&self.tokens[..index], token) {
+                errors.push(format!("tokens[{}]: {}", index, e));
+            }
+        }
+        if self
+            .oracle_datum_cbor
+            .as_ref()
+            .is_some_and(|datum| hex::decode(datum).is_err())
+        {
+            errors.push("oracle_datum_cbor must be hex".to_string());
+        }
+        errors
+    }
+}
+
+fn is_hex_of_len(value: &str, bytes: usize) -> bool {
+    hex::decode(value).is_ok_and(|decoded| decoded.len() == bytes)
+}
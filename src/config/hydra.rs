@@ -1,31 +1,332 @@
-use whisky::{get_mainnet_cost_models, Protocol};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs, sync::OnceLock};
+use whisky::{get_mainnet_cost_models, Protocol, WError};
 
-pub fn get_hydra_pp() -> Protocol {
+/// Source reported when no `protocol-parameters.json` is configured
+pub const BUILT_IN_SOURCE: &str = "built-in";
+
+/// Plutus languages, in the order of the cost models handed to the evaluator
+const PLUTUS_LANGUAGES: [&str; 3] = ["PlutusV1", "PlutusV2", "PlutusV3"];
+
+/// Language of hibiki's scripts
+const SCRIPT_LANGUAGE: &str = "PlutusV3";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExecutionUnits {
+    pub memory: u64,
+    pub steps: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionUnitPrices {
+    pub price_memory: f64,
+    pub price_steps: f64,
+}
+
+/// The fields hibiki uses of a `protocol-parameters.json`, the file hydra-node is
+/// started with
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolParameters {
+    pub tx_fee_per_byte: u64,
+    pub tx_fee_fixed: u64,
+    pub max_block_body_size: u64,
+    pub max_tx_size: u64,
+    pub max_block_header_size: u64,
+    pub stake_address_deposit: u64,
+    pub stake_pool_deposit: u64,
+    pub min_pool_cost: u64,
+    pub execution_unit_prices: ExecutionUnitPrices,
+    pub max_tx_execution_units: ExecutionUnits,
+    pub max_block_execution_units: ExecutionUnits,
+    pub max_value_size: u64,
+    pub collateral_percentage: u64,
+    pub max_collateral_inputs: u64,
+    pub utxo_cost_per_byte: u64,
+    /// Absent before Conway
+    #[serde(default)]
+    pub min_fee_ref_script_cost_per_byte: f64,
+    /// Cost model by language name, e.g. `PlutusV3`
+    #[serde(default)]
+    pub cost_models: BTreeMap<String, Vec<i64>>,
+}
+
+/// The parameters of the head used when none are configured
+fn built_in_parameters() -> Value {
+    let cost_models: BTreeMap<&str, Vec<i64>> = PLUTUS_LANGUAGES
+        .into_iter()
+        .zip(get_mainnet_cost_models())
+        .collect();
+    json!({
+        "txFeePerByte": 0,
+        "txFeeFixed": 0,
+        "maxBlockBodySize": 98_304_000u64,
+        "maxTxSize": 4_294_967_295u64,
+        "maxBlockHeaderSize": 1100,
+        "stakeAddressDeposit": 0,
+        "stakePoolDeposit": 0,
+        "minPoolCost": 0,
+        "executionUnitPrices": { "priceMemory": 0.0, "priceSteps": 0.0 },
+        "maxTxExecutionUnits": {
+            "memory": 16_000_000_000u64,
+            "steps": 10_000_000_000_000u64,
+        },
+        "maxBlockExecutionUnits": {
+            "memory": 80_000_000_000u64,
+            "steps": 40_000_000_000_000u64,
+        },
+        "maxValueSize": 5000,
+        "collateralPercentage": 150,
+        "maxCollateralInputs": 3,
+        "utxoCostPerByte": 0,
+        "minFeeRefScriptCostPerByte": 0,
+        "costModels": cost_models,
+    })
+}
+
+/// Merge `overrides` into `base`, recursing into objects present in both
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Protocol parameters and cost models of the Hydra head, which txs are built and
+/// evaluated against
+#[derive(Debug, Clone)]
+pub struct HydraParams {
+    pub source: String,
+    pub parameters: ProtocolParameters,
+    pub protocol: Protocol,
+    /// Plutus V1, V2 and V3 models; languages the head has no model for get the
+    /// evaluator's
+    pub cost_models: Vec<Vec<i64>>,
+}
+
+impl HydraParams {
+    /// Read `protocol-parameters.json` from `path`, or use the built-in parameters when
+    /// no path is set, then apply `overrides` (keyed like the file)
+    pub fn load(path: Option<&str>, overrides: &toml::Table) -> Result<Self, WError> {
+        let (source, mut parameters) = match path {
+            Some(path) => {
+                let json = fs::read_to_string(path).map_err(|e| {
+                    WError::new(
+                        "HydraParams - load",
+                        &format!("Failed to read {}: {}", path, e),
+                    )
+                })?;
+                let parameters = serde_json::from_str(&json).map_err(|e| {
+                    WError::new(
+                        "HydraParams - load",
+                        &format!("Invalid protocol parameters {}: {}", path, e),
+                    )
+                })?;
+                (path.to_string(), parameters)
+            }
+            None => (BUILT_IN_SOURCE.to_string(), built_in_parameters()),
+        };
+        let source = match overrides.is_empty() {
+            true => source,
+            false => format!("{} with config overrides", source),
+        };
+        let overrides = serde_json::to_value(overrides)
+            .map_err(WError::from_err("HydraParams - load overrides"))?;
+        merge(&mut parameters, overrides);
+        Self::from_json(&source, parameters)
+    }
+
+    pub fn from_json(source: &str, json: Value) -> Result<Self, WError> {
+        let parameters: ProtocolParameters = serde_json::from_value(json).map_err(|e| {
+            WError::new(
+                "HydraParams - from_json",
+                &format!("Invalid protocol parameters {}: {}", source, e),
+            )
+        })?;
+        let cost_models = PLUTUS_LANGUAGES
+            .into_iter()
+            .zip(get_mainnet_cost_models())
+            .map(|(language, evaluator_model)| {
+                parameters
+                    .cost_models
+                    .get(language)
+                    .cloned()
+                    .unwrap_or(evaluator_model)
+            })
+            .collect();
+        Ok(HydraParams {
+            source: source.to_string(),
+            protocol: to_protocol(&parameters),
+            parameters,
+            cost_models,
+        })
+    }
+
+    /// Parameters the evaluator cannot apply as the head does, or that no script tx
+    /// could meet
+    pub fn errors(&self) -> Vec<String> {
+        let parameters = &self.parameters;
+        let mut errors = Vec::new();
+        if !parameters.cost_models.contains_key(SCRIPT_LANGUAGE) {
+            errors.push(format!(
+                "costModels has no {} model, which hibiki's scripts use",
+                SCRIPT_LANGUAGE
+            ));
+        }
+        for (language, evaluator_model) in
+            PLUTUS_LANGUAGES.into_iter().zip(get_mainnet_cost_models())
+        {
+            if let Some(model) = parameters.cost_models.get(language) {
+                if model.len() != evaluator_model.len() {
+                    errors.push(format!(
+                        "{} cost model has {} parameters, the evaluator expects {}",
+                        language,
+                        model.len(),
+                        evaluator_model.len()
+                    ));
+                }
+            }
+        }
+        let tx_units = &parameters.max_tx_execution_units;
+        if tx_units.memory == 0 || tx_units.steps == 0 {
+            errors.push("maxTxExecutionUnits is zero, no script could run".to_string());
+        }
+        errors
+    }
+}
+
+impl Default for HydraParams {
+    fn default() -> Self {
+        Self::from_json(BUILT_IN_SOURCE, built_in_parameters())
+            .expect("Built-in protocol parameters are invalid")
+    }
+}
+
+fn to_protocol(parameters: &ProtocolParameters) -> Protocol {
     Protocol {
         epoch: 0,
-        min_fee_a: 0,
-        min_fee_b: 0,
-        max_block_size: 98_304_000,
-        max_tx_size: 4_294_967_295,
-        max_block_header_size: 1100,
-        key_deposit: 0,
-        pool_deposit: 0,
-        min_pool_cost: "0".to_string(),
-        price_mem: 0.0,
-        price_step: 0.0,
-        max_tx_ex_mem: "16000000000".to_string(),
-        max_tx_ex_steps: "10000000000000".to_string(),
-        max_block_ex_mem: "80000000000".to_string(),
-        max_block_ex_steps: "40000000000000".to_string(),
-        max_val_size: 5000,
-        collateral_percent: 150.0,
-        max_collateral_inputs: 3,
-        coins_per_utxo_size: 0,
-        min_fee_ref_script_cost_per_byte: 0,
+        min_fee_a: parameters.tx_fee_per_byte as _,
+        min_fee_b: parameters.tx_fee_fixed as _,
+        max_block_size: parameters.max_block_body_size as _,
+        max_tx_size: parameters.max_tx_size as _,
+        max_block_header_size: parameters.max_block_header_size as _,
+        key_deposit: parameters.stake_address_deposit as _,
+        pool_deposit: parameters.stake_pool_deposit as _,
+        min_pool_cost: parameters.min_pool_cost.to_string(),
+        price_mem: parameters.execution_unit_prices.price_memory as _,
+        price_step: parameters.execution_unit_prices.price_steps as _,
+        max_tx_ex_mem: parameters.max_tx_execution_units.memory.to_string(),
+        max_tx_ex_steps: parameters.max_tx_execution_units.steps.to_string(),
+        max_block_ex_mem: parameters.max_block_execution_units.memory.to_string(),
+        max_block_ex_steps: parameters.max_block_execution_units.steps.to_string(),
+        max_val_size: parameters.max_value_size as _,
+        collateral_percent: parameters.collateral_percentage as _,
+        max_collateral_inputs: parameters.max_collateral_inputs as _,
+        coins_per_utxo_size: parameters.utxo_cost_per_byte as _,
+        min_fee_ref_script_cost_per_byte: parameters.min_fee_ref_script_cost_per_byte as _,
         decentralisation: 0.0,
     }
 }
 
+static HYDRA_PARAMS: OnceLock<HydraParams> = OnceLock::new();
+
+/// Install the parameters loaded at startup, before any tx is built
+pub fn init_hydra_params(params: HydraParams) -> &'static HydraParams {
+    if HYDRA_PARAMS.set(params).is_err() {
+        eprintln!("Warning: Hydra parameters already initialized, keeping the first ones");
+    }
+    hydra_params()
+}
+
+/// The parameters installed at startup, else the built-in ones
+pub fn hydra_params() -> &'static HydraParams {
+    HYDRA_PARAMS.get_or_init(HydraParams::default)
+}
+
+pub fn get_hydra_pp() -> Protocol {
+    hydra_params().protocol.clone()
+}
+
 pub fn get_hydra_cost_model() -> Vec<Vec<i64>> {
-    get_mainnet_cost_models()
+    hydra_params().cost_models.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head_parameters() -> Value {
+        let mut parameters = built_in_parameters();
+        parameters["maxTxSize"] = json!(16384);
+        parameters["maxTxExecutionUnits"] = json!({ "memory": 14000000, "steps": 10000000000u64 });
+        parameters
+    }
+
+    #[test]
+    fn test_built_in_parameters_pass() {
+        let params = HydraParams::default();
+        assert_eq!(params.source, BUILT_IN_SOURCE);
+        assert_eq!(params.errors(), Vec::<String>::new());
+        assert_eq!(params.cost_models, get_mainnet_cost_models());
+        assert_eq!(params.protocol.max_tx_ex_mem, "16000000000");
+        assert_eq!(params.protocol.max_block_ex_steps, "40000000000000");
+        assert_eq!(params.protocol.min_pool_cost, "0");
+    }
+
+    #[test]
+    fn test_load_file_with_overrides() {
+        let path = std::env::temp_dir().join(format!(
+            "hibiki-test-protocol-parameters-{}.json",
+            std::process::id()
+        ));
+        fs::write(&path, head_parameters().to_string()).unwrap();
+        let overrides: toml::Table =
+            toml::from_str("maxValueSize = 6000\n[maxTxExecutionUnits]\nsteps = 20000000000\n")
+                .unwrap();
+
+        let params = HydraParams::load(path.to_str(), &overrides).unwrap();
+        assert!(params.source.ends_with("with config overrides"));
+        assert_eq!(params.parameters.max_tx_size, 16384);
+        assert_eq!(params.parameters.max_value_size, 6000);
+        // Overriding one field of a nested object keeps the others from the file
+        assert_eq!(params.protocol.max_tx_ex_mem, "14000000");
+        assert_eq!(params.protocol.max_tx_ex_steps, "20000000000");
+        assert!(
+            HydraParams::load(Some("/nonexistent/protocol-parameters.json"), &overrides).is_err()
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut parameters = head_parameters();
+        parameters["costModels"] = json!({ "PlutusV2": [1, 2, 3] });
+        parameters["maxTxExecutionUnits"] = json!({ "memory": 0, "steps": 10000000000u64 });
+        let params = HydraParams::from_json("test.json", parameters).unwrap();
+        // Languages missing from the head fall back to the evaluator's models
+        assert_eq!(params.cost_models[0], get_mainnet_cost_models()[0]);
+        assert_eq!(params.cost_models[1], vec![1, 2, 3]);
+        assert_eq!(
+            params.errors(),
+            vec![
+                "costModels has no PlutusV3 model, which hibiki's scripts use".to_string(),
+                format!(
+                    "PlutusV2 cost model has 3 parameters, the evaluator expects {}",
+                    get_mainnet_cost_models()[1].len()
+                ),
+                "maxTxExecutionUnits is zero, no script could run".to_string(),
+            ]
+        );
+    }
 }
@@ -1,60 +1,500 @@
-use std::env::var;
+use serde::Deserialize;
+use std::{collections::BTreeMap, env::var, fs, str::FromStr, time::Duration};
+use whisky::{NetworkId, WError};
 
-pub mod gcp_secret_manager;
+use crate::{
+    config::{
+        deployment::{DeploymentConfig, DEFAULT_DEPLOYMENT_ID},
+        signer::{SignerBackend, SignerSettings},
+        signing_keys::{
+            signing_key_errors, Derivation, SigningKeyConfig, APP_OWNER_PREFIX,
+            FEE_COLLECTOR_PREFIX,
+        },
+    },
+    hydra_node::SubmitPolicy,
+    secret::{secrets, SecretProviderKind},
+    utils::{built_tx_registry::SignBindingMode, token_registry::TokenEntry},
+};
+
+pub mod deployment;
 pub mod hydra;
+pub mod self_check;
+pub mod signer;
+pub mod signing_keys;
+pub mod signing_policy;
+
+/// Env vars each adding one token unit, ticker taken from the name, kept from before the
+/// token registry
+const LEGACY_TOKEN_UNIT_VARS: [&str; 5] = [
+    "USDM_UNIT",
+    "NIGHT_UNIT",
+    "IAG_UNIT",
+    "SNEK_UNIT",
+    "HOSKY_UNIT",
+];
 
+/// Service configuration, loaded and validated once at startup (see [`AppConfig::load`])
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AppConfig {
-    pub network_id: String,
-    pub app_owner_mnemonic: String,
+    /// 0 for testnets, 1 for mainnet
+    pub network_id: u8,
     pub app_owner_vkey: String,
-    pub fee_collector_mnemonic: String,
     pub dex_oracle_nft: String,
+    pub port: u16,
+    pub metrics_port: u16,
+    /// Inline datum of the oracle UTxO, checked against `app_owner_vkey` at startup
+    pub oracle_datum_cbor: Option<String>,
+    /// Where the token registry persists runtime changes; they are lost on restart when unset
+    pub token_registry_path: Option<String>,
+    /// Tokens the registry starts with when no registry file exists yet
+    pub tokens: Vec<TokenEntry>,
+    /// `plutus.json` to build the scripts from; the compiled-in copy when unset
+    pub plutus_json_path: Option<String>,
+    /// The head's `protocol-parameters.json`; built-in parameters when unset
+    pub hydra_protocol_parameters_path: Option<String>,
+    /// Protocol parameters overriding those of the file, keyed like it
+    pub hydra_protocol_parameters: toml::Table,
+    /// WebSocket API of the head's hydra-node; txs cannot be submitted when unset
+    pub hydra_node_url: Option<String>,
+    /// How long `SubmitAndConfirmTx` waits for a snapshot when the request sets no timeout
+    pub hydra_confirm_timeout_ms: u64,
+    /// Resubmit a tx the head has not answered for this long
+    pub hydra_resubmit_interval_ms: u64,
+    pub hydra_max_submit_attempts: u32,
+    /// Where the head's UTxO index persists the last snapshot, so it is known before the
+    /// hydra-node replays its history; kept in memory only when unset
+    pub hydra_utxo_index_path: Option<String>,
+    /// Which transactions the app owner key may sign: `off`, `registry` or `token`
+    pub sign_binding_mode: SignBindingMode,
+    /// How long a built tx stays signable, and its build token valid
+    pub sign_binding_ttl_secs: u64,
+    /// Key of the build tokens, shared by every instance; required in `token` mode
+    pub sign_binding_hmac_key: Option<String>,
+    /// Hash-chained log of every signing attempt, opened at startup
+    pub signing_audit_log_path: Option<String>,
+    /// Sign without an audit log; startup fails when neither this nor the path is set
+    pub signing_audit_log_disabled: bool,
+    /// The only addresses txs signed by the fee collector key may pay to
+    pub fee_collector_treasury_addresses: Vec<String>,
+    /// Let the fee collector key sign txs spending script UTxOs
+    pub fee_collector_allow_script_inputs: bool,
+    /// Let the fee collector key sign txs that mint, withdraw, certify, vote or propose
+    pub fee_collector_allow_ledger_actions: bool,
+    /// Where each key lives, keyed by env prefix (`APP_OWNER`, `FEE_COLLECTOR`, ...);
+    /// keys not listed use the mnemonic backend
+    pub signers: BTreeMap<String, SignerSettings>,
+    /// Refuse the plaintext signer backends (mnemonic, root_key, cli_skey) for every key
+    pub require_encrypted_keys: bool,
+    /// After a rotation, keep signing with the previous key for this long
+    pub signing_key_grace_period_secs: u64,
+    /// Signing roles besides the app owner and fee collector
+    pub signing_keys: Vec<SigningKeyConfig>,
+    /// Where seed phrases and passphrases are read from, first match wins
+    pub secret_providers: Vec<SecretProviderKind>,
+    /// Tries of each secret lookup, retried with exponential backoff
+    pub secret_provider_attempts: u32,
+    /// Token admin RPCs must present in `x-hibiki-admin-token`; admin RPCs are disabled when unset
+    pub admin_api_token: Option<String>,
+    /// Id of the deployment described by the settings above
+    pub deployment_id: String,
+    /// Further deployments served by the same process, selected per request
+    pub deployments: Vec<DeploymentConfig>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            network_id: 0,
+            app_owner_vkey: String::new(),
+            dex_oracle_nft: String::new(),
+            port: 50051,
+            metrics_port: 9090,
+            oracle_datum_cbor: None,
+            token_registry_path: None,
+            tokens: Vec::new(),
+            plutus_json_path: None,
+            hydra_protocol_parameters_path: None,
+            hydra_protocol_parameters: toml::Table::new(),
+            hydra_node_url: None,
+            hydra_confirm_timeout_ms: 30_000,
+            hydra_resubmit_interval_ms: 5_000,
+            hydra_max_submit_attempts: 3,
+            hydra_utxo_index_path: None,
+            sign_binding_mode: SignBindingMode::Off,
+            sign_binding_ttl_secs: 300,
+            sign_binding_hmac_key: None,
+            signing_audit_log_path: None,
+            signing_audit_log_disabled: false,
+            fee_collector_treasury_addresses: Vec::new(),
+            fee_collector_allow_script_inputs: false,
+            fee_collector_allow_ledger_actions: false,
+            signers: BTreeMap::new(),
+            require_encrypted_keys: false,
+            signing_key_grace_period_secs: 0,
+            signing_keys: Vec::new(),
+            secret_providers: vec![SecretProviderKind::Env, SecretProviderKind::Gcp],
+            secret_provider_attempts: 3,
+            admin_api_token: None,
+            deployment_id: DEFAULT_DEPLOYMENT_ID.to_string(),
+            deployments: Vec::new(),
+        }
+    }
 }
 
 impl AppConfig {
-    pub fn new() -> AppConfig {
-        let network_id = var("NETWORK_ID").unwrap_or("0".to_string());
-
-        let app_owner_vkey = var("OWNER_VKEY").unwrap_or("".to_string());
-
-        let app_owner_mnemonic = match var("APP_OWNER_SEED_PHRASE") {
-            Ok(phrase) => convert_mnemonic_comma_to_space(&phrase),
-            Err(_) => match gcp_secret_manager::get_app_owner_seed_phrase() {
-                Ok(phrase) => convert_mnemonic_comma_to_space(&phrase),
-                Err(e) => {
-                    eprintln!("Failed to get APP_OWNER_SEED_PHRASE: {}", e);
-                    panic!("APP_OWNER_SEED_PHRASE not found in environment or Secret Manager");
-                }
-            },
+    /// Read the TOML file at `HIBIKI_CONFIG` (defaults when unset), apply env overrides
+    /// and validate
+    pub fn load() -> Result<AppConfig, String> {
+        let mut config = match var("HIBIKI_CONFIG") {
+            Ok(path) => {
+                let toml = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+                Self::from_toml(&toml)?
+            }
+            Err(_) => AppConfig::default(),
         };
+        config.apply_overrides(|name| var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<AppConfig, String> {
+        toml::from_str(toml).map_err(|e| format!("Invalid config file: {}", e))
+    }
 
-        let fee_collector_mnemonic = match var("FEE_COLLECTOR_SEED_PHRASE") {
-            Ok(phrase) => convert_mnemonic_comma_to_space(&phrase),
-            Err(_) => match gcp_secret_manager::get_fee_collector_seed_phrase() {
-                Ok(phrase) => convert_mnemonic_comma_to_space(&phrase),
-                Err(e) => {
-                    eprintln!("Failed to get FEE_COLLECTOR_SEED_PHRASE: {}", e);
-                    panic!("FEE_COLLECTOR_SEED_PHRASE not found in environment or Secret Manager");
+    /// Override file values with the env vars of the same setting, e.g. `NETWORK_ID`
+    pub fn apply_overrides(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), String> {
+        fn parse<T: FromStr>(name: &str, value: String) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("{} has an invalid value: {}", name, value))
+        }
+
+        if let Some(value) = lookup("NETWORK_ID") {
+            self.network_id = parse("NETWORK_ID", value)?;
+        }
+        if let Some(value) = lookup("PORT") {
+            self.port = parse("PORT", value)?;
+        }
+        if let Some(value) = lookup("METRICS_PORT") {
+            self.metrics_port = parse("METRICS_PORT", value)?;
+        }
+        if let Some(value) = lookup("HYDRA_CONFIRM_TIMEOUT_MS") {
+            self.hydra_confirm_timeout_ms = parse("HYDRA_CONFIRM_TIMEOUT_MS", value)?;
+        }
+        if let Some(value) = lookup("HYDRA_RESUBMIT_INTERVAL_MS") {
+            self.hydra_resubmit_interval_ms = parse("HYDRA_RESUBMIT_INTERVAL_MS", value)?;
+        }
+        if let Some(value) = lookup("HYDRA_MAX_SUBMIT_ATTEMPTS") {
+            self.hydra_max_submit_attempts = parse("HYDRA_MAX_SUBMIT_ATTEMPTS", value)?;
+        }
+        if let Some(value) = lookup("SIGN_BINDING_MODE") {
+            self.sign_binding_mode = parse("SIGN_BINDING_MODE", value)?;
+        }
+        if let Some(value) = lookup("SIGN_BINDING_TTL_SECS") {
+            self.sign_binding_ttl_secs = parse("SIGN_BINDING_TTL_SECS", value)?;
+        }
+        if let Some(value) = lookup("SIGNING_AUDIT_LOG_DISABLED") {
+            self.signing_audit_log_disabled = parse("SIGNING_AUDIT_LOG_DISABLED", value)?;
+        }
+        if let Some(value) = lookup("FEE_COLLECTOR_ALLOW_SCRIPT_INPUTS") {
+            self.fee_collector_allow_script_inputs =
+                parse("FEE_COLLECTOR_ALLOW_SCRIPT_INPUTS", value)?;
+        }
+        if let Some(value) = lookup("FEE_COLLECTOR_ALLOW_LEDGER_ACTIONS") {
+            self.fee_collector_allow_ledger_actions =
+                parse("FEE_COLLECTOR_ALLOW_LEDGER_ACTIONS", value)?;
+        }
+        if let Some(value) = lookup("REQUIRE_ENCRYPTED_KEYS") {
+            self.require_encrypted_keys = parse("REQUIRE_ENCRYPTED_KEYS", value)?;
+        }
+        if let Some(value) = lookup("SIGNING_KEY_GRACE_PERIOD_SECS") {
+            self.signing_key_grace_period_secs = parse("SIGNING_KEY_GRACE_PERIOD_SECS", value)?;
+        }
+        if let Some(value) = lookup("SECRET_PROVIDER_ATTEMPTS") {
+            self.secret_provider_attempts = parse("SECRET_PROVIDER_ATTEMPTS", value)?;
+        }
+        if let Some(value) = lookup("SECRET_PROVIDERS") {
+            self.secret_providers = value
+                .split(',')
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(|name| parse("SECRET_PROVIDERS", name.to_string()))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(value) = lookup("FEE_COLLECTOR_TREASURY_ADDRESSES") {
+            self.fee_collector_treasury_addresses = value
+                .split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| !address.is_empty())
+                .collect();
+        }
+        let strings = [
+            ("OWNER_VKEY", &mut self.app_owner_vkey),
+            ("DEX_ORACLE_NFT", &mut self.dex_oracle_nft),
+        ];
+        for (name, field) in strings {
+            if let Some(value) = lookup(name) {
+                *field = value;
+            }
+        }
+        if let Some(value) = lookup("ORACLE_DATUM_CBOR") {
+            self.oracle_datum_cbor = Some(value);
+        }
+        if let Some(value) = lookup("PLUTUS_JSON_PATH") {
+            self.plutus_json_path = Some(value);
+        }
+        if let Some(value) = lookup("HYDRA_PROTOCOL_PARAMETERS_PATH") {
+            self.hydra_protocol_parameters_path = Some(value);
+        }
+        if let Some(value) = lookup("HYDRA_NODE_URL") {
+            self.hydra_node_url = Some(value);
+        }
+        if let Some(value) = lookup("HYDRA_UTXO_INDEX_PATH") {
+            self.hydra_utxo_index_path = Some(value);
+        }
+        if let Some(value) = lookup("SIGNING_AUDIT_LOG_PATH") {
+            self.signing_audit_log_path = Some(value);
+        }
+        if let Some(value) = lookup("DEPLOYMENT_ID") {
+            self.deployment_id = value;
+        }
+        // Empty values keep the old meaning of an unset var
+        let shared_secrets = [
+            ("SIGN_BINDING_HMAC_KEY", &mut self.sign_binding_hmac_key),
+            ("ADMIN_API_TOKEN", &mut self.admin_api_token),
+        ];
+        for (name, field) in shared_secrets {
+            if let Some(value) = lookup(name) {
+                *field = Some(value).filter(|value| !value.is_empty());
+            }
+        }
+        for prefix in self.signer_prefixes() {
+            let name = |setting: &str| format!("{}_{}", prefix, setting);
+            let signer = self.signers.entry(prefix.clone()).or_default();
+            if let Some(value) = lookup(&name("SIGNER_BACKEND")) {
+                signer.backend = parse(&name("SIGNER_BACKEND"), value)?;
+            }
+            if let Some(value) = lookup(&name("MASTER_KEY")) {
+                signer.master_key = Some(parse(&name("MASTER_KEY"), value)?);
+            }
+            let paths = [
+                ("KEYSTORE_PATH", &mut signer.keystore_path),
+                ("SKEY_PATH", &mut signer.skey_path),
+                ("REMOTE_SIGNER_URL", &mut signer.remote_signer_url),
+                ("REMOTE_SIGNER_TOKEN", &mut signer.remote_signer_token),
+                ("DERIVATION_PATH", &mut signer.derivation_path),
+            ];
+            for (setting, field) in paths {
+                if let Some(value) = lookup(&name(setting)) {
+                    *field = Some(value);
                 }
-            },
-        };
+            }
+        }
+        if let Some(value) = lookup("TOKEN_REGISTRY_PATH") {
+            self.token_registry_path = Some(value);
+        }
+        for name in LEGACY_TOKEN_UNIT_VARS {
+            if let Some(unit) = lookup(name) {
+                if !self.tokens.iter().any(|token| token.unit == unit) {
+                    self.tokens.push(TokenEntry {
+                        unit,
+                        ticker: name.trim_end_matches("_UNIT").to_string(),
+                        ..TokenEntry::default()
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every problem with the config, reported together
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if self.network_id > 1 {
+            errors.push(format!(
+                "network_id {} is invalid (expected 0 or 1)",
+                self.network_id
+            ));
+        }
+        if let Some(url) = &self.hydra_node_url {
+            if !url.starts_with("ws://") && !url.starts_with("wss://") {
+                errors.push(format!(
+                    "hydra_node_url {} is not a ws:// or wss:// URL",
+                    url
+                ));
+            }
+        }
+        if self.hydra_max_submit_attempts == 0 {
+            errors.push("hydra_max_submit_attempts must be at least 1".to_string());
+        }
+        match self.sign_binding_hmac_key.as_deref() {
+            None if self.sign_binding_mode == SignBindingMode::RegistryOrToken => errors.push(
+                "sign_binding_hmac_key must be set when sign_binding_mode is token".to_string(),
+            ),
+            Some("") => errors.push("sign_binding_hmac_key must not be empty".to_string()),
+            _ => {}
+        }
+        if self.admin_api_token.as_deref() == Some("") {
+            errors.push(
+                "admin_api_token must not be empty (leave it unset to disable admin RPCs)"
+                    .to_string(),
+            );
+        }
+        if self.secret_providers.is_empty() {
+            errors.push("secret_providers must list at least one provider".to_string());
+        }
+        if self.secret_provider_attempts == 0 {
+            errors.push("secret_provider_attempts must be at least 1".to_string());
+        }
+        errors.extend(signing_key_errors(&self.signing_keys));
+        let prefixes = self.signer_prefixes();
+        for prefix in &prefixes {
+            if let Err(e) = self.signer_backend(prefix) {
+                errors.push(e);
+            }
+            if let Err(e) = self.derivation(prefix, Derivation::default()) {
+                errors.push(format!("signers.{}.derivation_path: {}", prefix, e));
+            }
+        }
+        for prefix in self.signers.keys() {
+            if !prefixes.contains(prefix) {
+                errors.push(format!("signers.{} is not the prefix of any key", prefix));
+            }
+        }
+        let deployments = self.deployments();
+        for (index, deployment) in deployments.iter().enumerate() {
+            let prefix = if index == 0 {
+                String::new()
+            } else {
+                format!("deployments.{}: ", deployment.id)
+            };
+            errors.extend(
+                deployment
+                    .errors()
+                    .into_iter()
+                    .map(|error| format!("{}{}", prefix, error)),
+            );
+            if deployments[..index]
+                .iter()
+                .any(|other| other.id == deployment.id)
+            {
+                errors.push(format!("deployment id {} is used twice", deployment.id));
+            }
+            if let Some(path) = &deployment.token_registry_path {
+                if deployments[..index]
+                    .iter()
+                    .any(|other| other.token_registry_path.as_ref() == Some(path))
+                {
+                    errors.push(format!(
+                        "{}token_registry_path {} is shared with another deployment",
+                        prefix, path
+                    ));
+                }
+            }
+        }
 
-        let dex_oracle_nft = var("DEX_ORACLE_NFT").unwrap_or("".to_string());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid configuration:\n  {}", errors.join("\n  ")))
+        }
+    }
 
-        AppConfig {
-            network_id,
-            app_owner_mnemonic,
-            app_owner_vkey,
-            fee_collector_mnemonic,
-            dex_oracle_nft,
+    /// Every deployment, the one described by the top-level settings first
+    ///
+    /// Deployments without their own `tokens` get the top-level ones.
+    pub fn deployments(&self) -> Vec<DeploymentConfig> {
+        let mut deployments = vec![DeploymentConfig {
+            id: self.deployment_id.clone(),
+            dex_oracle_nft: self.dex_oracle_nft.clone(),
+            app_owner_vkey: self.app_owner_vkey.clone(),
+            key_prefix: None,
+            oracle_datum_cbor: self.oracle_datum_cbor.clone(),
+            token_registry_path: self.token_registry_path.clone(),
+            tokens: self.tokens.clone(),
+        }];
+        for deployment in &self.deployments {
+            let mut deployment = deployment.clone();
+            if deployment.tokens.is_empty() {
+                deployment.tokens = self.tokens.clone();
+            }
+            deployments.push(deployment);
         }
+        deployments
     }
-}
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self::new()
+    /// How `SubmitAndConfirmTx` waits for and resubmits txs
+    pub fn submit_policy(&self) -> SubmitPolicy {
+        SubmitPolicy {
+            timeout: Duration::from_millis(self.hydra_confirm_timeout_ms),
+            resubmit_interval: Duration::from_millis(self.hydra_resubmit_interval_ms),
+            max_attempts: self.hydra_max_submit_attempts,
+        }
     }
+
+    /// Env prefix of every key: the built-in ones, those of deployments with their own keys
+    /// and the `signing_keys`
+    pub fn signer_prefixes(&self) -> Vec<String> {
+        let mut prefixes = vec![
+            APP_OWNER_PREFIX.to_string(),
+            FEE_COLLECTOR_PREFIX.to_string(),
+        ];
+        for deployment in &self.deployments {
+            if let Some(prefix) = &deployment.key_prefix {
+                prefixes.push(format!("{}_{}", prefix, APP_OWNER_PREFIX));
+                prefixes.push(format!("{}_{}", prefix, FEE_COLLECTOR_PREFIX));
+            }
+        }
+        prefixes.extend(self.signing_keys.iter().map(|key| key.env_prefix()));
+        prefixes
+    }
+
+    /// Backend of the key with env prefix `prefix`
+    pub fn signer_backend(&self, prefix: &str) -> Result<SignerBackend, String> {
+        self.signers
+            .get(prefix)
+            .cloned()
+            .unwrap_or_default()
+            .backend(prefix, self.require_encrypted_keys)
+    }
+
+    /// `derivation` of the key with env prefix `prefix`, with its `[signers]` overrides
+    pub fn derivation(&self, prefix: &str, derivation: Derivation) -> Result<Derivation, String> {
+        match self.signers.get(prefix) {
+            Some(signer) => signer.derivation(derivation),
+            None => Ok(derivation),
+        }
+    }
+
+    pub fn signing_key_grace_period(&self) -> Duration {
+        Duration::from_secs(self.signing_key_grace_period_secs)
+    }
+
+    /// Network for wallets and addresses
+    pub fn network(&self) -> NetworkId {
+        match self.network_id {
+            1 => NetworkId::Mainnet,
+            _ => NetworkId::Preprod,
+        }
+    }
+}
+
+/// Keep the config loaded at startup for the rest of the process
+pub fn init_app_config(config: AppConfig) -> &'static AppConfig {
+    Box::leak(Box::new(config))
+}
+
+/// Read `<PREFIX>_SEED_PHRASE` from the configured secret providers
+///
+/// Only needed for keys using the mnemonic backend.
+pub async fn get_mnemonic(prefix: &str) -> Result<String, WError> {
+    let phrase = secrets().get(&format!("{}_SEED_PHRASE", prefix)).await?;
+    Ok(convert_mnemonic_comma_to_space(&phrase))
 }
 
 fn convert_mnemonic_comma_to_space(mnemonic: &str) -> String {
@@ -63,6 +503,364 @@ fn convert_mnemonic_comma_to_space(mnemonic: &str) -> String {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::config::signing_keys::MasterKeyScheme;
+
+    const CONFIG_TOML: &str = r#"
+        network_id = 1
+        app_owner_vkey = "fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c"
+        dex_oracle_nft = "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d"
+        port = 50062
+
+        [[tokens]]
+        unit = "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d"
+        ticker = "USDM"
+        decimals = 6
+        min_transfer_amount = 1000000
+
+        [[tokens]]
+        unit = "378f9732c755ed6f4fc8d406f1461d0cca95d7d2e69416784684df39534e454b"
+    "#;
+
+    #[test]
+    fn test_config_file_with_env_overrides() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        assert_eq!(config.port, 50062);
+        assert_eq!(config.metrics_port, 9090);
+        assert_eq!(config.network(), NetworkId::Mainnet);
+
+        config
+            .apply_overrides(|name| match name {
+                "NETWORK_ID" => Some("0".to_string()),
+                "SNEK_UNIT" => Some(
+                    "378f9732c755ed6f4fc8d406f1461d0cca95d7d2e69416784684df39534e454b".to_string(),
+                ),
+                "HOSKY_UNIT" => Some(
+                    "a2818ba06a88bb6c08d10f4f9b897c09768f28d274093628ad7086fc484f534b59"
+                        .to_string(),
+                ),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.network_id, 0);
+        // Legacy unit vars only add tokens the file does not list
+        assert_eq!(config.tokens.len(), 3);
+        assert_eq!(config.tokens[0].decimals, 6);
+        assert_eq!(config.tokens[2].ticker, "HOSKY");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_validation_reports_every_error() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        config.network_id = 3;
+        config.tokens[1].unit = "lovelace".to_string();
+        config.tokens.push(config.tokens[0].clone());
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("network_id 3"));
+        assert!(error.contains("tokens[1]"));
+        assert!(error.contains("tokens[2]"));
+
+        assert!(config
+            .apply_overrides(|name| (name == "PORT").then(|| "http".to_string()))
+            .is_err());
+        assert!(AppConfig::from_toml("network = 1").is_err());
+    }
+
+    #[test]
+    fn test_hydra_protocol_parameters_overrides() {
+        let toml = format!(
+            "hydra_protocol_parameters_path = \"protocol-parameters.json\"\n{}\n[hydra_protocol_parameters.maxTxExecutionUnits]\nmemory = 14000000\n",
+            CONFIG_TOML
+        );
+        let mut config = AppConfig::from_toml(&toml).unwrap();
+        assert_eq!(
+            config.hydra_protocol_parameters["maxTxExecutionUnits"]["memory"].as_integer(),
+            Some(14000000)
+        );
+        config
+            .apply_overrides(|name| {
+                (name == "HYDRA_PROTOCOL_PARAMETERS_PATH").then(|| "/etc/hydra/pp.json".to_string())
+            })
+            .unwrap();
+        assert_eq!(
+            config.hydra_protocol_parameters_path.as_deref(),
+            Some("/etc/hydra/pp.json")
+        );
+    }
+
+    #[test]
+    fn test_hydra_node_settings() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        assert_eq!(config.submit_policy().max_attempts, 3);
+        config
+            .apply_overrides(|name| match name {
+                "HYDRA_NODE_URL" => Some("http://127.0.0.1:4001".to_string()),
+                "HYDRA_CONFIRM_TIMEOUT_MS" => Some("1500".to_string()),
+                "HYDRA_MAX_SUBMIT_ATTEMPTS" => Some("0".to_string()),
+                "HYDRA_UTXO_INDEX_PATH" => Some("/var/lib/hibiki/utxo.json".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.submit_policy().timeout, Duration::from_millis(1500));
+        assert_eq!(
+            config.hydra_utxo_index_path.as_deref(),
+            Some("/var/lib/hibiki/utxo.json")
+        );
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("hydra_node_url http://127.0.0.1:4001 is not a ws://"));
+        assert!(error.contains("hydra_max_submit_attempts"));
+    }
+
+    #[test]
+    fn test_sign_binding_settings() {
+        let toml = format!("sign_binding_mode = \"token\"\n{}", CONFIG_TOML);
+        let mut config = AppConfig::from_toml(&toml).unwrap();
+        assert_eq!(config.sign_binding_mode, SignBindingMode::RegistryOrToken);
+        assert_eq!(config.sign_binding_ttl_secs, 300);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("sign_binding_hmac_key must be set"));
+        config
+            .apply_overrides(|name| (name == "SIGN_BINDING_HMAC_KEY").then(|| "key".to_string()))
+            .unwrap();
+        assert_eq!(config.sign_binding_hmac_key.as_deref(), Some("key"));
+        assert!(config.validate().is_ok());
+        config
+            .apply_overrides(|name| match name {
+                "SIGN_BINDING_MODE" => Some("registry".to_string()),
+                "SIGN_BINDING_TTL_SECS" => Some("60".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.sign_binding_mode, SignBindingMode::Registry);
+        assert_eq!(config.sign_binding_ttl_secs, 60);
+
+        assert!(config
+            .apply_overrides(|name| (name == "SIGN_BINDING_MODE").then(|| "regsitry".to_string()))
+            .is_err());
+        let toml = format!("sign_binding_mode = \"on\"\n{}", CONFIG_TOML);
+        assert!(AppConfig::from_toml(&toml).is_err());
+    }
+
+    #[test]
+    fn test_signing_audit_log_settings() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        assert_eq!(config.signing_audit_log_path, None);
+        assert!(!config.signing_audit_log_disabled);
+        config
+            .apply_overrides(|name| match name {
+                "SIGNING_AUDIT_LOG_PATH" => Some("/var/log/hibiki/audit.jsonl".to_string()),
+                "SIGNING_AUDIT_LOG_DISABLED" => Some("true".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            config.signing_audit_log_path.as_deref(),
+            Some("/var/log/hibiki/audit.jsonl")
+        );
+        assert!(config.signing_audit_log_disabled);
+        assert!(config
+            .apply_overrides(
+                |name| (name == "SIGNING_AUDIT_LOG_DISABLED").then(|| "yes".to_string())
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_fee_collector_settings() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        assert!(config.fee_collector_treasury_addresses.is_empty());
+        assert!(!config.fee_collector_allow_script_inputs);
+        assert!(!config.fee_collector_allow_ledger_actions);
+        config
+            .apply_overrides(|name| match name {
+                "FEE_COLLECTOR_TREASURY_ADDRESSES" => {
+                    Some("addr_test1qa, addr_test1qb,".to_string())
+                }
+                "FEE_COLLECTOR_ALLOW_SCRIPT_INPUTS" => Some("true".to_string()),
+                "FEE_COLLECTOR_ALLOW_LEDGER_ACTIONS" => Some("true".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            config.fee_collector_treasury_addresses,
+            vec!["addr_test1qa", "addr_test1qb"]
+        );
+        assert!(config.fee_collector_allow_script_inputs);
+        assert!(config.fee_collector_allow_ledger_actions);
+
+        let config = AppConfig::from_toml(
+            "fee_collector_treasury_addresses = [\"addr_test1qa\"]\n\
+             fee_collector_allow_script_inputs = true",
+        )
+        .unwrap();
+        assert_eq!(
+            config.fee_collector_treasury_addresses,
+            vec!["addr_test1qa"]
+        );
+        assert!(config.fee_collector_allow_script_inputs);
+    }
+
+    #[test]
+    fn test_signer_settings() {
+        let toml = format!(
+            r#"{}
+            [signers.APP_OWNER]
+            backend = "keystore"
+            keystore_path = "/secrets/app_owner.keystore.json"
+            derivation_path = "m/1852'/1815'/1'/0/3"
+
+            [[signing_keys]]
+            role = "oracle_admin"
+            allowed_rpcs = ["*"]
+            "#,
+            CONFIG_TOML
+        );
+        let mut config = AppConfig::from_toml(&toml).unwrap();
+        assert_eq!(
+            config.signer_backend(APP_OWNER_PREFIX),
+            Ok(SignerBackend::Keystore {
+                path: "/secrets/app_owner.keystore.json".to_string()
+            })
+        );
+        assert_eq!(
+            config
+                .derivation(APP_OWNER_PREFIX, Derivation::default())
+                .unwrap()
+                .account_index,
+            1
+        );
+        assert_eq!(
+            config.signer_backend(FEE_COLLECTOR_PREFIX),
+            Ok(SignerBackend::Mnemonic)
+        );
+        assert!(config.validate().is_ok());
+
+        config
+            .apply_overrides(|name| match name {
+                "REQUIRE_ENCRYPTED_KEYS" => Some("true".to_string()),
+                "SIGNING_KEY_GRACE_PERIOD_SECS" => Some("600".to_string()),
+                "ORACLE_ADMIN_SIGNER_BACKEND" => Some("remote".to_string()),
+                "ORACLE_ADMIN_REMOTE_SIGNER_URL" => Some("http://signer:8080".to_string()),
+                "ORACLE_ADMIN_MASTER_KEY" => Some("ledger".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.signing_key_grace_period(), Duration::from_secs(600));
+        assert_eq!(
+            config.signers["ORACLE_ADMIN"].master_key,
+            Some(MasterKeyScheme::Ledger)
+        );
+        let error = config.validate().unwrap_err();
+        // The fee collector still uses a mnemonic
+        assert!(error.contains("signers.FEE_COLLECTOR uses a plaintext backend"));
+        assert!(!error.contains("signers.APP_OWNER"));
+        assert!(!error.contains("signers.ORACLE_ADMIN"));
+
+        let mut config = AppConfig::from_toml(&toml).unwrap();
+        config
+            .signers
+            .insert("ORACLE_ADMN".to_string(), SignerSettings::default());
+        config
+            .signers
+            .get_mut(APP_OWNER_PREFIX)
+            .unwrap()
+            .keystore_path = None;
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("signers.APP_OWNER.keystore_path must be set"));
+        assert!(error.contains("signers.ORACLE_ADMN is not the prefix of any key"));
+
+        for (name, value) in [
+            ("APP_OWNER_SIGNER_BACKEND", "hsm"),
+            ("FEE_COLLECTOR_MASTER_KEY", "trezor"),
+            ("SIGNING_KEY_GRACE_PERIOD_SECS", "10m"),
+            ("REQUIRE_ENCRYPTED_KEYS", "yes"),
+        ] {
+            assert!(config
+                .apply_overrides(|lookup| (lookup == name).then(|| value.to_string()))
+                .is_err());
+        }
+        let toml = format!("{}\n[signers.APP_OWNER]\nbackend = \"hsm\"", CONFIG_TOML);
+        assert!(AppConfig::from_toml(&toml).is_err());
+    }
+
+    #[test]
+    fn test_secret_provider_and_admin_settings() {
+        let mut config = AppConfig::from_toml(CONFIG_TOML).unwrap();
+        assert_eq!(
+            config.secret_providers,
+            vec![SecretProviderKind::Env, SecretProviderKind::Gcp]
+        );
+        assert_eq!(config.secret_provider_attempts, 3);
+        assert_eq!(config.admin_api_token, None);
+        config
+            .apply_overrides(|name| match name {
+                "SECRET_PROVIDERS" => Some("file, vault".to_string()),
+                "SECRET_PROVIDER_ATTEMPTS" => Some("5".to_string()),
+                "ADMIN_API_TOKEN" => Some("admin".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            config.secret_providers,
+            vec![SecretProviderKind::File, SecretProviderKind::Vault]
+        );
+        assert_eq!(config.secret_provider_attempts, 5);
+        assert_eq!(config.admin_api_token.as_deref(), Some("admin"));
+
+        // An empty var leaves admin RPCs disabled, as before
+        config
+            .apply_overrides(|name| (name == "ADMIN_API_TOKEN").then(String::new))
+            .unwrap();
+        assert_eq!(config.admin_api_token, None);
+
+        for (name, value) in [
+            ("SECRET_PROVIDERS", "env,aws"),
+            ("SECRET_PROVIDER_ATTEMPTS", "three"),
+        ] {
+            assert!(config
+                .apply_overrides(|lookup| (lookup == name).then(|| value.to_string()))
+                .is_err());
+        }
+        config.secret_providers.clear();
+        config.secret_provider_attempts = 0;
+        config.admin_api_token = Some(String::new());
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("secret_providers must list at least one provider"));
+        assert!(error.contains("secret_provider_attempts must be at least 1"));
+        assert!(error.contains("admin_api_token must not be empty"));
+    }
+
+    #[test]
+    fn test_deployments() {
+        let toml = format!(
+            r#"{}
+            [[deployments]]
+            id = "staging"
+            dex_oracle_nft = "04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66"
+            app_owner_vkey = "fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c"
+            key_prefix = "STAGING"
+            "#,
+            CONFIG_TOML
+        );
+        let mut config = AppConfig::from_toml(&toml).unwrap();
+        let deployments = config.deployments();
+        assert_eq!(deployments.len(), 2);
+        assert_eq!(deployments[0].id, DEFAULT_DEPLOYMENT_ID);
+        assert_eq!(deployments[1].key_prefix.as_deref(), Some("STAGING"));
+        // Tokens are inherited from the top level
+        assert_eq!(deployments[1].tokens, config.tokens);
+        assert!(config.validate().is_ok());
+
+        config.deployments[0].id = DEFAULT_DEPLOYMENT_ID.to_string();
+        config.deployments[0].dex_oracle_nft = "oracle".to_string();
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("deployment id default is used twice"));
+        assert!(error.contains("deployments.default: dex_oracle_nft"));
+    }
+
     #[test]
     fn test_mnemonic_comma_conversion() {
         let mnemonic = "solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution,solution";
@@ -0,0 +1,452 @@
+use std::fmt;
+use whisky::{csl, Asset, Budget, UTxO, UtxoInput, UtxoOutput, WData, WRedeemer};
+
+use crate::{
+    config::{
+        hydra::{ExecutionUnits, HydraParams},
+        AppConfig,
+    },
+    scripts::blueprint::{script_hash, LoadedBlueprint, EXPECTED_VALIDATORS},
+    utils::hydra::get_hydra_tx_builder,
+};
+
+/// Outcome of one startup check
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Every startup check, printed as a report before the server starts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelfCheckReport {
+    pub checks: Vec<SelfCheck>,
+}
+
+impl SelfCheckReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    fn push(&mut self, name: &str, outcome: Result<String, String>) {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.checks.push(SelfCheck {
+            name: name.to_string(),
+            passed,
+            detail,
+        });
+    }
+}
+
+impl fmt::Display for SelfCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Startup self-check:")?;
+        for check in &self.checks {
+            let status = if check.passed { "ok" } else { "FAIL" };
+            writeln!(f, "  [{}] {}: {}", status, check.name, check.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the blueprint, the Hydra protocol parameters, and the signing keys against
+/// `OWNER_VKEY` and the oracle datum, before serving
+///
+/// `key_hashes` are the `(role, key hash)` pairs of every loaded signer and
+/// `app_owners` the `(deployment id, app owner key hash)` pairs of every deployment.
+/// `script_evaluation` is the outcome of [`evaluate_script`].
+/// The oracle datum is checked when a deployment configures `oracle_datum_cbor`.
+pub fn run_self_check(
+    config: &AppConfig,
+    blueprint: &LoadedBlueprint,
+    hydra: &HydraParams,
+    key_hashes: &[(String, String)],
+    app_owners: &[(String, String)],
+    script_evaluation: Result<String, String>,
+) -> SelfCheckReport {
+    let mut report = SelfCheckReport::default();
+
+    report.push(
+        "NETWORK_ID",
+        match config.network_id {
+            0 => Ok("0 (testnet)".to_string()),
+            1 => Ok("1 (mainnet)".to_string()),
+            other => Err(format!(
+                "{} is not a valid network id (expected 0 or 1)",
+                other
+            )),
+        },
+    );
+    report.push(
+        "blueprint",
+        Ok(format!(
+            "{} {} from {}",
+            blueprint.preamble.title, blueprint.preamble.version, blueprint.source
+        )),
+    );
+    let mismatches = blueprint.hash_mismatches();
+    report.push(
+        "blueprint validator hashes",
+        match mismatches.is_empty() {
+            true => Ok(format!("{} validators", blueprint.validators.len())),
+            false => Err(mismatches.join("; ")),
+        },
+    );
+    let schema_errors = blueprint.schema_errors();
+    report.push(
+        "blueprint validator schemas",
+        match schema_errors.is_empty() {
+            true => Ok(format!("{} expected validators", EXPECTED_VALIDATORS.len())),
+            false => Err(schema_errors.join("; ")),
+        },
+    );
+    let hydra_errors = hydra.errors();
+    report.push(
+        "hydra protocol parameters",
+        match hydra_errors.is_empty() {
+            true => Ok(format!(
+                "{} (max tx size {}, max tx execution units {}/{})",
+                hydra.source,
+                hydra.parameters.max_tx_size,
+                hydra.parameters.max_tx_execution_units.memory,
+                hydra.parameters.max_tx_execution_units.steps
+            )),
+            false => Err(format!("{}: {}", hydra.source, hydra_errors.join("; "))),
+        },
+    );
+    report.push("script evaluation", script_evaluation);
+    for (role, key_hash) in key_hashes {
+        report.push(&format!("{} signer", role), Ok(key_hash.clone()));
+    }
+
+    for (index, deployment) in config.deployments().iter().enumerate() {
+        // Checks of the top-level deployment keep their plain names
+        let name = |check: &str| match index {
+            0 => check.to_string(),
+            _ => format!("{}: {}", deployment.id, check),
+        };
+        report.push(
+            &name("DEX_ORACLE_NFT"),
+            check_hash(&deployment.dex_oracle_nft).map(|_| deployment.dex_oracle_nft.clone()),
+        );
+
+        let owner_vkey =
+            check_hash(&deployment.app_owner_vkey).map(|_| deployment.app_owner_vkey.clone());
+        report.push(&name("OWNER_VKEY"), owner_vkey.clone());
+        let Ok(owner_vkey) = &owner_vkey else {
+            continue;
+        };
+
+        let app_owner = app_owners
+            .iter()
+            .find(|(id, _)| *id == deployment.id)
+            .map(|(_, key_hash)| key_hash);
+        report.push(
+            &name("app owner signer matches OWNER_VKEY"),
+            match app_owner {
+                Some(key_hash) if key_hash == owner_vkey => Ok(key_hash.clone()),
+                Some(key_hash) => Err(format!(
+                    "signer key hash {} differs from OWNER_VKEY {}, built txs could never be fully signed",
+                    key_hash, owner_vkey
+                )),
+                None => Err("no app owner signer loaded".to_string()),
+            },
+        );
+
+        if let Some(datum_hex) = &deployment.oracle_datum_cbor {
+            report.push(
+                &name("oracle datum owner keys include OWNER_VKEY"),
+                oracle_owner_keys(datum_hex).and_then(|owner_keys| {
+                    if owner_keys.contains(owner_vkey) {
+                        Ok(owner_keys.join(", "))
+                    } else {
+                        Err(format!(
+                            "oracle datum owner keys {} do not include OWNER_VKEY {}",
+                            owner_keys.join(", "),
+                            owner_vkey
+                        ))
+                    }
+                }),
+            );
+        }
+    }
+
+    report
+}
+
+/// A Plutus V3 validator accepting anything, `(program 1.1.0 (lam ctx (con unit ())))`
+const ALWAYS_SUCCEEDS_V3: &str = "46450101002499";
+
+/// Evaluate a tx minting with an always-succeeding Plutus V3 script under the installed
+/// Hydra cost models, and check the execution units its redeemer declares against
+/// `max_tx_execution_units`
+pub async fn evaluate_script(hydra: &HydraParams, network_id: u8) -> Result<String, String> {
+    let tx_hex = always_succeeds_mint_tx(network_id).await?;
+    let (memory, steps) = check_execution_units(&tx_hex, &hydra.parameters.max_tx_execution_units)?;
+    Ok(format!(
+        "always-succeeds mint evaluated to {}/{} execution units",
+        memory, steps
+    ))
+}
+
+async fn always_succeeds_mint_tx(network_id: u8) -> Result<String, String> {
+    let policy_id = script_hash(ALWAYS_SUCCEEDS_V3).map_err(|e| e.to_string())?;
+    let key_hash = csl::Ed25519KeyHash::from_bytes(vec![0; 28]).map_err(|e| format!("{:?}", e))?;
+    let address =
+        csl::EnterpriseAddress::new(network_id, &csl::Credential::from_keyhash(&key_hash))
+            .to_address()
+            .to_bech32(None)
+            .map_err(|e| format!("{:?}", e))?;
+    let funds = UTxO {
+        input: UtxoInput {
+            output_index: 0,
+            tx_hash: "00".repeat(32),
+        },
+        output: UtxoOutput {
+            address: address.clone(),
+            amount: vec![Asset::new_from_str("lovelace", "100000000")],
+            data_hash: None,
+            plutus_data: None,
+            script_ref: None,
+            script_hash: None,
+        },
+    };
+
+    let mut tx_builder = get_hydra_tx_builder();
+    tx_builder
+        .tx_in(
+            &funds.input.tx_hash,
+            funds.input.output_index,
+            &funds.output.amount,
+            &address,
+        )
+        .input_for_evaluation(&funds)
+        .mint_plutus_script_v3()
+        .mint(1, &policy_id, "")
+        .mint_redeemer_value(&WRedeemer {
+            data: WData::JSON(r#"{"constructor":0,"fields":[]}"#.to_string()),
+            ex_units: Budget::default(),
+        })
+        .minting_script(ALWAYS_SUCCEEDS_V3)
+        .tx_in_collateral(
+            &funds.input.tx_hash,
+            funds.input.output_index,
+            &funds.output.amount,
+            &address,
+        )
+        .change_address(&address)
+        .complete(None)
+        .await
+        .map_err(|e| format!("cannot evaluate the always-succeeds mint: {}", e))?;
+    Ok(tx_builder.tx_hex())
+}
+
+/// Memory and steps the tx's redeemers declare in total, failing beyond `max`
+fn check_execution_units(tx_hex: &str, max: &ExecutionUnits) -> Result<(u64, u64), String> {
+    let tx = csl::Transaction::from_hex(tx_hex).map_err(|e| format!("{:?}", e))?;
+    let redeemers = tx
+        .witness_set()
+        .redeemers()
+        .ok_or_else(|| "the evaluated tx has no redeemers".to_string())?;
+    let units = |quantity: csl::BigNum| {
+        quantity
+            .to_str()
+            .parse::<u64>()
+            .map_err(|e| format!("{:?}", e))
+    };
+    let (mut memory, mut steps) = (0u64, 0u64);
+    for index in 0..redeemers.len() {
+        let ex_units = redeemers.get(index).ex_units();
+        memory = memory.saturating_add(units(ex_units.mem())?);
+        steps = steps.saturating_add(units(ex_units.steps())?);
+    }
+    if memory > max.memory || steps > max.steps {
+        return Err(format!(
+            "needs {}/{} execution units, beyond max_tx_execution_units {}/{}",
+            memory, steps, max.memory, max.steps
+        ));
+    }
+    Ok((memory, steps))
+}
+
+/// Hex encoded 28 byte hash (key hash or policy id)
+fn check_hash(hash: &str) -> Result<(), String> {
+    match hex::decode(hash) {
+        Ok(bytes) if bytes.len() == 28 => Ok(()),
+        _ if hash.is_empty() => Err("not set".to_string()),
+        _ => Err(format!("{} is not a 28 byte hex hash", hash)),
+    }
+}
+
+/// The leading verification key hash fields of an `AppOracleDatum`
+fn oracle_owner_keys(datum_hex: &str) -> Result<Vec<String>, String> {
+    let datum = csl::PlutusData::from_hex(datum_hex)
+        .map_err(|e| format!("invalid oracle datum CBOR: {:?}", e))?;
+    let json =
+        csl::decode_plutus_datum_to_json_value(&datum, csl::PlutusDatumSchema::DetailedSchema)
+            .map_err(|e| format!("invalid oracle datum: {:?}", e))?;
+    let fields = json["fields"]
+        .as_array()
+        .ok_or("oracle datum is not a constructor")?;
+
+    let owner_keys: Vec<String> = fields
+        .iter()
+        .take(2)
+        .filter_map(|field| field["bytes"].as_str().map(|bytes| bytes.to_string()))
+        .collect();
+    if owner_keys.len() != 2 {
+        return Err("oracle datum does not start with two verification key hashes".to_string());
+    }
+    Ok(owner_keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{
+            deployment::{DeploymentConfig, DEFAULT_DEPLOYMENT_ID},
+            hydra::hydra_params,
+            signing_keys::APP_OWNER_ROLE,
+        },
+        test_utils::{init_test_env, TEST_NETWORK_ID},
+    };
+
+    const OWNER_VKEY: &str = "fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c";
+    const OTHER_VKEY: &str = "04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66";
+
+    fn config(network_id: u8, oracle_datum_cbor: String) -> AppConfig {
+        AppConfig {
+            network_id,
+            app_owner_vkey: OWNER_VKEY.to_string(),
+            dex_oracle_nft: "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d".to_string(),
+            oracle_datum_cbor: Some(oracle_datum_cbor),
+            ..AppConfig::default()
+        }
+    }
+
+    fn oracle_datum(owner_keys: [&str; 2]) -> String {
+        let json = serde_json::json!({
+            "constructor": 0,
+            "fields": [
+                { "bytes": owner_keys[0] },
+                { "bytes": owner_keys[1] },
+                { "list": [] },
+            ],
+        });
+        csl::encode_json_value_to_plutus_datum(json, csl::PlutusDatumSchema::DetailedSchema)
+            .unwrap()
+            .to_hex()
+    }
+
+    fn blueprint() -> LoadedBlueprint {
+        LoadedBlueprint::load(None).unwrap()
+    }
+
+    fn app_owners(key_hash: &str) -> Vec<(String, String)> {
+        vec![(DEFAULT_DEPLOYMENT_ID.to_string(), key_hash.to_string())]
+    }
+
+    fn failed(report: &SelfCheckReport) -> Vec<&str> {
+        report
+            .checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_self_check_passes() {
+        let key_hashes = [(APP_OWNER_ROLE.to_string(), OWNER_VKEY.to_string())];
+        let datum = oracle_datum([OWNER_VKEY, OTHER_VKEY]);
+        let report = run_self_check(
+            &config(0, datum),
+            &blueprint(),
+            &HydraParams::default(),
+            &key_hashes,
+            &app_owners(OWNER_VKEY),
+            Ok("evaluated".to_string()),
+        );
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.checks[0].detail, "0 (testnet)");
+    }
+
+    #[test]
+    fn test_self_check_reports_mismatches() {
+        let key_hashes = [(APP_OWNER_ROLE.to_string(), OTHER_VKEY.to_string())];
+        let datum = oracle_datum([OTHER_VKEY, OTHER_VKEY]);
+        let mut hydra = HydraParams::default();
+        hydra.parameters.cost_models.remove("PlutusV3");
+        let report = run_self_check(
+            &config(2, datum),
+            &blueprint(),
+            &hydra,
+            &key_hashes,
+            &app_owners(OTHER_VKEY),
+            Err("cost model mismatch".to_string()),
+        );
+        assert_eq!(
+            failed(&report),
+            vec![
+                "NETWORK_ID",
+                "hydra protocol parameters",
+                "script evaluation",
+                "app owner signer matches OWNER_VKEY",
+                "oracle datum owner keys include OWNER_VKEY",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_self_check_names_deployment_checks() {
+        let mut config = config(0, oracle_datum([OWNER_VKEY, OTHER_VKEY]));
+        config.deployments = vec![DeploymentConfig {
+            id: "staging".to_string(),
+            dex_oracle_nft: "04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66".to_string(),
+            app_owner_vkey: OTHER_VKEY.to_string(),
+            ..DeploymentConfig::default()
+        }];
+        let mut app_owners = app_owners(OWNER_VKEY);
+        app_owners.push(("staging".to_string(), OWNER_VKEY.to_string()));
+        let report = run_self_check(
+            &config,
+            &blueprint(),
+            &HydraParams::default(),
+            &[],
+            &app_owners,
+            Ok("evaluated".to_string()),
+        );
+        assert_eq!(
+            failed(&report),
+            vec!["staging: app owner signer matches OWNER_VKEY"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_script_with_built_in_parameters() {
+        init_test_env();
+        let outcome = evaluate_script(hydra_params(), TEST_NETWORK_ID).await;
+        assert!(outcome.is_ok(), "{:?}", outcome);
+    }
+
+    #[tokio::test]
+    async fn test_execution_units_beyond_limit() {
+        init_test_env();
+        let tx_hex = always_succeeds_mint_tx(TEST_NETWORK_ID).await.unwrap();
+        let limit = &hydra_params().parameters.max_tx_execution_units;
+        let (memory, steps) = check_execution_units(&tx_hex, limit).unwrap();
+        assert!(memory > 0 && steps > 0);
+
+        let tight = ExecutionUnits {
+            memory: memory - 1,
+            steps,
+        };
+        let error = check_execution_units(&tx_hex, &tight).unwrap_err();
+        assert!(error.contains("beyond max_tx_execution_units"));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::config::signing_keys::{Derivation, MasterKeyScheme};
+
+/// Where a signing key lives, built from the key's [`SignerSettings`]
+///
+/// * `mnemonic` (default) - seed phrase from the secret providers, held in memory
+/// * `keystore` - encrypted keystore file, decrypted at startup
+/// * `root_key` - extended root private key (`xprv1...` / `root_xsk1...`) from the secret providers
+/// * `cli_skey` - cardano-cli `.skey` file, used as is without derivation
+/// * `remote` - external signer service, the key never enters hibiki
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerBackend {
+    Mnemonic,
+    Keystore {
+        path: String,
+    },
+    RootKey,
+    CliSkey {
+        path: String,
+    },
+    Remote {
+        url: String,
+        auth_token: Option<String>,
+    },
+}
+
+/// The `backend` setting of a key, see [`SignerBackend`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerBackendKind {
+    #[default]
+    Mnemonic,
+    Keystore,
+    RootKey,
+    CliSkey,
+    Remote,
+}
+
+impl FromStr for SignerBackendKind {
+    type Err = String;
+
+    fn from_str(backend: &str) -> Result<Self, Self::Err> {
+        match backend {
+            "mnemonic" => Ok(SignerBackendKind::Mnemonic),
+            "keystore" => Ok(SignerBackendKind::Keystore),
+            "root_key" => Ok(SignerBackendKind::RootKey),
+            "cli_skey" => Ok(SignerBackendKind::CliSkey),
+            "remote" => Ok(SignerBackendKind::Remote),
+            other => Err(format!(
+                "Unknown signer backend {} (expected mnemonic, keystore, root_key, cli_skey or remote)",
+                other
+            )),
+        }
+    }
+}
+
+/// One key's entry in the `[signers.<PREFIX>]` tables of the config, e.g. `[signers.APP_OWNER]`,
+/// each setting overridable by `<PREFIX>_SIGNER_BACKEND`, `<PREFIX>_KEYSTORE_PATH`, ...
+///
+/// Secrets (`<PREFIX>_SEED_PHRASE`, `<PREFIX>_KEYSTORE_PASSPHRASE`, `<PREFIX>_ROOT_KEY`,
+/// `<PREFIX>_BIP39_PASSPHRASE`) are not part of it, they are fetched at load time.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignerSettings {
+    pub backend: SignerBackendKind,
+    /// Encrypted keystore file, keystore backend only
+    pub keystore_path: Option<String>,
+    /// cardano-cli `.skey` file, cli_skey backend only
+    pub skey_path: Option<String>,
+    /// Remote signer service, remote backend only
+    pub remote_signer_url: Option<String>,
+    /// Optional bearer token of the remote signer
+    pub remote_signer_token: Option<String>,
+    /// CIP-1852 path replacing the key's derivation, e.g. `m/1852'/1815'/1'/0/3`
+    pub derivation_path: Option<String>,
+    pub master_key: Option<MasterKeyScheme>,
+}
+
+impl SignerSettings {
+    /// The backend of the key with env prefix `prefix`
+    ///
+    /// With `require_encrypted_keys` the plaintext backends (mnemonic, root_key, cli_skey)
+    /// are refused, so a deployment cannot silently fall back to an unencrypted key.
+    pub fn backend(
+        &self,
+        prefix: &str,
+        require_encrypted_keys: bool,
+    ) -> Result<SignerBackend, String> {
+        let required = |value: &Option<String>, setting: &str| {
+            value
+                .clone()
+                .ok_or_else(|| format!("signers.{}.{} must be set", prefix, setting))
+        };
+
+        match self.backend {
+            SignerBackendKind::Mnemonic | SignerBackendKind::RootKey | SignerBackendKind::CliSkey
+                if require_encrypted_keys =>
+            {
+                Err(format!(
+                    "require_encrypted_keys is set but signers.{} uses a plaintext backend (use keystore or remote)",
+                    prefix
+                ))
+            }
+            SignerBackendKind::Mnemonic => Ok(SignerBackend::Mnemonic),
+            SignerBackendKind::Keystore => Ok(SignerBackend::Keystore {
+                path: required(&self.keystore_path, "keystore_path")?,
+            }),
+            SignerBackendKind::RootKey => Ok(SignerBackend::RootKey),
+            SignerBackendKind::CliSkey => Ok(SignerBackend::CliSkey {
+                path: required(&self.skey_path, "skey_path")?,
+            }),
+            SignerBackendKind::Remote => Ok(SignerBackend::Remote {
+                url: required(&self.remote_signer_url, "remote_signer_url")?,
+                auth_token: self.remote_signer_token.clone(),
+            }),
+        }
+    }
+
+    /// `derivation` with `derivation_path` and `master_key` applied when set
+    pub fn derivation(&self, derivation: Derivation) -> Result<Derivation, String> {
+        let mut derivation = derivation;
+        if let Some(path) = &self.derivation_path {
+            derivation = derivation.with_path(path)?;
+        }
+        if let Some(master_key) = self.master_key {
+            derivation.master_key = master_key;
+        }
+        Ok(derivation)
+    }
+}
@@ -0,0 +1,289 @@
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Roles always present in the key registry
+pub const APP_OWNER_ROLE: &str = "app_owner";
+pub const FEE_COLLECTOR_ROLE: &str = "fee_collector";
+
+/// Env prefixes of the built-in keys, also their names under `[signers]`
+pub const APP_OWNER_PREFIX: &str = "APP_OWNER";
+pub const FEE_COLLECTOR_PREFIX: &str = "FEE_COLLECTOR";
+
+/// Matches every RPC in `allowed_rpcs`
+pub const ANY_RPC: &str = "*";
+
+/// First hardened BIP32 index; path indices are given below it
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// Highest CIP-1852 role: 0 external, 1 internal, 2 staking
+const MAX_ROLE: u32 = 2;
+
+/// How the BIP32 master key is generated from a mnemonic (CIP-3)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MasterKeyScheme {
+    /// Software wallets and Trezor
+    #[default]
+    Icarus,
+    /// Ledger hardware wallets
+    Ledger,
+}
+
+impl FromStr for MasterKeyScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "icarus" => Ok(MasterKeyScheme::Icarus),
+            "ledger" => Ok(MasterKeyScheme::Ledger),
+            other => Err(format!(
+                "Unknown master key scheme {} (expected icarus or ledger)",
+                other
+            )),
+        }
+    }
+}
+
+/// CIP-1852 derivation of a key: m/1852'/1815'/account_index'/role/key_index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(try_from = "DerivationFields")]
+pub struct Derivation {
+    pub account_index: u32,
+    /// 0 for external (payment) keys, 1 for internal (change) keys, 2 for staking keys
+    pub role: u32,
+    pub key_index: u32,
+    pub master_key: MasterKeyScheme,
+}
+
+/// `Derivation` as written in the signing keys config, checked before use
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct DerivationFields {
+    account_index: u32,
+    role: u32,
+    key_index: u32,
+    master_key: MasterKeyScheme,
+}
+
+impl TryFrom<DerivationFields> for Derivation {
+    type Error = String;
+
+    fn try_from(fields: DerivationFields) -> Result<Self, Self::Error> {
+        Derivation {
+            account_index: fields.account_index,
+            role: fields.role,
+            key_index: fields.key_index,
+            master_key: fields.master_key,
+        }
+        .validated()
+    }
+}
+
+impl Derivation {
+    /// Parse a CIP-1852 path such as `m/1852'/1815'/1'/0/3`, keeping the master key scheme
+    pub fn with_path(self, path: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid CIP-1852 derivation path {}", path);
+        let segments: Vec<&str> = path.trim().split('/').collect();
+        let [m, purpose, coin, account, role, key] = segments.as_slice() else {
+            return Err(invalid());
+        };
+        if *m != "m" || !is_hardened(purpose, "1852") || !is_hardened(coin, "1815") {
+            return Err(invalid());
+        }
+        let account_index = account
+            .strip_suffix('\'')
+            .or_else(|| account.strip_suffix('H'))
+            .and_then(|index| index.parse().ok())
+            .ok_or_else(invalid)?;
+        Derivation {
+            account_index,
+            role: role.parse().map_err(|_| invalid())?,
+            key_index: key.parse().map_err(|_| invalid())?,
+            ..self
+        }
+        .validated()
+        .map_err(|e| format!("{}: {}", invalid(), e))
+    }
+
+    /// Reject indices the path could not express: hardened ones, or an unknown role
+    pub fn validated(self) -> Result<Self, String> {
+        if self.account_index >= HARDENED {
+            return Err(format!(
+                "account_index {} must be below 2^31",
+                self.account_index
+            ));
+        }
+        if self.role > MAX_ROLE {
+            return Err(format!("role {} must be 0, 1 or 2", self.role));
+        }
+        if self.key_index >= HARDENED {
+            return Err(format!("key_index {} must be below 2^31", self.key_index));
+        }
+        Ok(self)
+    }
+}
+
+fn is_hardened(segment: &str, index: &str) -> bool {
+    *segment == format!("{}'", index) || *segment == format!("{}H", index)
+}
+
+/// One named signing key from the `[[signing_keys]]` list of the config
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningKeyConfig {
+    /// Role name clients pass to the role-based signing RPCs, e.g. `oracle_admin`
+    pub role: String,
+    /// Prefix of the key's `[signers]` entry and secrets, e.g. `ORACLE_ADMIN` uses
+    /// `[signers.ORACLE_ADMIN]` and `ORACLE_ADMIN_SEED_PHRASE`.
+    /// Defaults to the upper-cased role.
+    #[serde(default)]
+    pub env_prefix: Option<String>,
+    #[serde(default)]
+    pub derivation: Derivation,
+    /// RPC names this key may be used from, or `*` for all role-based signing RPCs
+    pub allowed_rpcs: Vec<String>,
+}
+
+impl SigningKeyConfig {
+    pub fn env_prefix(&self) -> String {
+        self.env_prefix
+            .clone()
+            .unwrap_or_else(|| self.role.to_uppercase())
+    }
+}
+
+/// Roles that are empty, reserved for the built-in keys or defined twice
+pub fn signing_key_errors(keys: &[SigningKeyConfig]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut roles: Vec<&str> = vec![APP_OWNER_ROLE, FEE_COLLECTOR_ROLE];
+    for key in keys {
+        if key.role.is_empty() {
+            errors.push("signing_keys: role must not be empty".to_string());
+        } else if roles.contains(&key.role.as_str()) {
+            errors.push(format!("signing_keys: role {} is defined twice", key.role));
+        } else {
+            roles.push(&key.role);
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    /// The `[[signing_keys]]` of a config file, if they parse and pass validation
+    fn signing_keys(toml: &str) -> Result<Vec<SigningKeyConfig>, String> {
+        let keys = AppConfig::from_toml(toml)?.signing_keys;
+        match signing_key_errors(&keys).as_slice() {
+            [] => Ok(keys),
+            errors => Err(errors.join("\n")),
+        }
+    }
+
+    #[test]
+    fn test_parse_signing_keys_config() {
+        let keys = signing_keys(
+            r#"
+            [[signing_keys]]
+            role = "oracle_admin"
+            derivation = { account_index = 1 }
+            allowed_rpcs = ["sign_transaction_with_role"]
+
+            [[signing_keys]]
+            role = "hydra_head_1"
+            env_prefix = "HEAD_1"
+            allowed_rpcs = ["*"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].env_prefix(), "ORACLE_ADMIN");
+        assert_eq!(
+            keys[0].derivation,
+            Derivation {
+                account_index: 1,
+                ..Derivation::default()
+            }
+        );
+        assert_eq!(keys[1].env_prefix(), "HEAD_1");
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let derivation = Derivation {
+            master_key: MasterKeyScheme::Ledger,
+            ..Derivation::default()
+        }
+        .with_path("m/1852'/1815'/2'/1/7")
+        .unwrap();
+        assert_eq!(
+            derivation,
+            Derivation {
+                account_index: 2,
+                role: 1,
+                key_index: 7,
+                master_key: MasterKeyScheme::Ledger,
+            }
+        );
+
+        for path in [
+            "m/1852'/1815'/2/1/7",
+            "m/44'/1815'/0'/0/0",
+            "m/1852'/1815'/0'/0",
+            "m/1852'/1815'/2147483648'/0/0",
+            "m/1852'/1815'/0'/3/0",
+            "m/1852'/1815'/0'/0/2147483648",
+        ] {
+            assert!(Derivation::default().with_path(path).is_err());
+        }
+        assert_eq!(
+            Derivation::default()
+                .with_path("m/1852'/1815'/2147483647'/2/2147483647")
+                .unwrap()
+                .role,
+            2
+        );
+    }
+
+    #[test]
+    fn test_reject_out_of_range_derivation() {
+        let key = |derivation: &str| {
+            format!(
+                "[[signing_keys]]\nrole = \"stop_key\"\nderivation = {}\nallowed_rpcs = []",
+                derivation
+            )
+        };
+        for derivation in [
+            "{ account_index = 2147483648 }",
+            "{ role = 3 }",
+            "{ key_index = 4294967295 }",
+        ] {
+            assert!(signing_keys(&key(derivation)).is_err());
+        }
+
+        let keys = signing_keys(&key("{ role = 2, master_key = \"ledger\" }")).unwrap();
+        assert_eq!(keys[0].derivation.role, 2);
+        assert_eq!(keys[0].derivation.master_key, MasterKeyScheme::Ledger);
+    }
+
+    #[test]
+    fn test_reject_duplicate_or_reserved_roles() {
+        let reserved = "[[signing_keys]]\nrole = \"app_owner\"\nallowed_rpcs = []";
+        assert!(signing_keys(reserved).is_err());
+
+        let duplicate = r#"
+            [[signing_keys]]
+            role = "stop_key"
+            allowed_rpcs = []
+
+            [[signing_keys]]
+            role = "stop_key"
+            allowed_rpcs = []
+        "#;
+        assert!(signing_keys(duplicate).is_err());
+        assert!(signing_keys("[[signing_keys]]\nrole = \"\"\nallowed_rpcs = []").is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use whisky::{csl, UtxoInput, WError};
+
+use crate::{config::AppConfig, hydra_node::utxo_index::UtxoIndex};
+
+/// Restrictions the fee collector key enforces before signing a transaction.
+///
+/// The fee collector only signs transactions whose outputs all pay to a configured
+/// treasury address, and by default refuses transactions spending script UTxOs or
+/// carrying a mint, withdrawals, certificates, votes or proposals.
+/// Script spends are told from the tx body and the resolved inputs, never from the
+/// witness set, since redeemers can be stripped before signing and added back after.
+pub struct FeeCollectorPolicy {
+    pub treasury_addresses: Vec<String>,
+    pub allow_script_inputs: bool,
+    pub allow_ledger_actions: bool,
+}
+
+impl FeeCollectorPolicy {
+    pub fn from_config(config: &AppConfig) -> Self {
+        FeeCollectorPolicy {
+            treasury_addresses: config.fee_collector_treasury_addresses.clone(),
+            allow_script_inputs: config.fee_collector_allow_script_inputs,
+            allow_ledger_actions: config.fee_collector_allow_ledger_actions,
+        }
+    }
+
+    /// `utxos` resolves the tx's inputs; without it script inputs can't be ruled out
+    pub fn check(&self, tx_hex: &str, utxos: Option<&UtxoIndex>) -> Result<(), WError> {
+        let tx = csl::Transaction::from_hex(tx_hex)
+            .map_err(WError::from_err("FeeCollectorPolicy - from_hex"))?;
+        let body = tx.body();
+
+        let outputs = body.outputs();
+        for index in 0..outputs.len() {
+            let address = outputs
+                .get(index)
+                .address()
+                .to_bech32(None)
+                .map_err(WError::from_err("FeeCollectorPolicy - to_bech32"))?;
+            if !self.treasury_addresses.contains(&address) {
+                return Err(WError::new(
+                    "FeeCollectorPolicy - outputs",
+                    &format!("Output {} pays to non-treasury address {}", index, address),
+                ));
+            }
+        }
+
+        if !self.allow_script_inputs {
+            if body.script_data_hash().is_some() {
+                return Err(WError::new(
+                    "FeeCollectorPolicy - inputs",
+                    "Transaction has a script data hash",
+                ));
+            }
+            check_key_inputs(&body.inputs(), utxos)?;
+        }
+
+        if !self.allow_ledger_actions {
+            check_no_ledger_actions(&body)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The body must not mint, withdraw rewards, carry certificates, vote or propose
+fn check_no_ledger_actions(body: &csl::TransactionBody) -> Result<(), WError> {
+    let actions = [
+        ("a mint", body.mint().map_or(0, |mint| mint.len())),
+        (
+            "withdrawals",
+            body.withdrawals()
+                .map_or(0, |withdrawals| withdrawals.len()),
+        ),
+        ("certificates", body.certs().map_or(0, |certs| certs.len())),
+        (
+            "votes",
+            body.voting_procedures()
+                .map_or(0, |procedures| procedures.get_voters().len()),
+        ),
+        (
+            "proposals",
+            body.voting_proposals()
+                .map_or(0, |proposals| proposals.len()),
+        ),
+    ];
+    match actions.iter().find(|(_, count)| *count > 0) {
+        Some((action, _)) => Err(WError::new(
+            "FeeCollectorPolicy - ledger actions",
+            &format!("Transaction has {}", action),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Every input must resolve to a UTxO locked by a key, not by a plutus or native script
+fn check_key_inputs(
+    inputs: &csl::TransactionInputs,
+    utxos: Option<&UtxoIndex>,
+) -> Result<(), WError> {
+    let utxos = utxos.ok_or_else(|| {
+        WError::new(
+            "FeeCollectorPolicy - inputs",
+            "Cannot resolve inputs to rule out script UTxOs, the head's UTxO set is not indexed",
+        )
+    })?;
+    for index in 0..inputs.len() {
+        let input = inputs.get(index);
+        let input = UtxoInput {
+            output_index: input.index(),
+            tx_hash: input.transaction_id().to_hex(),
+        };
+        let utxo = utxos.get(&input).ok_or_else(|| {
+            WError::new(
+                "FeeCollectorPolicy - inputs",
+                &format!("Input {}#{} not found", input.tx_hash, input.output_index),
+            )
+        })?;
+        let address = csl::Address::from_bech32(&utxo.output.address)
+            .map_err(WError::from_err("FeeCollectorPolicy - from_bech32"))?;
+        if address
+            .payment_cred()
+            .is_some_and(|credential| credential.has_script_hash())
+        {
+            return Err(WError::new(
+                "FeeCollectorPolicy - inputs",
+                &format!(
+                    "Input {}#{} is locked by a script",
+                    input.tx_hash, input.output_index
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hydra_node::HydraUtxoSet;
+    use serde_json::json;
+
+    // Transaction paying to two base addresses, without redeemers
+    const TX_HEX: &str = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca10081825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402f5d90103a0";
+    const OUTPUT_ADDRESS_0: &str = "addr_test1qqzgg5pcaeyea69uptl9da5g7fajm4m0yvxndx9f4lxpkehqgezy0s04rtdwlc0tlvxafpdrfxnsg7ww68ge3j7l0lnszsw2wt";
+    const OUTPUT_ADDRESS_1: &str = "addr_test1qq2rfkutnykch5tw6uw9y8gdaavpra3h6egfmr20vg9dq0hpqstx3elazm9jlkt7rx27zqtvxl6hvmecwveu8qv97e4slng4tz";
+    const INPUT: &str = "e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd8#1";
+
+    fn policy(allow_script_inputs: bool) -> FeeCollectorPolicy {
+        FeeCollectorPolicy {
+            treasury_addresses: vec![OUTPUT_ADDRESS_0.to_string(), OUTPUT_ADDRESS_1.to_string()],
+            allow_script_inputs,
+            allow_ledger_actions: false,
+        }
+    }
+
+    /// The head's UTxO set with the input of `TX_HEX` at `address`
+    fn utxos(address: &str) -> UtxoIndex {
+        let utxo: HydraUtxoSet = serde_json::from_value(json!({
+            INPUT: { "address": address, "value": { "lovelace": 1_000_000_000 } },
+        }))
+        .unwrap();
+        UtxoIndex::build(1, &utxo, &[]).unwrap()
+    }
+
+    /// `TX_HEX` with its body changed by `change`
+    fn with_body(change: impl FnOnce(&mut csl::TransactionBody)) -> String {
+        let tx = csl::Transaction::from_hex(TX_HEX).unwrap();
+        let mut body = tx.body();
+        change(&mut body);
+        csl::Transaction::new(&body, &tx.witness_set(), tx.auxiliary_data()).to_hex()
+    }
+
+    /// `TX_HEX` as a script spend with its redeemers stripped: the body keeps its script data hash
+    fn stripped_script_tx() -> String {
+        with_body(|body| {
+            body.set_script_data_hash(&csl::ScriptDataHash::from_bytes(vec![1u8; 32]).unwrap())
+        })
+    }
+
+    fn key_credential() -> csl::Credential {
+        csl::Credential::from_keyhash(&csl::Ed25519KeyHash::from_bytes(vec![4u8; 28]).unwrap())
+    }
+
+    /// Passes `tx_hex` only when ledger actions are allowed
+    fn assert_ledger_action_refused(tx_hex: &str) {
+        let utxos = utxos(OUTPUT_ADDRESS_0);
+        let error = policy(false).check(tx_hex, Some(&utxos)).unwrap_err();
+        assert!(format!("{:?}", error).contains("ledger actions"));
+        let allowing = FeeCollectorPolicy {
+            allow_ledger_actions: true,
+            ..policy(false)
+        };
+        assert!(allowing.check(tx_hex, Some(&utxos)).is_ok());
+    }
+
+    /// An enterprise address locked by a native script requiring one key
+    fn native_script_address() -> String {
+        let key_hash = csl::Ed25519KeyHash::from_bytes(vec![2u8; 28]).unwrap();
+        let script = csl::NativeScript::new_script_pubkey(&csl::ScriptPubkey::new(&key_hash));
+        let credential = csl::Credential::from_scripthash(&script.hash());
+        csl::EnterpriseAddress::new(0, &credential)
+            .to_address()
+            .to_bech32(None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fee_collector_policy_allows_treasury_outputs() {
+        let utxos = utxos(OUTPUT_ADDRESS_0);
+        assert!(policy(false).check(TX_HEX, Some(&utxos)).is_ok());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_non_treasury_output() {
+        let policy = FeeCollectorPolicy {
+            treasury_addresses: vec![OUTPUT_ADDRESS_0.to_string()],
+            allow_script_inputs: false,
+            allow_ledger_actions: false,
+        };
+        let utxos = utxos(OUTPUT_ADDRESS_0);
+        assert!(policy.check(TX_HEX, Some(&utxos)).is_err());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_without_treasury() {
+        let policy = FeeCollectorPolicy {
+            treasury_addresses: vec![],
+            allow_script_inputs: true,
+            allow_ledger_actions: false,
+        };
+        assert!(policy.check(TX_HEX, None).is_err());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_unresolved_inputs() {
+        assert!(policy(false).check(TX_HEX, None).is_err());
+        let empty = UtxoIndex::build(1, &HydraUtxoSet::new(), &[]).unwrap();
+        assert!(policy(false).check(TX_HEX, Some(&empty)).is_err());
+        assert!(policy(true).check(TX_HEX, None).is_ok());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_stripped_redeemers() {
+        let tx_hex = stripped_script_tx();
+        assert!(csl::Transaction::from_hex(&tx_hex)
+            .unwrap()
+            .witness_set()
+            .redeemers()
+            .is_none());
+        let utxos = utxos(OUTPUT_ADDRESS_0);
+        assert!(policy(false).check(&tx_hex, Some(&utxos)).is_err());
+        assert!(policy(true).check(&tx_hex, Some(&utxos)).is_ok());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_native_script_input() {
+        let utxos = utxos(&native_script_address());
+        assert!(policy(false).check(TX_HEX, Some(&utxos)).is_err());
+        assert!(policy(true).check(TX_HEX, Some(&utxos)).is_ok());
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_mint() {
+        let assets = csl::MintAssets::new_from_entry(
+            &csl::AssetName::new(vec![1u8]).unwrap(),
+            &csl::Int::new_i32(1),
+        )
+        .unwrap();
+        let policy_id = csl::ScriptHash::from_bytes(vec![3u8; 28]).unwrap();
+        let mint = csl::Mint::new_from_entry(&policy_id, &assets);
+        assert_ledger_action_refused(&with_body(|body| body.set_mint(&mint)));
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_withdrawals() {
+        let mut withdrawals = csl::Withdrawals::new();
+        withdrawals.insert(
+            &csl::RewardAddress::new(0, &key_credential()),
+            &csl::BigNum::from(1_000_000u64),
+        );
+        assert_ledger_action_refused(&with_body(|body| body.set_withdrawals(&withdrawals)));
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_certificates() {
+        let mut certs = csl::Certificates::new();
+        certs.add(&csl::Certificate::new_stake_registration(
+            &csl::StakeRegistration::new(&key_credential()),
+        ));
+        assert_ledger_action_refused(&with_body(|body| body.set_certs(&certs)));
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_votes() {
+        let mut procedures = csl::VotingProcedures::new();
+        procedures.insert(
+            &csl::Voter::new_stake_pool_key_hash(
+                &csl::Ed25519KeyHash::from_bytes(vec![5u8; 28]).unwrap(),
+            ),
+            &csl::GovernanceActionId::new(
+                &csl::TransactionHash::from_bytes(vec![6u8; 32]).unwrap(),
+                0,
+            ),
+            &csl::VotingProcedure::new(csl::VoteKind::Yes),
+        );
+        assert_ledger_action_refused(&with_body(|body| body.set_voting_procedures(&procedures)));
+    }
+
+    #[test]
+    fn test_fee_collector_policy_rejects_proposals() {
+        let anchor = csl::Anchor::new(
+            &csl::URL::new("https://example.com".to_string()).unwrap(),
+            &csl::AnchorDataHash::from_bytes(vec![7u8; 32]).unwrap(),
+        );
+        let proposal = csl::VotingProposal::new(
+            &csl::GovernanceAction::new_info_action(&csl::InfoAction::new()),
+            &anchor,
+            &csl::RewardAddress::new(0, &key_credential()),
+            &csl::BigNum::from(100_000_000_000u64),
+        );
+        let mut proposals = csl::VotingProposals::new();
+        proposals.add(&proposal);
+        assert_ledger_action_refused(&with_body(|body| body.set_voting_proposals(&proposals)));
+    }
+}
@@ -0,0 +1,294 @@
+use std::{path::PathBuf, sync::Arc};
+use whisky::{
+    data::{PlutusData, PolicyId},
+    MintingBlueprint, SpendingBlueprint, WError, WithdrawalBlueprint,
+};
+
+use crate::{
+    config::{
+        deployment::DeploymentConfig,
+        signing_keys::{
+            Derivation, APP_OWNER_PREFIX, APP_OWNER_ROLE, FEE_COLLECTOR_PREFIX, FEE_COLLECTOR_ROLE,
+        },
+        AppConfig,
+    },
+    scripts::{
+        cache::CachedBlueprint, HydraAccountOperation, HydraAccountRedeemer,
+        HydraOrderBookRedeemer, HydraTokensRedeemer, HydraUserIntentDatum, HydraUserIntentRedeemer,
+        UserAccount, SCRIPTS,
+    },
+    signer::RotatingSigner,
+    utils::{token_registry::TokenRegistry, wallet::load_rotating_signer},
+};
+
+/// Request metadata selecting the deployment; the default deployment when absent
+pub const DEPLOYMENT_METADATA_KEY: &str = "x-hibiki-deployment";
+
+/// Blueprints parameterized by a deployment's oracle NFT, with their scripts computed at load
+pub struct DeploymentScripts {
+    pub oracle_policy: PolicyId,
+    pub hydra_user_intent_mint:
+        CachedBlueprint<MintingBlueprint<PolicyId, HydraUserIntentRedeemer>>,
+    pub hydra_user_intent_spend:
+        CachedBlueprint<SpendingBlueprint<PolicyId, PlutusData, HydraUserIntentDatum>>,
+    pub hydra_account_spend:
+        CachedBlueprint<SpendingBlueprint<PolicyId, HydraAccountRedeemer, UserAccount>>,
+    pub hydra_account_withdrawal:
+        CachedBlueprint<WithdrawalBlueprint<PolicyId, HydraAccountOperation>>,
+    pub hydra_order_book_withdrawal:
+        CachedBlueprint<WithdrawalBlueprint<PolicyId, HydraOrderBookRedeemer>>,
+    pub hydra_token_mint: CachedBlueprint<MintingBlueprint<PolicyId, HydraTokensRedeemer>>,
+}
+
+impl DeploymentScripts {
+    pub fn new(dex_oracle_nft: &str, network_id: u8) -> Result<Self, WError> {
+        let oracle_policy = PolicyId::new(dex_oracle_nft);
+        let intent = &SCRIPTS.hydra_user_intent;
+        let account = &SCRIPTS.hydra_account_balance;
+        let order_book = &SCRIPTS.hydra_order_book;
+        let token = &SCRIPTS.hydra_token;
+        Ok(DeploymentScripts {
+            hydra_user_intent_mint: CachedBlueprint::new((intent.mint)(
+                &oracle_policy,
+                network_id,
+            ))?,
+            hydra_user_intent_spend: CachedBlueprint::new((intent.spend)(
+                &oracle_policy,
+                network_id,
+            ))?,
+            hydra_account_spend: CachedBlueprint::new((account.spend)(&oracle_policy, network_id))?,
+            hydra_account_withdrawal: CachedBlueprint::new((account.withdrawal)(
+                &oracle_policy,
+                network_id,
+            ))?,
+            hydra_order_book_withdrawal: CachedBlueprint::new((order_book.withdrawal)(
+                &oracle_policy,
+                network_id,
+            ))?,
+            hydra_token_mint: CachedBlueprint::new((token.mint)(&oracle_policy, network_id))?,
+            oracle_policy,
+        })
+    }
+
+    /// `(name, script hash)` of every script, for reporting
+    pub fn hashes(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("hydra_user_intent.mint", &self.hydra_user_intent_mint.hash),
+            (
+                "hydra_user_intent.spend",
+                &self.hydra_user_intent_spend.hash,
+            ),
+            ("hydra_account.spend", &self.hydra_account_spend.hash),
+            (
+                "hydra_account.withdraw",
+                &self.hydra_account_withdrawal.hash,
+            ),
+            (
+                "hydra_order_book.withdraw",
+                &self.hydra_order_book_withdrawal.hash,
+            ),
+            ("hydra_tokens.mint", &self.hydra_token_mint.hash),
+        ]
+    }
+}
+
+/// A DEX deployment served by this process, with its own scripts, tokens and keys
+pub struct Deployment {
+    pub id: String,
+    pub dex_oracle_nft: String,
+    pub app_owner_vkey: String,
+    pub scripts: DeploymentScripts,
+    pub tokens: TokenRegistry,
+    pub app_owner_signer: Arc<RotatingSigner>,
+    pub fee_collector_signer: Arc<RotatingSigner>,
+    /// Whether the signers are this deployment's own rather than the shared default ones
+    pub has_own_signers: bool,
+}
+
+impl Deployment {
+    pub fn new(
+        config: &DeploymentConfig,
+        network_id: u8,
+        app_owner_signer: Arc<RotatingSigner>,
+        fee_collector_signer: Arc<RotatingSigner>,
+        has_own_signers: bool,
+    ) -> Result<Self, WError> {
+        let scripts = DeploymentScripts::new(&config.dex_oracle_nft, network_id)?;
+        let tokens = TokenRegistry::new(
+            config.tokens.clone(),
+            config.token_registry_path.as_ref().map(PathBuf::from),
+            scripts.hydra_token_mint.hash.clone(),
+        )?;
+        Ok(Deployment {
+            id: config.id.clone(),
+            dex_oracle_nft: config.dex_oracle_nft.clone(),
+            app_owner_vkey: config.app_owner_vkey.clone(),
+            scripts,
+            tokens,
+            app_owner_signer,
+            fee_collector_signer,
+            has_own_signers,
+        })
+    }
+
+    /// Policy id of the Hydra tokens minted for this deployment
+    pub fn hydra_token_hash(&self) -> &str {
+        &self.scripts.hydra_token_mint.hash
+    }
+
+    /// This deployment's signer for a built-in role
+    pub fn signer(&self, role: &str) -> Option<Arc<RotatingSigner>> {
+        match role {
+            APP_OWNER_ROLE => Some(self.app_owner_signer.clone()),
+            FEE_COLLECTOR_ROLE => Some(self.fee_collector_signer.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Every deployment of the config, looked up by id
+pub struct Deployments {
+    deployments: Vec<Arc<Deployment>>,
+}
+
+impl Deployments {
+    /// Load every deployment; those without a `key_prefix` share the given signers
+    pub async fn load(
+        config: &AppConfig,
+        app_owner_signer: Arc<RotatingSigner>,
+        fee_collector_signer: Arc<RotatingSigner>,
+    ) -> Result<Self, WError> {
+        let mut deployments = Vec::new();
+        for deployment in config.deployments() {
+            let loaded = match &deployment.key_prefix {
+                Some(prefix) => Deployment::new(
+                    &deployment,
+                    config.network_id,
+                    load_rotating_signer(
+                        config,
+                        &format!("{}/{}", deployment.id, APP_OWNER_ROLE),
+                        format!("{}_{}", prefix, APP_OWNER_PREFIX),
+                        Derivation::default(),
+                    )
+                    .await?,
+                    load_rotating_signer(
+                        config,
+                        &format!("{}/{}", deployment.id, FEE_COLLECTOR_ROLE),
+                        format!("{}_{}", prefix, FEE_COLLECTOR_PREFIX),
+                        Derivation::default(),
+                    )
+                    .await?,
+                    true,
+                )?,
+                None => Deployment::new(
+                    &deployment,
+                    config.network_id,
+                    app_owner_signer.clone(),
+                    fee_collector_signer.clone(),
+                    false,
+                )?,
+            };
+            deployments.push(Arc::new(loaded));
+        }
+        Ok(Deployments { deployments })
+    }
+
+    pub fn new(deployments: Vec<Deployment>) -> Self {
+        Deployments {
+            deployments: deployments.into_iter().map(Arc::new).collect(),
+        }
+    }
+
+    /// The deployment with `id`, or the default (first) one when `id` is empty
+    pub fn get(&self, id: &str) -> Result<Arc<Deployment>, WError> {
+        let deployment = if id.is_empty() {
+            self.deployments.first()
+        } else {
+            self.deployments
+                .iter()
+                .find(|deployment| deployment.id == id)
+        };
+        deployment
+            .cloned()
+            .ok_or_else(|| WError::new("Deployments - get", &format!("Unknown deployment: {}", id)))
+    }
+
+    pub fn all(&self) -> &[Arc<Deployment>] {
+        &self.deployments
+    }
+
+    /// Signers held by a single deployment, not shared with the default one
+    pub fn own_signers(&self) -> Vec<&RotatingSigner> {
+        self.deployments
+            .iter()
+            .filter(|deployment| deployment.has_own_signers)
+            .flat_map(|deployment| {
+                [
+                    &*deployment.app_owner_signer,
+                    &*deployment.fee_collector_signer,
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::deployment::DEFAULT_DEPLOYMENT_ID,
+        test_utils::{init_test_env, test_deployment, TEST_NETWORK_ID},
+    };
+
+    #[tokio::test]
+    async fn test_deployments_have_separate_scripts_and_tokens() {
+        let deployments = Deployments::new(vec![
+            test_deployment(
+                DEFAULT_DEPLOYMENT_ID,
+                "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d",
+            )
+            .await,
+            test_deployment(
+                "staging",
+                "04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66",
+            )
+            .await,
+        ]);
+
+        let default = deployments.get("").unwrap();
+        let staging = deployments.get("staging").unwrap();
+        assert_eq!(default.id, DEFAULT_DEPLOYMENT_ID);
+        assert!(deployments.get("canary").is_err());
+
+        assert_ne!(default.hydra_token_hash(), staging.hydra_token_hash());
+        assert_ne!(
+            default.scripts.hydra_user_intent_mint.hash,
+            staging.scripts.hydra_user_intent_mint.hash
+        );
+        // The same L1 token has a different Hydra unit per deployment
+        assert_ne!(
+            default.tokens.hydra_to_l1_map(),
+            staging.tokens.hydra_to_l1_map()
+        );
+        assert!(deployments.own_signers().is_empty());
+    }
+
+    #[test]
+    fn test_deployment_scripts_are_computed_at_load() {
+        init_test_env();
+        let oracle_nft = "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d";
+        let first = DeploymentScripts::new(oracle_nft, TEST_NETWORK_ID).unwrap();
+
+        let spend = &first.hydra_account_spend;
+        assert_eq!(spend.script.hash, spend.hash);
+        assert_eq!(
+            spend.script.address.as_deref(),
+            Some(spend.address.as_str())
+        );
+        assert_eq!(
+            spend.script_ref_hex(),
+            crate::utils::hydra::get_script_ref_hex(&spend.cbor).unwrap()
+        );
+        assert_eq!(first.hydra_token_mint.script.address, None);
+    }
+}
@@ -0,0 +1,34 @@
+use whisky::WError;
+
+use crate::{
+    ext_services::{AddTokenRequest, AddTokenResponse},
+    handler::list_tokens::to_token_info,
+    utils::token_registry::{TokenEntry, TokenRegistry},
+};
+
+pub fn handler(
+    request: AddTokenRequest,
+    registry: &TokenRegistry,
+) -> Result<AddTokenResponse, WError> {
+    let decimals = u8::try_from(request.decimals)
+        .map_err(|_| WError::new("AddToken", "decimals is out of range"))?;
+    let min_transfer_amount = match request.min_transfer_amount.as_str() {
+        "" => 0,
+        amount => amount
+            .parse()
+            .map_err(|_| WError::new("AddToken", "min_transfer_amount must be a raw quantity"))?,
+    };
+    let token = TokenEntry {
+        unit: request.unit,
+        ticker: request.ticker,
+        decimals,
+        display_name: request.display_name,
+        min_transfer_amount,
+        delisted: false,
+    };
+    registry.add(token.clone())?;
+    println!("Added token {}", token.unit);
+    Ok(AddTokenResponse {
+        token: Some(to_token_info(&token, registry)),
+    })
+}
@@ -0,0 +1,22 @@
+use whisky::WError;
+
+use crate::{
+    ext_services::{ConvertAmountsRequest, ConvertAmountsResponse, ConvertedAmount},
+    utils::token_registry::TokenRegistry,
+};
+
+pub fn handler(
+    request: ConvertAmountsRequest,
+    registry: &TokenRegistry,
+) -> Result<ConvertAmountsResponse, WError> {
+    let mut amounts = Vec::with_capacity(request.amounts.len());
+    for amount in request.amounts {
+        let (token, quantity) = registry.resolve_amount(&amount.unit, &amount.quantity)?;
+        amounts.push(ConvertedAmount {
+            display: token.display_amount(quantity),
+            quantity: quantity.to_string(),
+            unit: token.unit,
+        });
+    }
+    Ok(ConvertAmountsResponse { amounts })
+}
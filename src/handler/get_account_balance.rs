@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use whisky::{Asset, WError};
+
+use crate::{
+    deployment::Deployment,
+    ext_services::{
+        AccountBalanceUtxo, AccountInfo, ConvertedAmount, GetAccountBalanceRequest,
+        GetAccountBalanceResponse,
+    },
+    hydra_node::utxo_index::HeadUtxoIndex,
+    scripts::UserTradeAccount,
+    utils::{token::to_l1_assets, token_registry::TokenRegistry},
+};
+
+pub fn handler(
+    request: GetAccountBalanceRequest,
+    deployment: &Deployment,
+    utxo_index: Option<&HeadUtxoIndex>,
+) -> Result<GetAccountBalanceResponse, WError> {
+    let account = request
+        .account
+        .ok_or_else(|| WError::new("GetAccountBalance", "account is required"))?;
+    let account = trade_account(
+        &account,
+        &deployment.scripts.hydra_order_book_withdrawal.hash,
+    )?;
+    let index = utxo_index.and_then(HeadUtxoIndex::current).ok_or_else(|| {
+        WError::new(
+            "GetAccountBalance",
+            "The head's UTxO set is not indexed yet, is hydra_node_url set?",
+        )
+    })?;
+
+    let hydra_to_l1 = deployment.tokens.hydra_to_l1_map();
+    let mut totals: BTreeMap<String, u64> = BTreeMap::new();
+    let mut utxos = Vec::new();
+    for utxo in index.account_balance_utxos(&deployment.id, &account)? {
+        let mut l1_assets = Vec::with_capacity(utxo.output.amount.len());
+        let mut unknown_units = Vec::new();
+        for asset in &utxo.output.amount {
+            // A unit the registry does not map is left out of the totals, not fatal
+            match to_l1_assets(std::slice::from_ref(asset), &hydra_to_l1) {
+                Ok(converted) => l1_assets.extend(converted),
+                Err(_) => unknown_units.push(ConvertedAmount {
+                    unit: asset.unit(),
+                    quantity: asset.quantity(),
+                    display: String::new(),
+                }),
+            }
+        }
+        let mut amounts = Vec::with_capacity(l1_assets.len());
+        for asset in &l1_assets {
+            let quantity = raw_quantity(asset)?;
+            let total = totals.entry(asset.unit()).or_default();
+            *total = total.checked_add(quantity).ok_or_else(|| {
+                WError::new(
+                    "GetAccountBalance",
+                    &format!("Balance of {} overflows", asset.unit()),
+                )
+            })?;
+            amounts.push(converted(&deployment.tokens, asset.unit(), quantity));
+        }
+        if request.include_utxos || !unknown_units.is_empty() {
+            utxos.push(AccountBalanceUtxo {
+                tx_hash: utxo.input.tx_hash.clone(),
+                output_index: utxo.input.output_index,
+                amounts,
+                unknown_units,
+            });
+        }
+    }
+
+    Ok(GetAccountBalanceResponse {
+        balances: totals
+            .into_iter()
+            .map(|(unit, quantity)| converted(&deployment.tokens, unit, quantity))
+            .collect(),
+        utxos,
+        snapshot_number: index.snapshot_number,
+    })
+}
+
+/// The account as balance UTxO datums hold it
+fn trade_account(
+    account: &AccountInfo,
+    hydra_order_book_hash: &str,
+) -> Result<UserTradeAccount, WError> {
+    if account.account_type != "spot_account" {
+        return Err(WError::new(
+            "GetAccountBalance",
+            &format!("Unsupported account type: {}", account.account_type),
+        ));
+    }
+    let account_info = hibiki_proto::services::AccountInfo {
+        account_id: account.account_id.clone(),
+        account_type: account.account_type.clone(),
+        master_key: account.master_key.clone(),
+        is_script_master_key: account.is_script_master_key,
+        operation_key: account.operation_key.clone(),
+        is_script_operation_key: account.is_script_operation_key,
+    };
+    Ok(UserTradeAccount::from_proto(
+        &account_info,
+        hydra_order_book_hash,
+    ))
+}
+
+fn raw_quantity(asset: &Asset) -> Result<u64, WError> {
+    asset.quantity().parse().map_err(|_| {
+        WError::new(
+            "GetAccountBalance",
+            &format!("Invalid quantity {} of {}", asset.quantity(), asset.unit()),
+        )
+    })
+}
+
+fn converted(registry: &TokenRegistry, unit: String, quantity: u64) -> ConvertedAmount {
+    ConvertedAmount {
+        display: registry
+            .find(&unit)
+            .map(|token| token.display_amount(quantity))
+            .unwrap_or_default(),
+        quantity: quantity.to_string(),
+        unit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::deployment::DEFAULT_DEPLOYMENT_ID,
+        hydra_node::{utxo_index::DeploymentRoles, HydraUtxoSet},
+        test_utils::{test_deployment, TEST_USDM_UNIT as USDM},
+    };
+    use serde_json::{json, Value};
+    use whisky::data::PlutusDataJson;
+
+    const ORACLE_NFT: &str = "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d";
+    const TX: &str = "522ef27241b80a8ea4f54ed5c1674fcf49638c3a4b65a0e21371a21a0d343956";
+    const ALICE: &str = "2953c409-63d0-4bee-abbe-f11e4c8be0ce";
+    const BOB: &str = "0b9a4a2d-2f4e-4a57-9f0e-5c9e1d6a7b31";
+    const CAROL: &str = "7d1e5b0c-3a8f-4c2d-b6e4-9a0f1c2d3e4f";
+
+    async fn deployment() -> Deployment {
+        test_deployment(DEFAULT_DEPLOYMENT_ID, ORACLE_NFT).await
+    }
+
+    fn account(account_id: &str) -> AccountInfo {
+        AccountInfo {
+            account_id: account_id.to_string(),
+            account_type: "spot_account".to_string(),
+            master_key: "75073cd8873918696e373264763422e5c4d4fe63b4e941c42a5381d6".to_string(),
+            is_script_master_key: false,
+            operation_key: "c472ce633c4acf91dda311f10cb45671d6dcfbae3bdc25d219f83159".to_string(),
+            is_script_operation_key: false,
+        }
+    }
+
+    /// A balance UTxO of `account_id` holding `quantity` of each L1 unit, as Hydra tokens
+    fn balance_utxo(
+        deployment: &Deployment,
+        account_id: &str,
+        quantities: &[(&str, u64)],
+    ) -> Value {
+        let datum = trade_account(
+            &account(account_id),
+            &deployment.scripts.hydra_order_book_withdrawal.hash,
+        )
+        .unwrap();
+        let mut hydra_tokens = serde_json::Map::new();
+        for (unit, quantity) in quantities {
+            let token = deployment.tokens.find(unit).unwrap();
+            let hydra_unit = token.hydra_unit(deployment.hydra_token_hash());
+            hydra_tokens.insert(hydra_unit[56..].to_string(), json!(quantity));
+        }
+        json!({
+            "address": deployment.scripts.hydra_account_spend.address,
+            "value": { "lovelace": 0, deployment.hydra_token_hash(): hydra_tokens },
+            "inlineDatum": serde_json::from_str::<Value>(&datum.to_json_string()).unwrap(),
+        })
+    }
+
+    fn request(account_id: &str, include_utxos: bool) -> GetAccountBalanceRequest {
+        GetAccountBalanceRequest {
+            account: Some(account(account_id)),
+            include_utxos,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_account_balance() {
+        let deployment = deployment().await;
+        let utxo: HydraUtxoSet = serde_json::from_value(json!({
+            format!("{}#0", TX): balance_utxo(&deployment, ALICE, &[(USDM, 12_500_000)]),
+            format!("{}#1", TX): balance_utxo(&deployment, ALICE, &[(USDM, 500_000), ("lovelace", 2_000_000)]),
+            format!("{}#2", TX): balance_utxo(&deployment, BOB, &[(USDM, 1)]),
+        }))
+        .unwrap();
+        let head = HeadUtxoIndex::new(vec![DeploymentRoles::new(&deployment)], None).unwrap();
+        head.apply(4, &utxo).unwrap();
+
+        let reply = handler(request(ALICE, false), &deployment, Some(&head)).unwrap();
+        assert_eq!(reply.snapshot_number, 4);
+        assert!(reply.utxos.is_empty());
+        let usdm = reply
+            .balances
+            .iter()
+            .find(|balance| balance.unit == USDM)
+            .unwrap();
+        assert_eq!(usdm.quantity, "13000000");
+        assert_eq!(usdm.display, "13 USDM");
+        let lovelace = reply
+            .balances
+            .iter()
+            .find(|balance| balance.unit == "lovelace")
+            .unwrap();
+        assert_eq!(lovelace.quantity, "2000000");
+
+        let reply = handler(request(ALICE, true), &deployment, Some(&head)).unwrap();
+        assert_eq!(reply.utxos.len(), 2);
+        assert_eq!(reply.utxos[1].output_index, 1);
+        assert_eq!(reply.utxos[1].amounts.len(), 2);
+
+        let reply = handler(request(CAROL, true), &deployment, Some(&head)).unwrap();
+        assert!(reply.balances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_balance_errors() {
+        let deployment = deployment().await;
+        assert!(handler(request(ALICE, false), &deployment, None).is_err());
+
+        let head = HeadUtxoIndex::new(vec![DeploymentRoles::new(&deployment)], None).unwrap();
+        head.apply(1, &HydraUtxoSet::new()).unwrap();
+        let mut funding = request(ALICE, false);
+        funding.account.as_mut().unwrap().account_type = "funding_account".to_string();
+        assert!(handler(funding, &deployment, Some(&head)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_units_are_reported_per_utxo() {
+        let deployment = deployment().await;
+        let mut unknown = balance_utxo(&deployment, ALICE, &[(USDM, 3)]);
+        let hydra_tokens = &mut unknown["value"][deployment.hydra_token_hash()];
+        hydra_tokens["00ff"] = json!(9);
+        let utxo: HydraUtxoSet = serde_json::from_value(json!({
+            format!("{}#0", TX): balance_utxo(&deployment, ALICE, &[(USDM, 2)]),
+            format!("{}#1", TX): unknown,
+        }))
+        .unwrap();
+        let head = HeadUtxoIndex::new(vec![DeploymentRoles::new(&deployment)], None).unwrap();
+        head.apply(5, &utxo).unwrap();
+
+        let reply = handler(request(ALICE, false), &deployment, Some(&head)).unwrap();
+        assert_eq!(reply.balances.len(), 1);
+        assert_eq!(reply.balances[0].quantity, "5");
+        // Only the UTxO holding the unknown unit is listed
+        assert_eq!(reply.utxos.len(), 1);
+        assert_eq!(reply.utxos[0].output_index, 1);
+        assert_eq!(reply.utxos[0].amounts.len(), 1);
+        let unknown_unit = &reply.utxos[0].unknown_units[0];
+        assert_eq!(
+            unknown_unit.unit,
+            format!("{}00ff", deployment.hydra_token_hash())
+        );
+        assert_eq!(unknown_unit.quantity, "9");
+    }
+}
@@ -0,0 +1,48 @@
+use whisky::WError;
+
+use crate::{
+    deployment::Deployment,
+    ext_services::{
+        BlueprintInfo, GetDeploymentInfoRequest, GetDeploymentInfoResponse, ValidatorInfo,
+    },
+    scripts::blueprint::LoadedBlueprint,
+};
+
+pub fn handler(
+    _request: GetDeploymentInfoRequest,
+    deployment: &Deployment,
+    blueprint: &LoadedBlueprint,
+) -> Result<GetDeploymentInfoResponse, WError> {
+    let preamble = &blueprint.preamble;
+    Ok(GetDeploymentInfoResponse {
+        deployment_id: deployment.id.clone(),
+        dex_oracle_nft: deployment.dex_oracle_nft.clone(),
+        blueprint: Some(BlueprintInfo {
+            source: blueprint.source.clone(),
+            title: preamble.title.clone(),
+            description: preamble.description.clone(),
+            version: preamble.version.clone(),
+            plutus_version: preamble.plutus_version.clone(),
+            compiler_name: preamble.compiler.name.clone(),
+            compiler_version: preamble.compiler.version.clone(),
+            license: preamble.license.clone(),
+            validators: blueprint
+                .validators
+                .iter()
+                .map(|validator| ValidatorInfo {
+                    title: validator.title.clone(),
+                    hash: validator.hash.clone(),
+                })
+                .collect(),
+        }),
+        scripts: deployment
+            .scripts
+            .hashes()
+            .into_iter()
+            .map(|(title, hash)| ValidatorInfo {
+                title: title.to_string(),
+                hash: hash.to_string(),
+            })
+            .collect(),
+    })
+}
@@ -5,43 +5,63 @@ use whisky::{
 };
 
 use crate::{
-    config::AppConfig,
-    constant::{dex_oracle_nft, l2_ref_scripts_index},
-    scripts::{
-        hydra_user_intent_mint_minting_blueprint, hydra_user_intent_spend_spending_blueprint,
-        MasterIntent, MintMasterIntent, TransferIntent, UserTradeAccount,
-    },
+    constant::l2_ref_scripts_index,
+    deployment::Deployment,
+    hydra_node::utxo_index::{HeadUtxoIndex, InputResolver, UtxoRole},
+    scripts::{MasterIntent, MintMasterIntent, TransferIntent, UserTradeAccount},
     utils::{
-        hydra::{get_hydra_tx_builder, get_script_ref_hex},
+        hydra::get_hydra_tx_builder,
         proto::{assets_to_mvalue, from_proto_amount, from_proto_utxo},
         token::to_hydra_token,
     },
 };
 
 // Changed from async to synchronous function to avoid Send issues with Rc<T>
-pub async fn handler(request: InternalTransferRequest) -> Result<IntentTxResponse, WError> {
-    let AppConfig { app_owner_vkey, .. } = AppConfig::new();
-    let collateral = from_proto_utxo(request.collateral_utxo.as_ref().unwrap());
-    let empty_utxo = from_proto_utxo(request.empty_utxo.as_ref().unwrap());
-    let ref_input = from_proto_utxo(request.dex_order_book_utxo.as_ref().unwrap());
+pub async fn handler(
+    request: InternalTransferRequest,
+    deployment: &Deployment,
+    utxo_index: Option<&HeadUtxoIndex>,
+) -> Result<IntentTxResponse, WError> {
+    let resolver = InputResolver::new(utxo_index, &deployment.id);
+    let collateral = resolver.utxo(
+        request.collateral_utxo.as_ref().map(from_proto_utxo),
+        UtxoRole::Collateral,
+        "collateral_utxo",
+    )?;
+    let empty_utxo = resolver.utxo(
+        request.empty_utxo.as_ref().map(from_proto_utxo),
+        UtxoRole::Empty,
+        "empty_utxo",
+    )?;
+    let ref_input = resolver.utxo(
+        request.dex_order_book_utxo.as_ref().map(from_proto_utxo),
+        UtxoRole::OrderBook,
+        "dex_order_book_utxo",
+    )?;
+    resolver.check_unspent(&[&collateral, &empty_utxo, &ref_input])?;
     let account = request.account.unwrap();
 
     let mut tx_builder = get_hydra_tx_builder();
-    let policy_id = whisky::data::PolicyId::new(dex_oracle_nft());
-    let user_intent_mint = hydra_user_intent_mint_minting_blueprint(&policy_id);
-    let user_intent_spend = hydra_user_intent_spend_spending_blueprint(&policy_id);
+    let scripts = &deployment.scripts;
+    let user_intent_mint = &scripts.hydra_user_intent_mint;
+    let user_intent_spend = &scripts.hydra_user_intent_spend;
 
-    let from_account = UserTradeAccount::from_proto(&account);
-    let to_account = UserTradeAccount::from_proto(&request.receiver_account.unwrap());
+    let order_book_hash = &scripts.hydra_order_book_withdrawal.hash;
+    let from_account = UserTradeAccount::from_proto(&account, order_book_hash);
+    let to_account =
+        UserTradeAccount::from_proto(&request.receiver_account.unwrap(), order_book_hash);
+    let to_transfer = deployment
+        .tokens
+        .validate_transfer(&from_proto_amount(&request.to_transfer))?;
     let transfer_amount_l2 =
-        assets_to_mvalue(&to_hydra_token(&from_proto_amount(&request.to_transfer)));
+        assets_to_mvalue(&to_hydra_token(&to_transfer, deployment.hydra_token_hash()));
 
     // Create transfer intent
     let hydra_account_intent = TransferIntent::new(to_account.clone(), transfer_amount_l2);
     let redeemer_json = MintMasterIntent::new(from_account.clone(), hydra_account_intent.clone());
     let datum_json = MasterIntent::new(from_account, hydra_account_intent);
 
-    let intent_script_ref_hex = Some(get_script_ref_hex(&user_intent_mint.cbor)?);
+    let intent_script_ref_hex = Some(user_intent_mint.script_ref_hex().to_string());
     let intent_mint_ref_utxo = UTxO {
         input: UtxoInput {
             output_index: l2_ref_scripts_index::hydra_user_intent::MINT,
@@ -86,7 +106,7 @@ pub async fn handler(request: InternalTransferRequest) -> Result<IntentTxRespons
         )
         .input_for_evaluation(&empty_utxo)
         .tx_out(&empty_utxo.output.address, &empty_utxo.output.amount)
-        .required_signer_hash(&app_owner_vkey)
+        .required_signer_hash(&deployment.app_owner_vkey)
         .required_signer_hash(&account.master_key)
         .tx_in_collateral(
             &collateral.input.tx_hash,
@@ -0,0 +1,31 @@
+use whisky::WError;
+
+use crate::{
+    ext_services::{ListTokensRequest, ListTokensResponse, TokenInfo},
+    utils::token_registry::{TokenEntry, TokenRegistry},
+};
+
+pub fn handler(
+    _request: ListTokensRequest,
+    registry: &TokenRegistry,
+) -> Result<ListTokensResponse, WError> {
+    Ok(ListTokensResponse {
+        tokens: registry
+            .list()
+            .iter()
+            .map(|token| to_token_info(token, registry))
+            .collect(),
+    })
+}
+
+pub fn to_token_info(token: &TokenEntry, registry: &TokenRegistry) -> TokenInfo {
+    TokenInfo {
+        unit: token.unit.clone(),
+        hydra_unit: token.hydra_unit(registry.hydra_token_hash()),
+        ticker: token.ticker.clone(),
+        decimals: token.decimals as u32,
+        display_name: token.display_name.clone(),
+        min_transfer_amount: token.min_transfer_amount.to_string(),
+        delisted: token.delisted,
+    }
+}
@@ -0,0 +1,14 @@
+use whisky::{calculate_tx_hash, WError};
+
+use crate::{
+    ext_services::{MergeTransactionWitnessesRequest, MergeTransactionWitnessesResponse},
+    utils::witness::merge_vkey_witnesses,
+};
+
+pub fn handler(
+    request: MergeTransactionWitnessesRequest,
+) -> Result<MergeTransactionWitnessesResponse, WError> {
+    let signed_tx = merge_vkey_witnesses(&request.tx_hex, &request.vkey_witnesses)?;
+    let tx_hash = calculate_tx_hash(&signed_tx)?;
+    Ok(MergeTransactionWitnessesResponse { signed_tx, tx_hash })
+}
@@ -1,5 +1,17 @@
+pub mod add_token;
+pub mod convert_amounts;
+pub mod get_account_balance;
+pub mod get_deployment_info;
 pub mod internal_transfer;
+pub mod list_tokens;
+pub mod merge_transaction_witnesses;
 pub mod process_transfer;
+pub mod remove_token;
+pub mod rotate_signing_keys;
 pub mod serialize_transfer_intent_datum;
 pub mod sign_transaction;
 pub mod sign_transaction_with_fee_collector;
+pub mod sign_transaction_with_role;
+pub mod sign_transaction_witness;
+pub mod submit_and_confirm_tx;
+pub mod verify_transaction_signatures;
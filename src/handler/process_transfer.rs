@@ -5,54 +5,94 @@ use std::collections::HashMap;
 use whisky::{
     calculate_tx_hash,
     data::{Constr, List, PlutusData, PlutusDataJson},
-    Budget, UTxO, UtxoInput, UtxoOutput, WData, WError, WRedeemer, Wallet,
+    Asset, Budget, UTxO, UtxoInput, UtxoOutput, WData, WError, WRedeemer,
 };
 
 use crate::{
-    config::AppConfig,
-    constant::{all_hydra_to_l1_token_map, dex_oracle_nft, l2_ref_scripts_index},
+    constant::l2_ref_scripts_index,
+    deployment::Deployment,
     handler::sign_transaction::check_signature_sign_tx,
+    hydra_node::utxo_index::{HeadUtxoIndex, InputResolver, UtxoRole},
     scripts::{
-        hydra_account_spend_spending_blueprint, hydra_account_withdraw_withdrawal_blueprint,
-        hydra_user_intent_mint_minting_blueprint, hydra_user_intent_spend_spending_blueprint,
         HydraAccountOperation, HydraAccountRedeemer, HydraUserIntentRedeemer, UserTradeAccount,
     },
+    signer::Signer,
     utils::{
-        hydra::{get_hydra_tx_builder, get_script_ref_hex},
+        audit_log::SigningContext,
+        hydra::get_hydra_tx_builder,
         proto::{
             extract_transfer_amount_from_intent, from_proto_balance_utxos, from_proto_utxo,
             to_proto_amount,
         },
-        token::{to_hydra_token, to_l1_assets},
+        token::{remaining_balance, to_hydra_token, to_l1_assets},
     },
 };
 
 pub async fn handler(
     request: ProcessTransferRequest,
-    app_owner_wallet: &Wallet,
+    app_owner_signer: &dyn Signer,
+    ctx: &SigningContext,
+    deployment: &Deployment,
+    utxo_index: Option<&HeadUtxoIndex>,
 ) -> Result<ProcessTransferResponse, WError> {
-    let AppConfig { app_owner_vkey, .. } = AppConfig::new();
-
-    let collateral = from_proto_utxo(request.collateral_utxo.as_ref().unwrap());
-    let ref_input = from_proto_utxo(request.dex_order_book_utxo.as_ref().unwrap());
+    let hydra_to_l1 = deployment.tokens.hydra_to_l1_map();
+    let scripts = &deployment.scripts;
+    let resolver = InputResolver::new(utxo_index, &deployment.id);
+    let collateral = resolver.utxo(
+        request.collateral_utxo.as_ref().map(from_proto_utxo),
+        UtxoRole::Collateral,
+        "collateral_utxo",
+    )?;
+    let ref_input = resolver.utxo(
+        request.dex_order_book_utxo.as_ref().map(from_proto_utxo),
+        UtxoRole::OrderBook,
+        "dex_order_book_utxo",
+    )?;
     let intent_utxo = from_proto_utxo(request.transferral_intent_utxo.as_ref().unwrap());
-    let from_account = UserTradeAccount::from_proto(request.account.as_ref().unwrap());
-    let to_account = UserTradeAccount::from_proto(request.receiver_account.as_ref().unwrap());
+    let order_book_hash = &scripts.hydra_order_book_withdrawal.hash;
+    let from_account =
+        UserTradeAccount::from_proto(request.account.as_ref().unwrap(), order_book_hash);
+    let to_account =
+        UserTradeAccount::from_proto(request.receiver_account.as_ref().unwrap(), order_book_hash);
 
-    // Parse sender's balance UTXOs
-    let (from_updated_balance_l1, from_account_utxos) =
-        from_proto_balance_utxos(request.account_balance_utxos.as_ref().unwrap());
+    // Parse sender's balance UTXOs; the index finds them when none are given
+    let given_balance = request
+        .account_balance_utxos
+        .as_ref()
+        .map(from_proto_balance_utxos);
+    let given_utxos = given_balance
+        .as_ref()
+        .map(|(_, utxos)| utxos.clone())
+        .unwrap_or_default();
+    let from_account_utxos =
+        resolver.account_balance_utxos(given_utxos, &from_account, "account_balance_utxos")?;
+    let mut inputs = vec![&collateral, &ref_input, &intent_utxo];
+    inputs.extend(&from_account_utxos);
+    resolver.check_unspent(&inputs)?;
 
     let to_updated_balance_l2 = extract_transfer_amount_from_intent(&intent_utxo)?;
 
+    // Without a given balance, the sender keeps what its UTxOs hold less the transfer
+    let from_updated_balance_l1 = match given_balance {
+        Some((updated_balance, _)) => updated_balance,
+        None => {
+            let balance: Vec<Asset> = from_account_utxos
+                .iter()
+                .flat_map(|utxo| utxo.output.amount.iter().cloned())
+                .collect();
+            let remaining = remaining_balance(&balance, &to_updated_balance_l2)
+                .map_err(WError::from_err("remaining_balance"))?;
+            to_l1_assets(&remaining, &hydra_to_l1).map_err(WError::from_err("to_l1_assets"))?
+        }
+    };
+
     // For outputs, we need one UTXO address - use the first sender's UTXO address as template
     let account_balance_address = &from_account_utxos[0].output.address;
 
-    let policy_id = whisky::data::PolicyId::new(dex_oracle_nft());
-    let user_intent_mint = hydra_user_intent_mint_minting_blueprint(&policy_id);
-    let user_intent_spend = hydra_user_intent_spend_spending_blueprint(&policy_id);
-    let account_balance_spend = hydra_account_spend_spending_blueprint(&policy_id);
-    let internal_transfer_withdraw = hydra_account_withdraw_withdrawal_blueprint(&policy_id);
+    let user_intent_mint = &scripts.hydra_user_intent_mint;
+    let user_intent_spend = &scripts.hydra_user_intent_spend;
+    let account_balance_spend = &scripts.hydra_account_spend;
+    let internal_transfer_withdraw = &scripts.hydra_account_withdrawal;
 
     let mut from_unit_tx_index_map: HashMap<String, AssetList> =
         HashMap::with_capacity(from_updated_balance_l1.len());
@@ -61,7 +101,7 @@ pub async fn handler(
 
     let mut current_index = 0u32;
 
-    let intent_mint_script_ref_hex = Some(get_script_ref_hex(&user_intent_mint.cbor)?);
+    let intent_mint_script_ref_hex = Some(user_intent_mint.script_ref_hex().to_string());
     let intent_mint_ref_utxo = UTxO {
         input: UtxoInput {
             output_index: l2_ref_scripts_index::hydra_user_intent::MINT,
@@ -77,7 +117,7 @@ pub async fn handler(
         },
     };
 
-    let intent_spend_script_ref_hex = Some(get_script_ref_hex(&user_intent_spend.cbor)?);
+    let intent_spend_script_ref_hex = Some(user_intent_spend.script_ref_hex().to_string());
     let intent_spend_ref_utxo = UTxO {
         input: UtxoInput {
             output_index: l2_ref_scripts_index::hydra_user_intent::SPEND,
@@ -94,7 +134,7 @@ pub async fn handler(
     };
 
     let account_balance_spend_script_ref_hex =
-        Some(get_script_ref_hex(&account_balance_spend.cbor)?);
+        Some(account_balance_spend.script_ref_hex().to_string());
     let account_balance_spend_ref_utxo = UTxO {
         input: UtxoInput {
             output_index: l2_ref_scripts_index::hydra_account_balance::SPEND,
@@ -111,7 +151,7 @@ pub async fn handler(
     };
 
     let account_balance_withdrawal_script_ref_hex =
-        Some(get_script_ref_hex(&account_balance_spend.cbor)?);
+        Some(account_balance_spend.script_ref_hex().to_string());
     let account_balance_withdrawal_ref_utxo = UTxO {
         input: UtxoInput {
             output_index: l2_ref_scripts_index::hydra_account_balance::WITHDRAWAL,
@@ -186,7 +226,7 @@ pub async fn handler(
         tx_builder
             .tx_out(
                 account_balance_address,
-                &to_hydra_token(std::slice::from_ref(&asset)),
+                &to_hydra_token(std::slice::from_ref(&asset), deployment.hydra_token_hash()),
             )
             .tx_out_inline_datum_value(&WData::JSON(from_account.to_json_string()));
 
@@ -204,7 +244,7 @@ pub async fn handler(
             .tx_out(account_balance_address, std::slice::from_ref(&asset))
             .tx_out_inline_datum_value(&WData::JSON(to_account.to_json_string()));
 
-        let l1_assets = to_l1_assets(std::slice::from_ref(&asset), all_hydra_to_l1_token_map())
+        let l1_assets = to_l1_assets(std::slice::from_ref(&asset), &hydra_to_l1)
             .map_err(WError::from_err("to_l1_assets"))?;
 
         to_unit_tx_index_map.insert(
@@ -252,13 +292,13 @@ pub async fn handler(
         )
         .input_for_evaluation(&collateral)
         .change_address(&request.address)
-        .required_signer_hash(&app_owner_vkey)
+        .required_signer_hash(&deployment.app_owner_vkey)
         .complete(None)
         .await?;
 
     let tx_hex = tx_builder.tx_hex();
     let tx_hash = calculate_tx_hash(&tx_hex)?;
-    let signed_tx = check_signature_sign_tx(app_owner_wallet, &tx_hex)?;
+    let signed_tx = check_signature_sign_tx(app_owner_signer, &tx_hex, ctx).await?;
 
     Ok(ProcessTransferResponse {
         signed_tx,
@@ -0,0 +1,18 @@
+use whisky::WError;
+
+use crate::{
+    ext_services::{RemoveTokenRequest, RemoveTokenResponse},
+    handler::list_tokens::to_token_info,
+    utils::token_registry::TokenRegistry,
+};
+
+pub fn handler(
+    request: RemoveTokenRequest,
+    registry: &TokenRegistry,
+) -> Result<RemoveTokenResponse, WError> {
+    let token = registry.remove(&request.unit)?;
+    println!("Removed token {}", token.unit);
+    Ok(RemoveTokenResponse {
+        token: Some(to_token_info(&token, registry)),
+    })
+}
@@ -0,0 +1,39 @@
+use whisky::WError;
+
+use crate::{
+    ext_services::{RotateSigningKeysRequest, RotateSigningKeysResponse, RotatedKey},
+    signer::RotatingSigner,
+};
+
+pub async fn handler(
+    request: RotateSigningKeysRequest,
+    signers: &[&RotatingSigner],
+) -> Result<RotateSigningKeysResponse, WError> {
+    let selected: Vec<&RotatingSigner> = signers
+        .iter()
+        .copied()
+        .filter(|signer| request.role.is_empty() || signer.role() == request.role)
+        .collect();
+    if selected.is_empty() {
+        return Err(WError::new(
+            "RotateSigningKeys",
+            &format!("Unknown role: {}", request.role),
+        ));
+    }
+
+    let mut keys = Vec::with_capacity(selected.len());
+    for signer in selected {
+        let rotation = signer.rotate().await?;
+        println!(
+            "Rotated {} signing key: {} -> {}",
+            rotation.role, rotation.previous_key_hash, rotation.key_hash
+        );
+        keys.push(RotatedKey {
+            changed: rotation.changed(),
+            role: rotation.role,
+            key_hash: rotation.key_hash,
+            previous_key_hash: rotation.previous_key_hash,
+        });
+    }
+    Ok(RotateSigningKeysResponse { keys })
+}
@@ -1,6 +1,7 @@
 use whisky::{data::PlutusDataJson, WData, WError};
 
 use crate::{
+    deployment::Deployment,
     scripts::{MasterIntent, TransferIntent, UserTradeAccount},
     services::{SerializeDatumResponse, SerializeTransferalIntentDatumRequest},
     utils::{
@@ -11,11 +12,17 @@ use crate::{
 
 pub fn handler(
     request: SerializeTransferalIntentDatumRequest,
+    deployment: &Deployment,
 ) -> Result<SerializeDatumResponse, WError> {
-    let from_account = UserTradeAccount::from_proto(&request.account.unwrap());
-    let to_account = UserTradeAccount::from_proto(&request.receiver_account.unwrap());
+    let order_book_hash = &deployment.scripts.hydra_order_book_withdrawal.hash;
+    let from_account = UserTradeAccount::from_proto(&request.account.unwrap(), order_book_hash);
+    let to_account =
+        UserTradeAccount::from_proto(&request.receiver_account.unwrap(), order_book_hash);
+    let to_transfer = deployment
+        .tokens
+        .validate_transfer(&from_proto_amount(&request.to_transfer))?;
     let transfer_amount =
-        assets_to_mvalue(&to_hydra_token(&from_proto_amount(&request.to_transfer)));
+        assets_to_mvalue(&to_hydra_token(&to_transfer, deployment.hydra_token_hash()));
 
     let hydra_account_intent = TransferIntent::new(to_account, transfer_amount);
     let datum_json = MasterIntent::new(from_account, hydra_account_intent);
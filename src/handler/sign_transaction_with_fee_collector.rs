@@ -1,15 +1,24 @@
 use hibiki_proto::services::{SignTransactionRequest, SignTransactionResponse};
-use whisky::{calculate_tx_hash, WError, Wallet};
+use whisky::{calculate_tx_hash, WError};
 
-use crate::handler::sign_transaction::check_signature_sign_tx;
+use crate::{
+    config::signing_policy::FeeCollectorPolicy, handler::sign_transaction::check_signature_sign_tx,
+    hydra_node::utxo_index::UtxoIndex, signer::Signer, utils::audit_log::SigningContext,
+};
 
-pub fn handler(
+pub async fn handler(
     request: SignTransactionRequest,
-    fee_collector_owner_wallet: &Wallet,
+    fee_collector_signer: &dyn Signer,
+    fee_collector_policy: &FeeCollectorPolicy,
+    utxos: Option<&UtxoIndex>,
+    ctx: &SigningContext,
 ) -> Result<SignTransactionResponse, WError> {
     let tx_hex = request.tx_hex;
 
-    let signed_tx = check_signature_sign_tx(fee_collector_owner_wallet, &tx_hex)?;
+    fee_collector_policy
+        .check(&tx_hex, utxos)
+        .or_else(|e| ctx.audited(&tx_hex, Err(e)))?;
+    let signed_tx = check_signature_sign_tx(fee_collector_signer, &tx_hex, ctx).await?;
     let tx_hash = calculate_tx_hash(&signed_tx)?;
     let reply = SignTransactionResponse { signed_tx, tx_hash };
     Ok(reply)
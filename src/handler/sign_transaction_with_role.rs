@@ -0,0 +1,19 @@
+use whisky::{calculate_tx_hash, WError};
+
+use crate::{
+    ext_services::{SignTransactionWithRoleRequest, SignTransactionWithRoleResponse},
+    handler::sign_transaction::check_signature_sign_tx,
+    signer::Signer,
+    utils::audit_log::SigningContext,
+};
+
+pub async fn handler(
+    request: SignTransactionWithRoleRequest,
+    signer: &dyn Signer,
+    ctx: &SigningContext,
+) -> Result<SignTransactionWithRoleResponse, WError> {
+    let tx_hex = request.tx_hex;
+    let signed_tx = check_signature_sign_tx(signer, &tx_hex, ctx).await?;
+    let tx_hash = calculate_tx_hash(&signed_tx)?;
+    Ok(SignTransactionWithRoleResponse { signed_tx, tx_hash })
+}
@@ -0,0 +1,22 @@
+use whisky::{calculate_tx_hash, WError};
+
+use crate::{
+    ext_services::{SignTransactionWitnessRequest, SignTransactionWitnessResponse},
+    signer::Signer,
+    utils::{audit_log::SigningContext, witness::extract_new_vkey_witnesses},
+};
+
+pub async fn handler(
+    request: SignTransactionWitnessRequest,
+    signer: &dyn Signer,
+    ctx: &SigningContext,
+) -> Result<SignTransactionWitnessResponse, WError> {
+    let tx_hex = request.tx_hex;
+    let signed_tx = ctx.audited(&tx_hex, signer.sign_tx(&tx_hex).await)?;
+    let vkey_witnesses = extract_new_vkey_witnesses(&tx_hex, &signed_tx)?;
+    let tx_hash = calculate_tx_hash(&tx_hex)?;
+    Ok(SignTransactionWitnessResponse {
+        vkey_witnesses,
+        tx_hash,
+    })
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+use whisky::{calculate_tx_hash, WError};
+
+use crate::{
+    config::AppConfig,
+    ext_services::{SubmitAndConfirmTxRequest, SubmitAndConfirmTxResponse, TxInvalid},
+    hydra_node::{HydraNodeClient, TxConfirmation},
+};
+
+/// Longest a request may ask to wait for its confirmation
+const MAX_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub async fn handler(
+    request: SubmitAndConfirmTxRequest,
+    hydra_node: Option<&HydraNodeClient>,
+    config: &AppConfig,
+) -> Result<SubmitAndConfirmTxResponse, WError> {
+    let Some(hydra_node) = hydra_node else {
+        return Err(WError::new(
+            "SubmitAndConfirmTx",
+            "No hydra-node configured, set hydra_node_url",
+        ));
+    };
+    let mut policy = config.submit_policy();
+    if request.timeout_ms > 0 {
+        policy.timeout = Duration::from_millis(request.timeout_ms).min(MAX_TIMEOUT);
+    }
+
+    let tx_hash = calculate_tx_hash(&request.tx_hex)?;
+    let reply = match hydra_node
+        .submit_and_confirm(&request.tx_hex, &policy)
+        .await?
+    {
+        TxConfirmation::Confirmed {
+            snapshot_number,
+            attempts,
+        } => SubmitAndConfirmTxResponse {
+            tx_hash,
+            confirmed: true,
+            snapshot_number,
+            invalid: None,
+            attempts,
+        },
+        TxConfirmation::Invalid { reason, attempts } => SubmitAndConfirmTxResponse {
+            tx_hash,
+            confirmed: false,
+            snapshot_number: 0,
+            invalid: Some(TxInvalid { reason }),
+            attempts,
+        },
+    };
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hydra_node::{mock::MockHydraNode, HydraNodeConfig, HydraUtxoSet};
+
+    const TX_HEX: &str = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca10081825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402f5d90103a0";
+
+    fn request() -> SubmitAndConfirmTxRequest {
+        SubmitAndConfirmTxRequest {
+            tx_hex: TX_HEX.to_string(),
+            timeout_ms: 5_000,
+        }
+    }
+
+    fn config() -> AppConfig {
+        AppConfig {
+            hydra_confirm_timeout_ms: 30_000,
+            hydra_resubmit_interval_ms: 5_000,
+            hydra_max_submit_attempts: 3,
+            ..AppConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_confirm_tx() {
+        let node = MockHydraNode::start().await;
+        node.open_head(HydraUtxoSet::new());
+        let (client, _) = HydraNodeClient::connect(HydraNodeConfig::new(&node.url()));
+
+        let reply = handler(request(), Some(&client), &config()).await.unwrap();
+        assert_eq!(reply.tx_hash, calculate_tx_hash(TX_HEX).unwrap());
+        assert!(reply.confirmed);
+        assert_eq!(reply.snapshot_number, 1);
+        assert_eq!(reply.attempts, 1);
+
+        node.reject_txs("BadInputsUTxO");
+        let reply = handler(request(), Some(&client), &config()).await.unwrap();
+        assert!(!reply.confirmed);
+        assert_eq!(reply.invalid.unwrap().reason, "BadInputsUTxO");
+    }
+
+    #[tokio::test]
+    async fn test_no_hydra_node_configured() {
+        assert!(handler(request(), None, &config()).await.is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use whisky::{calculate_tx_hash, WError};
+
+use crate::{
+    ext_services::{VerifyTransactionSignaturesRequest, VerifyTransactionSignaturesResponse},
+    signer::Signer,
+    utils::witness::{required_signers, verify_signatures},
+};
+
+pub async fn handler(
+    request: VerifyTransactionSignaturesRequest,
+    app_owner_signer: &dyn Signer,
+    fee_collector_signer: &dyn Signer,
+) -> Result<VerifyTransactionSignaturesResponse, WError> {
+    let tx_hex = request.tx_hex;
+    let report = verify_signatures(&tx_hex)?;
+    let required = required_signers(&tx_hex)?;
+    let app_owner_required = required.contains(&app_owner_signer.key_hash().await?);
+    let fee_collector_required = required.contains(&fee_collector_signer.key_hash().await?);
+
+    Ok(VerifyTransactionSignaturesResponse {
+        tx_hash: calculate_tx_hash(&tx_hex)?,
+        fully_signed: report.fully_signed,
+        present_signers: report.present_signers,
+        missing_signers: report.missing_signers,
+        invalid_witnesses: report.invalid_witnesses,
+        extraneous_witnesses: report.extraneous_witnesses,
+        app_owner_required,
+        fee_collector_required,
+    })
+}
@@ -0,0 +1,374 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use whisky::{csl, Asset, UTxO, UtxoInput, UtxoOutput, WError};
+
+use crate::{scripts::blueprint::script_hash, utils::hydra::get_script_ref_hex};
+
+/// Envelope type of the txs hibiki submits
+pub const TX_ENVELOPE_TYPE: &str = "Tx ConwayEra";
+
+/// A tx as hydra-node exchanges it, a cardano-cli text envelope
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HydraTransaction {
+    #[serde(rename = "type")]
+    pub envelope_type: String,
+    #[serde(default)]
+    pub description: String,
+    pub cbor_hex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<String>,
+}
+
+impl HydraTransaction {
+    pub fn new(tx_hex: &str) -> Self {
+        HydraTransaction {
+            envelope_type: TX_ENVELOPE_TYPE.to_string(),
+            description: String::new(),
+            cbor_hex: tx_hex.to_string(),
+            tx_id: None,
+        }
+    }
+}
+
+/// Commands sent to hydra-node
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "tag")]
+pub enum ClientInput {
+    NewTx { transaction: HydraTransaction },
+    GetUTxO,
+}
+
+/// An output of the head's UTxO set, in cardano-api JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HydraTxOut {
+    pub address: String,
+    /// `lovelace` to a quantity, and each policy id to asset names to quantities
+    pub value: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub datumhash: Option<String>,
+    #[serde(default)]
+    pub inline_datum: Option<Value>,
+    /// CBOR of `inline_datum`, left out by older hydra-node versions
+    #[serde(default)]
+    pub inline_datum_raw: Option<String>,
+    #[serde(default)]
+    pub reference_script: Option<Value>,
+}
+
+/// The head's UTxO set, by `<tx hash>#<output index>`
+pub type HydraUtxoSet = BTreeMap<String, HydraTxOut>;
+
+/// An event of the head, as parsed from hydra-node's server outputs
+///
+/// Only the fields hibiki uses are kept. Both the older (full tx objects) and newer
+/// (tx ids only) shapes of `TxValid` and `SnapshotConfirmed` are understood.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HydraEvent {
+    Greetings {
+        head_status: String,
+        hydra_node_version: Option<String>,
+    },
+    HeadIsOpen {
+        head_id: String,
+        utxo: HydraUtxoSet,
+    },
+    HeadIsClosed {
+        head_id: String,
+        snapshot_number: u64,
+        contestation_deadline: String,
+    },
+    TxValid {
+        tx_id: String,
+    },
+    TxInvalid {
+        tx_id: String,
+        reason: String,
+    },
+    SnapshotConfirmed {
+        number: u64,
+        /// Ids of the txs the snapshot confirmed
+        confirmed: Vec<String>,
+        /// The UTxO set after the snapshot, when hydra-node includes it
+        utxo: Option<HydraUtxoSet>,
+    },
+    GetUTxOResponse {
+        utxo: HydraUtxoSet,
+    },
+    CommandFailed {
+        client_input: Value,
+    },
+    /// Any other server output, such as `PeerConnected` or `ReadyToFanout`
+    Other {
+        tag: String,
+    },
+}
+
+/// A server output with its sequence number; `Greetings` has none
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerOutput {
+    pub seq: Option<u64>,
+    pub event: HydraEvent,
+}
+
+fn field_str(json: &Value, key: &str) -> String {
+    json[key].as_str().unwrap_or_default().to_string()
+}
+
+fn utxo_set(json: &Value) -> Result<HydraUtxoSet, WError> {
+    serde_json::from_value(json.clone()).map_err(WError::from_err("hydra_node - utxo set"))
+}
+
+/// Id of a tx given either as an id or as a tx object
+fn tx_id(json: &Value) -> String {
+    match json {
+        Value::String(tx_id) => tx_id.clone(),
+        _ => field_str(json, "txId"),
+    }
+}
+
+pub fn parse_server_output(text: &str) -> Result<ServerOutput, WError> {
+    let json: Value =
+        serde_json::from_str(text).map_err(WError::from_err("parse_server_output"))?;
+    let tag = field_str(&json, "tag");
+    let event = match tag.as_str() {
+        "Greetings" => HydraEvent::Greetings {
+            head_status: field_str(&json, "headStatus"),
+            hydra_node_version: json["hydraNodeVersion"].as_str().map(str::to_string),
+        },
+        "HeadIsOpen" => HydraEvent::HeadIsOpen {
+            head_id: field_str(&json, "headId"),
+            utxo: utxo_set(&json["utxo"])?,
+        },
+        "HeadIsClosed" => HydraEvent::HeadIsClosed {
+            head_id: field_str(&json, "headId"),
+            snapshot_number: json["snapshotNumber"].as_u64().unwrap_or_default(),
+            contestation_deadline: field_str(&json, "contestationDeadline"),
+        },
+        "TxValid" => HydraEvent::TxValid {
+            tx_id: tx_id(json.get("transactionId").unwrap_or(&json["transaction"])),
+        },
+        "TxInvalid" => HydraEvent::TxInvalid {
+            tx_id: tx_id(&json["transaction"]),
+            reason: match &json["validationError"]["reason"] {
+                Value::String(reason) => reason.clone(),
+                other => other.to_string(),
+            },
+        },
+        "SnapshotConfirmed" => {
+            let snapshot = &json["snapshot"];
+            let confirmed = snapshot
+                .get("confirmed")
+                .or_else(|| snapshot.get("confirmedTransactions"))
+                .and_then(Value::as_array)
+                .map(|txs| txs.iter().map(tx_id).collect())
+                .unwrap_or_default();
+            HydraEvent::SnapshotConfirmed {
+                number: snapshot["number"].as_u64().unwrap_or_default(),
+                confirmed,
+                utxo: match snapshot.get("utxo") {
+                    Some(utxo) => Some(utxo_set(utxo)?),
+                    None => None,
+                },
+            }
+        }
+        "GetUTxOResponse" => HydraEvent::GetUTxOResponse {
+            utxo: utxo_set(&json["utxo"])?,
+        },
+        "CommandFailed" => HydraEvent::CommandFailed {
+            client_input: json["clientInput"].clone(),
+        },
+        _ => HydraEvent::Other { tag },
+    };
+    Ok(ServerOutput {
+        seq: json["seq"].as_u64(),
+        event,
+    })
+}
+
+/// The head's UTxO set as whisky UTxOs, e.g. to build txs spending them
+pub fn to_utxos(utxo: &HydraUtxoSet) -> Result<Vec<UTxO>, WError> {
+    utxo.iter()
+        .map(|(tx_in, tx_out)| to_utxo(tx_in, tx_out))
+        .collect()
+}
+
+pub fn to_utxo(tx_in: &str, tx_out: &HydraTxOut) -> Result<UTxO, WError> {
+    let (tx_hash, output_index) = tx_in
+        .split_once('#')
+        .and_then(|(tx_hash, index)| Some((tx_hash, index.parse().ok()?)))
+        .ok_or_else(|| WError::new("to_utxo", &format!("Invalid tx input {}", tx_in)))?;
+
+    let mut amount = Vec::new();
+    for (policy_id, quantity) in &tx_out.value {
+        match quantity {
+            Value::Object(assets) => {
+                for (asset_name, quantity) in assets {
+                    amount.push(Asset::new_from_str(
+                        &format!("{}{}", policy_id, asset_name),
+                        &quantity.to_string(),
+                    ));
+                }
+            }
+            quantity => amount.push(Asset::new_from_str(policy_id, &quantity.to_string())),
+        }
+    }
+
+    let plutus_data = match (&tx_out.inline_datum_raw, &tx_out.inline_datum) {
+        (Some(raw), _) => Some(raw.clone()),
+        (None, Some(datum)) => Some(
+            csl::encode_json_value_to_plutus_datum(
+                datum.clone(),
+                csl::PlutusDatumSchema::DetailedSchema,
+            )
+            .map_err(WError::from_err("to_utxo - inline datum"))?
+            .to_hex(),
+        ),
+        (None, None) => None,
+    };
+
+    // Only Plutus V3 reference scripts, the kind hibiki's txs reference
+    let script_cbor = tx_out
+        .reference_script
+        .as_ref()
+        .filter(|script| script["script"]["type"] == "PlutusScriptV3")
+        .and_then(|script| script["script"]["cborHex"].as_str());
+    let (script_ref, script_hash) = match script_cbor {
+        Some(cbor) => (Some(get_script_ref_hex(cbor)?), Some(script_hash(cbor)?)),
+        None => (None, None),
+    };
+
+    Ok(UTxO {
+        input: UtxoInput {
+            output_index,
+            tx_hash: tx_hash.to_string(),
+        },
+        output: UtxoOutput {
+            address: tx_out.address.clone(),
+            amount,
+            data_hash: tx_out.datumhash.clone(),
+            plutus_data,
+            script_ref,
+            script_hash,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TX_ID: &str = "e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd8";
+
+    #[test]
+    fn test_parse_tx_events() {
+        let valid = parse_server_output(&format!(
+            r#"{{"tag":"TxValid","headId":"01","seq":7,"transactionId":"{}"}}"#,
+            TX_ID
+        ))
+        .unwrap();
+        assert_eq!(valid.seq, Some(7));
+        assert_eq!(
+            valid.event,
+            HydraEvent::TxValid {
+                tx_id: TX_ID.to_string()
+            }
+        );
+
+        // Older hydra-node versions send the whole tx
+        let valid = parse_server_output(&format!(
+            r#"{{"tag":"TxValid","seq":8,"transaction":{{"txId":"{}","cborHex":"84"}}}}"#,
+            TX_ID
+        ))
+        .unwrap();
+        assert_eq!(
+            valid.event,
+            HydraEvent::TxValid {
+                tx_id: TX_ID.to_string()
+            }
+        );
+
+        let invalid = parse_server_output(&format!(
+            r#"{{"tag":"TxInvalid","seq":9,"utxo":{{}},"transaction":{{"txId":"{}"}},"validationError":{{"reason":"BadInputsUTxO"}}}}"#,
+            TX_ID
+        ))
+        .unwrap();
+        assert_eq!(
+            invalid.event,
+            HydraEvent::TxInvalid {
+                tx_id: TX_ID.to_string(),
+                reason: "BadInputsUTxO".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_snapshot_confirmed() {
+        let output = parse_server_output(&format!(
+            r#"{{"tag":"SnapshotConfirmed","seq":10,"snapshot":{{"number":3,"confirmed":[{{"txId":"{}"}}],"utxo":{{}}}}}}"#,
+            TX_ID
+        ))
+        .unwrap();
+        assert_eq!(
+            output.event,
+            HydraEvent::SnapshotConfirmed {
+                number: 3,
+                confirmed: vec![TX_ID.to_string()],
+                utxo: Some(HydraUtxoSet::new()),
+            }
+        );
+
+        let output = parse_server_output(&format!(
+            r#"{{"tag":"SnapshotConfirmed","snapshot":{{"number":4,"confirmedTransactions":["{}"]}}}}"#,
+            TX_ID
+        ))
+        .unwrap();
+        assert_eq!(output.seq, None);
+        assert!(matches!(
+            output.event,
+            HydraEvent::SnapshotConfirmed {
+                number: 4,
+                utxo: None,
+                ..
+            }
+        ));
+        assert_eq!(
+            parse_server_output(r#"{"tag":"PeerConnected","peer":"bob","seq":1}"#)
+                .unwrap()
+                .event,
+            HydraEvent::Other {
+                tag: "PeerConnected".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_utxos() {
+        let utxo: HydraUtxoSet = serde_json::from_value(serde_json::json!({
+            format!("{}#1", TX_ID): {
+                "address": "addr_test1wzg9",
+                "value": {
+                    "lovelace": 0,
+                    "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913": { "5553444d": 25000000 }
+                },
+                "datum": null,
+                "inlineDatum": { "constructor": 0, "fields": [] },
+                "referenceScript": null
+            }
+        }))
+        .unwrap();
+        let utxos = to_utxos(&utxo).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].input.output_index, 1);
+        assert_eq!(utxos[0].input.tx_hash, TX_ID);
+        assert_eq!(
+            utxos[0].output.amount[0].unit(),
+            "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d"
+        );
+        assert_eq!(utxos[0].output.amount[0].quantity(), "25000000");
+        assert_eq!(utxos[0].output.plutus_data.as_deref(), Some("d87980"));
+        assert!(to_utxo("not-an-input", &utxo.values().next().unwrap().clone()).is_err());
+    }
+}
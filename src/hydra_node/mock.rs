@@ -0,0 +1,275 @@
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{Request, Response},
+        Message,
+    },
+};
+use whisky::calculate_tx_hash;
+
+use crate::hydra_node::messages::{HydraUtxoSet, TX_ENVELOPE_TYPE};
+
+const HEAD_ID: &str = "0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f";
+
+enum Outgoing {
+    Text(String),
+    Disconnect,
+}
+
+struct MockState {
+    seq: u64,
+    /// Every server output sent so far, replayed to clients asking for history
+    history: Vec<String>,
+    clients: Vec<mpsc::UnboundedSender<Outgoing>>,
+    head_status: String,
+    utxo: HydraUtxoSet,
+    snapshot_number: u64,
+    received_txs: Vec<String>,
+    /// Reason every `NewTx` is rejected with, when set
+    rejection: Option<String>,
+}
+
+impl MockState {
+    /// Send a server output to every client and keep it for replay
+    fn emit(&mut self, mut output: Value) {
+        self.seq += 1;
+        output["seq"] = json!(self.seq);
+        output["headId"] = json!(HEAD_ID);
+        let text = output.to_string();
+        self.history.push(text.clone());
+        self.clients
+            .retain(|client| client.send(Outgoing::Text(text.clone())).is_ok());
+    }
+
+    fn greetings(&self) -> String {
+        json!({
+            "tag": "Greetings",
+            "me": { "vkey": "00" },
+            "headStatus": self.head_status,
+            "hydraNodeVersion": "mock",
+        })
+        .to_string()
+    }
+
+    fn utxo_json(&self) -> Value {
+        serde_json::to_value(&self.utxo).unwrap()
+    }
+
+    /// Answer a client input; `GetUTxO` is answered to its sender only
+    fn handle_input(&mut self, text: &str, client: &mpsc::UnboundedSender<Outgoing>) {
+        let input: Value = serde_json::from_str(text).unwrap_or_default();
+        match input["tag"].as_str() {
+            Some("NewTx") => {
+                let cbor_hex = input["transaction"]["cborHex"]
+                    .as_str()
+                    .or_else(|| input["transaction"].as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let Ok(tx_id) = calculate_tx_hash(&cbor_hex) else {
+                    self.emit(json!({ "tag": "CommandFailed", "clientInput": input }));
+                    return;
+                };
+                self.received_txs.push(cbor_hex.clone());
+                let transaction = json!({
+                    "type": TX_ENVELOPE_TYPE,
+                    "description": "",
+                    "cborHex": cbor_hex,
+                    "txId": tx_id,
+                });
+                match self.rejection.clone() {
+                    Some(reason) => self.emit(json!({
+                        "tag": "TxInvalid",
+                        "utxo": self.utxo_json(),
+                        "transaction": transaction,
+                        "validationError": { "reason": reason },
+                    })),
+                    None => {
+                        self.emit(json!({ "tag": "TxValid", "transactionId": tx_id }));
+                        self.snapshot_number += 1;
+                        self.emit(json!({
+                            "tag": "SnapshotConfirmed",
+                            "snapshot": {
+                                "headId": HEAD_ID,
+                                "number": self.snapshot_number,
+                                "confirmed": [transaction],
+                                "utxo": self.utxo_json(),
+                            },
+                            "signatures": { "multiSignature": [] },
+                        }));
+                    }
+                }
+            }
+            Some("GetUTxO") => {
+                let response = json!({
+                    "tag": "GetUTxOResponse",
+                    "headId": HEAD_ID,
+                    "utxo": self.utxo_json(),
+                });
+                let _ = client.send(Outgoing::Text(response.to_string()));
+            }
+            _ => self.emit(json!({ "tag": "CommandFailed", "clientInput": input })),
+        }
+    }
+}
+
+/// An in-process hydra-node speaking its WebSocket API, for tests
+///
+/// Every `NewTx` is valid and confirmed in its own snapshot unless [`reject_txs`] is
+/// set; the UTxO set only changes through [`set_utxo`], as no ledger is applied.
+///
+/// [`reject_txs`]: MockHydraNode::reject_txs
+/// [`set_utxo`]: MockHydraNode::set_utxo
+pub struct MockHydraNode {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    task: JoinHandle<()>,
+}
+
+impl MockHydraNode {
+    /// Listen on a random local port, with an idle head
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(MockState {
+            seq: 0,
+            history: Vec::new(),
+            clients: Vec::new(),
+            head_status: "Idle".to_string(),
+            utxo: HydraUtxoSet::new(),
+            snapshot_number: 0,
+            received_txs: Vec::new(),
+            rejection: None,
+        }));
+        let accept_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_client(stream, accept_state.clone()));
+            }
+        });
+        MockHydraNode { addr, state, task }
+    }
+
+    /// URL to pass to [`crate::hydra_node::HydraNodeConfig::new`]
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    pub fn open_head(&self, utxo: HydraUtxoSet) {
+        let mut state = self.state.lock().unwrap();
+        state.head_status = "Open".to_string();
+        state.utxo = utxo.clone();
+        state.emit(json!({ "tag": "HeadIsOpen", "utxo": utxo }));
+    }
+
+    pub fn close_head(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.head_status = "Closed".to_string();
+        let snapshot_number = state.snapshot_number;
+        state.emit(json!({
+            "tag": "HeadIsClosed",
+            "snapshotNumber": snapshot_number,
+            "contestationDeadline": "2030-01-01T00:00:00Z",
+        }));
+    }
+
+    /// The UTxO set reported from now on
+    pub fn set_utxo(&self, utxo: HydraUtxoSet) {
+        self.state.lock().unwrap().utxo = utxo;
+    }
+
+    /// Answer every `NewTx` from now on with `TxInvalid` and this reason
+    pub fn reject_txs(&self, reason: &str) {
+        self.state.lock().unwrap().rejection = Some(reason.to_string());
+    }
+
+    pub fn accept_txs(&self) {
+        self.state.lock().unwrap().rejection = None;
+    }
+
+    /// CBOR of every `NewTx` received, in order
+    pub fn received_txs(&self) -> Vec<String> {
+        self.state.lock().unwrap().received_txs.clone()
+    }
+
+    /// Close every open connection, as a restarting hydra-node would
+    pub fn disconnect_clients(&self) {
+        for client in self.state.lock().unwrap().clients.drain(..) {
+            let _ = client.send(Outgoing::Disconnect);
+        }
+    }
+}
+
+impl Drop for MockHydraNode {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.disconnect_clients();
+    }
+}
+
+async fn serve_client(stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let mut history = false;
+    // The handshake's error response is large, but only built by tungstenite
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| {
+        history = request
+            .uri()
+            .query()
+            .is_some_and(|query| query.contains("history=yes"));
+        Ok(response)
+    };
+    let Ok(socket) = accept_hdr_async(stream, callback).await else {
+        return;
+    };
+
+    let (client, mut outgoing) = mpsc::unbounded_channel();
+    // Register and take the history together, so no output is missed or sent twice
+    let initial = {
+        let mut state = state.lock().unwrap();
+        let mut initial = vec![state.greetings()];
+        if history {
+            initial.extend(state.history.iter().cloned());
+        }
+        state.clients.push(client.clone());
+        initial
+    };
+
+    let (mut write, mut read) = socket.split();
+    for text in initial {
+        if write.send(Message::Text(text)).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            message = outgoing.recv() => match message {
+                Some(Outgoing::Text(text)) => {
+                    if write.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Outgoing::Disconnect) | None => {
+                    let _ = write.close().await;
+                    return;
+                }
+            },
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    state.lock().unwrap().handle_input(&text, &client);
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,641 @@
+use futures::{SinkExt, StreamExt};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{
+    net::TcpStream,
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+    time::{sleep, sleep_until, timeout_at, Instant},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use whisky::{calculate_tx_hash, WError};
+
+pub mod messages;
+#[cfg(any(test, feature = "mock-hydra-node"))]
+pub mod mock;
+pub mod utxo_index;
+
+pub use messages::*;
+
+/// Events buffered per subscriber before it lags
+const EVENT_CAPACITY: usize = 1024;
+
+/// How long `submit_and_confirm` waits for the UTxO set after missing events
+const UTXO_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where and how persistently to connect to hydra-node
+#[derive(Debug, Clone, PartialEq)]
+pub struct HydraNodeConfig {
+    /// WebSocket API of hydra-node, e.g. `ws://127.0.0.1:4001`
+    pub url: String,
+    /// First delay before reconnecting, doubled on every failed attempt
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+}
+
+impl HydraNodeConfig {
+    pub fn new(url: &str) -> Self {
+        HydraNodeConfig {
+            url: url.to_string(),
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// The API URL asking hydra-node to replay every past server output
+    fn history_url(&self) -> String {
+        match self.url.contains('?') {
+            true => format!("{}&history=yes", self.url),
+            false => format!("{}/?history=yes", self.url.trim_end_matches('/')),
+        }
+    }
+}
+
+/// What the client has seen of the head
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeadState {
+    pub connected: bool,
+    /// `Idle`, `Initializing`, `Open`, `Closed`, ... as hydra-node names them
+    pub status: Option<String>,
+    pub head_id: Option<String>,
+    pub snapshot_number: u64,
+    /// Sequence number of the last server output, to skip it when history is replayed
+    pub last_seq: Option<u64>,
+}
+
+impl HeadState {
+    fn apply(&mut self, event: &HydraEvent) {
+        match event {
+            HydraEvent::Greetings { head_status, .. } => {
+                self.status = Some(head_status.clone());
+            }
+            HydraEvent::HeadIsOpen { head_id, .. } => {
+                self.status = Some("Open".to_string());
+                self.head_id = Some(head_id.clone());
+            }
+            HydraEvent::HeadIsClosed {
+                head_id,
+                snapshot_number,
+                ..
+            } => {
+                self.status = Some("Closed".to_string());
+                self.head_id = Some(head_id.clone());
+                self.snapshot_number = *snapshot_number;
+            }
+            HydraEvent::SnapshotConfirmed { number, .. } => {
+                self.snapshot_number = *number;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether the head accepted a submitted tx
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxValidity {
+    Valid,
+    Invalid { reason: String },
+}
+
+/// How long to wait for a submitted tx to be confirmed, and how to resubmit it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmitPolicy {
+    /// Deadline for the tx to be confirmed in a snapshot or rejected
+    pub timeout: Duration,
+    /// Resubmit the tx when the head has not answered for this long, e.g. because the
+    /// connection dropped with the `NewTx` in flight
+    pub resubmit_interval: Duration,
+    /// `NewTx` submissions at most, the first one included
+    pub max_attempts: u32,
+}
+
+/// Final outcome of a submitted tx
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxConfirmation {
+    Confirmed { snapshot_number: u64, attempts: u32 },
+    Invalid { reason: String, attempts: u32 },
+}
+
+/// A tx sent with `NewTx`, with the events that follow it
+pub struct TxSubmission {
+    pub tx_id: String,
+    events: broadcast::Receiver<HydraEvent>,
+}
+
+impl TxSubmission {
+    /// Wait for the head's `TxValid` or `TxInvalid` of this tx
+    pub async fn validity(&mut self, timeout: Duration) -> Result<TxValidity, WError> {
+        let tx_id = self.tx_id.clone();
+        wait_for(&mut self.events, timeout, |event| match event {
+            HydraEvent::TxValid { tx_id: id } if *id == tx_id => Some(Ok(TxValidity::Valid)),
+            HydraEvent::TxInvalid { tx_id: id, reason } if *id == tx_id => {
+                Some(Ok(TxValidity::Invalid {
+                    reason: reason.clone(),
+                }))
+            }
+            HydraEvent::HeadIsClosed { .. } => Some(Err("Head closed".to_string())),
+            _ => None,
+        })
+        .await
+        .map_err(|e| WError::new("TxSubmission - validity", &format!("{}: {}", tx_id, e)))
+    }
+}
+
+/// Wait until `select` picks an event, skipping those a lagging receiver missed
+async fn wait_for<T>(
+    events: &mut broadcast::Receiver<HydraEvent>,
+    timeout: Duration,
+    mut select: impl FnMut(&HydraEvent) -> Option<Result<T, String>>,
+) -> Result<T, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match timeout_at(deadline, events.recv()).await {
+            Ok(Ok(event)) => {
+                if let Some(result) = select(&event) {
+                    return result;
+                }
+            }
+            Ok(Err(broadcast::error::RecvError::Lagged(_))) => {}
+            Ok(Err(broadcast::error::RecvError::Closed)) => {
+                return Err("Hydra node client stopped".to_string())
+            }
+            Err(_) => return Err(format!("No answer within {:?}", timeout)),
+        }
+    }
+}
+
+/// Client of the hydra-node WebSocket API
+///
+/// Connects in the background, reconnecting with backoff whenever the connection
+/// drops. Every connection asks for the full history; server outputs already seen, by
+/// `seq`, are skipped so subscribers get each event once. Commands sent while
+/// disconnected are queued until the next connection.
+pub struct HydraNodeClient {
+    commands: mpsc::UnboundedSender<String>,
+    events: broadcast::Sender<HydraEvent>,
+    head: Arc<RwLock<HeadState>>,
+    task: JoinHandle<()>,
+}
+
+impl HydraNodeClient {
+    /// Start connecting to hydra-node
+    ///
+    /// The returned receiver gets every event from the first connection on, history
+    /// included, e.g. to build state from the whole head.
+    pub fn connect(config: HydraNodeConfig) -> (Self, broadcast::Receiver<HydraEvent>) {
+        let (commands, commands_rx) = mpsc::unbounded_channel();
+        let (events, events_rx) = broadcast::channel(EVENT_CAPACITY);
+        let head = Arc::new(RwLock::new(HeadState::default()));
+        let task = tokio::spawn(run(config, commands_rx, events.clone(), head.clone()));
+        let client = HydraNodeClient {
+            commands,
+            events,
+            head,
+            task,
+        };
+        (client, events_rx)
+    }
+
+    /// Events from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<HydraEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn head(&self) -> HeadState {
+        self.head.read().unwrap().clone()
+    }
+
+    fn send(&self, input: &ClientInput) -> Result<(), WError> {
+        let text =
+            serde_json::to_string(input).map_err(WError::from_err("HydraNodeClient - send"))?;
+        self.commands
+            .send(text)
+            .map_err(|_| WError::new("HydraNodeClient - send", "Client stopped"))
+    }
+
+    /// Submit a signed tx to the head with `NewTx`
+    pub fn submit_tx(&self, tx_hex: &str) -> Result<TxSubmission, WError> {
+        let tx_id = calculate_tx_hash(tx_hex)?;
+        // Subscribe first so the answer cannot be missed
+        let events = self.subscribe();
+        self.send(&ClientInput::NewTx {
+            transaction: HydraTransaction::new(tx_hex),
+        })?;
+        Ok(TxSubmission { tx_id, events })
+    }
+
+    /// Submit a signed tx and wait until a snapshot confirms it or the head rejects it
+    ///
+    /// A `TxInvalid` after the tx was seen valid, as a resubmission of an applied tx
+    /// gets, is ignored. After falling behind the events, whether the tx was applied is
+    /// unknown, so a `TxInvalid` or the deadline is first checked against the head's
+    /// UTxO set. Fails when the deadline passes or the head closes.
+    pub async fn submit_and_confirm(
+        &self,
+        tx_hex: &str,
+        policy: &SubmitPolicy,
+    ) -> Result<TxConfirmation, WError> {
+        let mut submission = self.submit_tx(tx_hex)?;
+        let tx_id = submission.tx_id.clone();
+        self.await_confirmation(tx_hex, &tx_id, &mut submission.events, policy)
+            .await
+    }
+
+    async fn await_confirmation(
+        &self,
+        tx_hex: &str,
+        tx_id: &str,
+        events: &mut broadcast::Receiver<HydraEvent>,
+        policy: &SubmitPolicy,
+    ) -> Result<TxConfirmation, WError> {
+        let error = |message: String| WError::new("HydraNodeClient - submit_and_confirm", &message);
+        let deadline = Instant::now() + policy.timeout;
+        let mut next_attempt = Instant::now() + policy.resubmit_interval;
+        let mut attempts = 1;
+        let mut valid = false;
+        // Events were dropped, among them perhaps this tx's TxValid or snapshot
+        let mut lagged = false;
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(HydraEvent::TxValid { tx_id: id }) if id == tx_id => valid = true,
+                    Ok(HydraEvent::TxInvalid { tx_id: id, reason }) if id == tx_id && !valid => {
+                        if lagged {
+                            if let Some(confirmed) = self.applied_confirmation(tx_id, attempts).await? {
+                                return Ok(confirmed);
+                            }
+                        }
+                        return Ok(TxConfirmation::Invalid { reason, attempts });
+                    }
+                    Ok(HydraEvent::SnapshotConfirmed { number, confirmed, .. })
+                        if confirmed.iter().any(|id| id == tx_id) =>
+                    {
+                        return Ok(TxConfirmation::Confirmed {
+                            snapshot_number: number,
+                            attempts,
+                        });
+                    }
+                    // The snapshot confirming it may have been missed, a later one shows it
+                    Ok(HydraEvent::SnapshotConfirmed { number, utxo: Some(utxo), .. })
+                        if lagged && includes_tx(&utxo, tx_id) =>
+                    {
+                        return Ok(TxConfirmation::Confirmed {
+                            snapshot_number: number,
+                            attempts,
+                        });
+                    }
+                    Ok(HydraEvent::HeadIsClosed { .. }) => {
+                        return Err(error(format!("Head closed before {} was confirmed", tx_id)));
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("Missed {} hydra-node events waiting for {}", skipped, tx_id);
+                        lagged = true;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        return Err(error("Hydra node client stopped".to_string()));
+                    }
+                },
+                _ = sleep_until(next_attempt), if !valid && attempts < policy.max_attempts => {
+                    self.send(&ClientInput::NewTx {
+                        transaction: HydraTransaction::new(tx_hex),
+                    })?;
+                    attempts += 1;
+                    next_attempt += policy.resubmit_interval;
+                }
+                _ = sleep_until(deadline) => {
+                    if lagged {
+                        if let Some(confirmed) = self.applied_confirmation(tx_id, attempts).await? {
+                            return Ok(confirmed);
+                        }
+                    }
+                    return Err(error(format!(
+                        "{} not confirmed within {:?} after {} attempts",
+                        tx_id, policy.timeout, attempts
+                    )));
+                }
+            }
+        }
+    }
+
+    /// `Confirmed` at the head's latest snapshot when an output of `tx_id` is in its
+    /// UTxO set
+    ///
+    /// A tx whose outputs were all spent since is not found.
+    async fn applied_confirmation(
+        &self,
+        tx_id: &str,
+        attempts: u32,
+    ) -> Result<Option<TxConfirmation>, WError> {
+        let utxo = self.get_utxo(UTXO_CHECK_TIMEOUT).await?;
+        Ok(
+            includes_tx(&utxo, tx_id).then(|| TxConfirmation::Confirmed {
+                snapshot_number: self.head().snapshot_number,
+                attempts,
+            }),
+        )
+    }
+
+    /// The head's current UTxO set, with `GetUTxO`
+    pub async fn get_utxo(&self, timeout: Duration) -> Result<HydraUtxoSet, WError> {
+        let mut events = self.subscribe();
+        self.send(&ClientInput::GetUTxO)?;
+        wait_for(&mut events, timeout, |event| match event {
+            HydraEvent::GetUTxOResponse { utxo } => Some(Ok(utxo.clone())),
+            HydraEvent::CommandFailed { client_input } if client_input["tag"] == "GetUTxO" => {
+                Some(Err("GetUTxO failed".to_string()))
+            }
+            _ => None,
+        })
+        .await
+        .map_err(|e| WError::new("HydraNodeClient - get_utxo", &e))
+    }
+}
+
+/// Whether an output of `tx_id` is in `utxo`
+fn includes_tx(utxo: &HydraUtxoSet, tx_id: &str) -> bool {
+    utxo.keys()
+        .any(|key| key.split_once('#').is_some_and(|(hash, _)| hash == tx_id))
+}
+
+impl Drop for HydraNodeClient {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn run(
+    config: HydraNodeConfig,
+    mut commands: mpsc::UnboundedReceiver<String>,
+    events: broadcast::Sender<HydraEvent>,
+    head: Arc<RwLock<HeadState>>,
+) {
+    let mut delay = config.reconnect_delay;
+    loop {
+        match connect_async(config.history_url()).await {
+            Ok((socket, _)) => {
+                delay = config.reconnect_delay;
+                head.write().unwrap().connected = true;
+                let outcome = serve(socket, &mut commands, &events, &head).await;
+                head.write().unwrap().connected = false;
+                match outcome {
+                    Ok(()) => return,
+                    Err(e) => eprintln!("hydra-node connection lost: {}", e),
+                }
+            }
+            Err(e) => eprintln!("Failed to connect to hydra-node {}: {}", config.url, e),
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(config.max_reconnect_delay);
+    }
+}
+
+/// Relay commands and server outputs until the connection drops (`Err`) or the client
+/// is dropped (`Ok`)
+async fn serve(
+    socket: Socket,
+    commands: &mut mpsc::UnboundedReceiver<String>,
+    events: &broadcast::Sender<HydraEvent>,
+    head: &RwLock<HeadState>,
+) -> Result<(), String> {
+    let (mut write, mut read) = socket.split();
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(text) => write.send(Message::Text(text)).await.map_err(|e| e.to_string())?,
+                None => return Ok(()),
+            },
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => handle_output(&text, events, head),
+                Some(Ok(Message::Close(_))) | None => return Err("closed by hydra-node".to_string()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.to_string()),
+            },
+        }
+    }
+}
+
+fn handle_output(text: &str, events: &broadcast::Sender<HydraEvent>, head: &RwLock<HeadState>) {
+    let output = match parse_server_output(text) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Unreadable hydra-node output: {}", e);
+            return;
+        }
+    };
+    {
+        let mut head = head.write().unwrap();
+        if let Some(seq) = output.seq {
+            if head.last_seq.is_some_and(|last_seq| seq <= last_seq) {
+                return;
+            }
+            head.last_seq = Some(seq);
+        }
+        head.apply(&output.event);
+    }
+    // No subscribers is fine
+    let _ = events.send(output.event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hydra_node::mock::MockHydraNode;
+
+    const TX_HEX: &str = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca10081825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402f5d90103a0";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn config(url: &str) -> HydraNodeConfig {
+        HydraNodeConfig {
+            reconnect_delay: Duration::from_millis(20),
+            ..HydraNodeConfig::new(url)
+        }
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<HydraEvent>) -> HydraEvent {
+        tokio::time::timeout(TIMEOUT, events.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_submit_tx() {
+        let node = MockHydraNode::start().await;
+        node.open_head(HydraUtxoSet::new());
+        let (client, _) = HydraNodeClient::connect(config(&node.url()));
+
+        let mut submission = client.submit_tx(TX_HEX).unwrap();
+        assert_eq!(submission.tx_id, calculate_tx_hash(TX_HEX).unwrap());
+        assert_eq!(
+            submission.validity(TIMEOUT).await.unwrap(),
+            TxValidity::Valid
+        );
+        assert_eq!(node.received_txs(), vec![TX_HEX.to_string()]);
+        assert_eq!(client.get_utxo(TIMEOUT).await.unwrap(), HydraUtxoSet::new());
+
+        node.reject_txs("BadInputsUTxO");
+        let mut submission = client.submit_tx(TX_HEX).unwrap();
+        assert_eq!(
+            submission.validity(TIMEOUT).await.unwrap(),
+            TxValidity::Invalid {
+                reason: "BadInputsUTxO".to_string()
+            }
+        );
+        assert_eq!(client.head().status.as_deref(), Some("Open"));
+        assert_eq!(client.head().snapshot_number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_confirm() {
+        let node = MockHydraNode::start().await;
+        node.open_head(HydraUtxoSet::new());
+        let (client, _) = HydraNodeClient::connect(config(&node.url()));
+        let policy = SubmitPolicy {
+            timeout: TIMEOUT,
+            resubmit_interval: TIMEOUT,
+            max_attempts: 3,
+        };
+
+        assert_eq!(
+            client.submit_and_confirm(TX_HEX, &policy).await.unwrap(),
+            TxConfirmation::Confirmed {
+                snapshot_number: 1,
+                attempts: 1
+            }
+        );
+        node.reject_txs("BadInputsUTxO");
+        assert_eq!(
+            client.submit_and_confirm(TX_HEX, &policy).await.unwrap(),
+            TxConfirmation::Invalid {
+                reason: "BadInputsUTxO".to_string(),
+                attempts: 1
+            }
+        );
+    }
+
+    /// A subscriber that missed one event before `event`
+    fn lagged(
+        event: HydraEvent,
+    ) -> (
+        broadcast::Sender<HydraEvent>,
+        broadcast::Receiver<HydraEvent>,
+    ) {
+        let (sender, receiver) = broadcast::channel(1);
+        sender
+            .send(HydraEvent::Other {
+                tag: "PeerConnected".to_string(),
+            })
+            .unwrap();
+        sender.send(event).unwrap();
+        (sender, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_lagged_rejection_checks_the_utxo_set() {
+        let tx_id = calculate_tx_hash(TX_HEX).unwrap();
+        let applied: HydraUtxoSet = serde_json::from_value(serde_json::json!({
+            format!("{}#0", tx_id): { "address": "addr_test1wzg9", "value": { "lovelace": 1_000_000 } },
+        }))
+        .unwrap();
+        let node = MockHydraNode::start().await;
+        node.open_head(applied);
+        let (client, _) = HydraNodeClient::connect(config(&node.url()));
+        let policy = SubmitPolicy {
+            timeout: TIMEOUT,
+            resubmit_interval: TIMEOUT,
+            max_attempts: 1,
+        };
+        let rejected = HydraEvent::TxInvalid {
+            tx_id: tx_id.clone(),
+            reason: "BadInputsUTxO".to_string(),
+        };
+
+        // The missed events may have confirmed it, and its output is in the head
+        let (_sender, mut events) = lagged(rejected.clone());
+        let outcome = client
+            .await_confirmation(TX_HEX, &tx_id, &mut events, &policy)
+            .await
+            .unwrap();
+        assert!(
+            matches!(outcome, TxConfirmation::Confirmed { attempts: 1, .. }),
+            "{:?}",
+            outcome
+        );
+
+        node.set_utxo(HydraUtxoSet::new());
+        let (_sender, mut events) = lagged(rejected);
+        assert_eq!(
+            client
+                .await_confirmation(TX_HEX, &tx_id, &mut events, &policy)
+                .await
+                .unwrap(),
+            TxConfirmation::Invalid {
+                reason: "BadInputsUTxO".to_string(),
+                attempts: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_confirm_resubmits_until_deadline() {
+        // Nothing listens, so every attempt goes unanswered
+        let (client, _) = HydraNodeClient::connect(config("ws://127.0.0.1:1"));
+        let policy = SubmitPolicy {
+            timeout: Duration::from_millis(200),
+            resubmit_interval: Duration::from_millis(50),
+            max_attempts: 2,
+        };
+        let error = client
+            .submit_and_confirm(TX_HEX, &policy)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("after 2 attempts"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_history_once() {
+        let node = MockHydraNode::start().await;
+        node.open_head(HydraUtxoSet::new());
+        let (client, mut events) = HydraNodeClient::connect(config(&node.url()));
+
+        // History of the first connection
+        assert!(matches!(
+            next_event(&mut events).await,
+            HydraEvent::Greetings { .. }
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            HydraEvent::HeadIsOpen { .. }
+        ));
+
+        node.disconnect_clients();
+        node.close_head();
+        // The replayed HeadIsOpen is skipped, only the new greeting and event arrive
+        assert!(matches!(
+            next_event(&mut events).await,
+            HydraEvent::Greetings { .. }
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            HydraEvent::HeadIsClosed { .. }
+        ));
+        assert_eq!(client.head().status.as_deref(), Some("Closed"));
+        assert!(client.head().connected);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_node() {
+        let (client, _) = HydraNodeClient::connect(config("ws://127.0.0.1:1"));
+        let mut submission = client.submit_tx(TX_HEX).unwrap();
+        assert!(submission
+            .validity(Duration::from_millis(100))
+            .await
+            .is_err());
+        assert!(!client.head().connected);
+    }
+}
@@ -0,0 +1,864 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::{sync::broadcast, task::JoinHandle};
+use whisky::{csl, data::PlutusDataJson, UTxO, UtxoInput, WError};
+
+use crate::{
+    deployment::{Deployment, DeploymentScripts},
+    hydra_node::messages::{to_utxo, HydraEvent, HydraTxOut, HydraUtxoSet},
+};
+
+/// How long an empty UTxO handed to one request is withheld from the others
+const EMPTY_UTXO_RESERVATION: Duration = Duration::from_secs(60);
+
+/// What a UTxO of the head is to a deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UtxoRole {
+    /// At `hydra_account.spend`, with the account as inline datum
+    AccountBalance,
+    /// At `hydra_user_intent.spend`
+    UserIntent,
+    /// Holds the oracle NFT, read by every tx
+    OrderBook,
+    /// Holds one of the deployment's scripts as a reference script
+    ReferenceScript,
+    /// Lovelace of the app owner, output by the tx of the reference scripts
+    Collateral,
+    /// Any other lovelace-only UTxO of the app owner, spent to order intents
+    Empty,
+}
+
+impl UtxoRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UtxoRole::AccountBalance => "account balance",
+            UtxoRole::UserIntent => "user intent",
+            UtxoRole::OrderBook => "order book",
+            UtxoRole::ReferenceScript => "reference script",
+            UtxoRole::Collateral => "collateral",
+            UtxoRole::Empty => "empty",
+        }
+    }
+}
+
+/// What tells one deployment's UTxOs apart
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeploymentRoles {
+    pub deployment_id: String,
+    pub account_script_hash: String,
+    pub intent_script_hash: String,
+    pub oracle_policy: String,
+    pub owner_key_hash: String,
+    /// Every script of the deployment, as found in reference scripts
+    pub script_hashes: Vec<String>,
+}
+
+impl DeploymentRoles {
+    pub fn new(deployment: &Deployment) -> Self {
+        DeploymentRoles::from_scripts(
+            &deployment.id,
+            &deployment.scripts,
+            &deployment.dex_oracle_nft,
+            &deployment.app_owner_vkey,
+        )
+    }
+
+    pub fn from_scripts(
+        deployment_id: &str,
+        scripts: &DeploymentScripts,
+        dex_oracle_nft: &str,
+        app_owner_vkey: &str,
+    ) -> Self {
+        DeploymentRoles {
+            deployment_id: deployment_id.to_string(),
+            account_script_hash: scripts.hydra_account_spend.hash.clone(),
+            intent_script_hash: scripts.hydra_user_intent_spend.hash.clone(),
+            oracle_policy: dex_oracle_nft.to_string(),
+            owner_key_hash: app_owner_vkey.to_string(),
+            script_hashes: scripts
+                .hashes()
+                .into_iter()
+                .map(|(_, hash)| hash.to_string())
+                .collect(),
+        }
+    }
+
+    fn is_reference_script(&self, utxo: &UTxO) -> bool {
+        utxo.output.script_ref.is_some()
+            && utxo
+                .output
+                .script_hash
+                .as_ref()
+                .is_some_and(|hash| self.script_hashes.contains(hash))
+    }
+
+    /// `reference_txs` are the txs holding this deployment's reference scripts
+    fn role(&self, utxo: &UTxO, reference_txs: &HashSet<&str>) -> Option<UtxoRole> {
+        if self.is_reference_script(utxo) {
+            return Some(UtxoRole::ReferenceScript);
+        }
+        if utxo
+            .output
+            .amount
+            .iter()
+            .any(|asset| asset.unit().starts_with(&self.oracle_policy))
+        {
+            return Some(UtxoRole::OrderBook);
+        }
+        let payment_hash = payment_credential_hash(&utxo.output.address)?;
+        if payment_hash == self.account_script_hash {
+            Some(UtxoRole::AccountBalance)
+        } else if payment_hash == self.intent_script_hash {
+            Some(UtxoRole::UserIntent)
+        } else if payment_hash == self.owner_key_hash && is_lovelace_only(utxo) {
+            if reference_txs.contains(utxo.input.tx_hash.as_str()) {
+                Some(UtxoRole::Collateral)
+            } else {
+                Some(UtxoRole::Empty)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Key or script hash of an address's payment credential
+fn payment_credential_hash(address: &str) -> Option<String> {
+    let address = csl::Address::from_bech32(address).ok()?;
+    let credential = match csl::BaseAddress::from_address(&address) {
+        Some(base) => base.payment_cred(),
+        None => csl::EnterpriseAddress::from_address(&address)?.payment_cred(),
+    };
+    match (credential.to_keyhash(), credential.to_scripthash()) {
+        (Some(key_hash), _) => Some(key_hash.to_hex()),
+        (None, Some(script_hash)) => Some(script_hash.to_hex()),
+        (None, None) => None,
+    }
+}
+
+fn is_lovelace_only(utxo: &UTxO) -> bool {
+    utxo.output.plutus_data.is_none()
+        && utxo.output.script_ref.is_none()
+        && utxo
+            .output
+            .amount
+            .iter()
+            .all(|asset| asset.unit() == "lovelace")
+}
+
+fn outpoint(input: &UtxoInput) -> String {
+    format!("{}#{}", input.tx_hash, input.output_index)
+}
+
+/// Key of an account in the index: its datum as JSON, as hydra-node reports inline datums
+pub fn account_key(account: &impl PlutusDataJson) -> Result<String, WError> {
+    let json: Value =
+        serde_json::from_str(&account.to_json_string()).map_err(WError::from_err("account_key"))?;
+    Ok(json.to_string())
+}
+
+/// The inline datum of an output as JSON, decoding its CBOR when hydra-node gave no JSON
+fn inline_datum_json(tx_out: &HydraTxOut) -> Option<Value> {
+    if let Some(datum) = &tx_out.inline_datum {
+        return Some(datum.clone());
+    }
+    let plutus_data = csl::PlutusData::from_hex(tx_out.inline_datum_raw.as_ref()?).ok()?;
+    csl::decode_plutus_datum_to_json_value(&plutus_data, csl::PlutusDatumSchema::DetailedSchema)
+        .ok()
+}
+
+/// The head's UTxO set as of one snapshot, indexed by role, account and hydra unit
+#[derive(Debug, Default)]
+pub struct UtxoIndex {
+    pub snapshot_number: u64,
+    utxos: BTreeMap<String, UTxO>,
+    by_role: HashMap<(String, UtxoRole), BTreeSet<String>>,
+    by_account: HashMap<String, BTreeSet<String>>,
+    by_unit: HashMap<String, BTreeSet<String>>,
+}
+
+impl UtxoIndex {
+    pub fn build(
+        snapshot_number: u64,
+        utxo: &HydraUtxoSet,
+        roles: &[DeploymentRoles],
+    ) -> Result<Self, WError> {
+        let mut index = UtxoIndex {
+            snapshot_number,
+            ..UtxoIndex::default()
+        };
+        let mut tx_outs = HashMap::with_capacity(utxo.len());
+        for (tx_in, tx_out) in utxo {
+            let parsed = to_utxo(tx_in, tx_out)?;
+            let key = outpoint(&parsed.input);
+            for asset in &parsed.output.amount {
+                if asset.unit() != "lovelace" {
+                    index
+                        .by_unit
+                        .entry(asset.unit())
+                        .or_default()
+                        .insert(key.clone());
+                }
+            }
+            tx_outs.insert(key.clone(), tx_out);
+            index.utxos.insert(key, parsed);
+        }
+
+        for deployment in roles {
+            let reference_txs: HashSet<&str> = index
+                .utxos
+                .values()
+                .filter(|utxo| deployment.is_reference_script(utxo))
+                .map(|utxo| utxo.input.tx_hash.as_str())
+                .collect();
+            for (key, utxo) in &index.utxos {
+                let Some(role) = deployment.role(utxo, &reference_txs) else {
+                    continue;
+                };
+                if role == UtxoRole::AccountBalance {
+                    if let Some(datum) = inline_datum_json(tx_outs[key]) {
+                        index
+                            .by_account
+                            .entry(datum.to_string())
+                            .or_default()
+                            .insert(key.clone());
+                    }
+                }
+                index
+                    .by_role
+                    .entry((deployment.deployment_id.clone(), role))
+                    .or_default()
+                    .insert(key.clone());
+            }
+        }
+        Ok(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.utxos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.utxos.is_empty()
+    }
+
+    pub fn get(&self, input: &UtxoInput) -> Option<&UTxO> {
+        self.utxos.get(&outpoint(input))
+    }
+
+    fn lookup<'a>(&'a self, keys: Option<&'a BTreeSet<String>>) -> Vec<&'a UTxO> {
+        keys.into_iter()
+            .flatten()
+            .filter_map(|key| self.utxos.get(key))
+            .collect()
+    }
+
+    /// The deployment's UTxOs of `role`, ordered by input
+    pub fn role_utxos(&self, deployment_id: &str, role: UtxoRole) -> Vec<&UTxO> {
+        self.lookup(self.by_role.get(&(deployment_id.to_string(), role)))
+    }
+
+    /// UTxOs holding `unit`, a hydra unit
+    pub fn unit_utxos(&self, unit: &str) -> Vec<&UTxO> {
+        self.lookup(self.by_unit.get(unit))
+    }
+
+    /// Balance UTxOs of an account, keyed by [`account_key`], of any deployment
+    pub fn account_utxos(&self, account_key: &str) -> Vec<&UTxO> {
+        self.lookup(self.by_account.get(account_key))
+    }
+
+    /// The deployment's balance UTxOs of `account`
+    pub fn account_balance_utxos(
+        &self,
+        deployment_id: &str,
+        account: &impl PlutusDataJson,
+    ) -> Result<Vec<&UTxO>, WError> {
+        let balances = self
+            .by_role
+            .get(&(deployment_id.to_string(), UtxoRole::AccountBalance));
+        Ok(self
+            .account_utxos(&account_key(account)?)
+            .into_iter()
+            .filter(|utxo| balances.is_some_and(|keys| keys.contains(&outpoint(&utxo.input))))
+            .collect())
+    }
+
+    /// The first of the deployment's UTxOs of `role`
+    pub fn resolve(&self, deployment_id: &str, role: UtxoRole) -> Result<UTxO, WError> {
+        self.role_utxos(deployment_id, role)
+            .first()
+            .map(|utxo| (*utxo).clone())
+            .ok_or_else(|| {
+                WError::new(
+                    "UtxoIndex - resolve",
+                    &format!(
+                        "No {} UTxO of deployment {} in snapshot {}",
+                        role.name(),
+                        deployment_id,
+                        self.snapshot_number
+                    ),
+                )
+            })
+    }
+
+    /// Inputs of `utxos` no longer in the head's UTxO set
+    pub fn stale_inputs(&self, utxos: &[&UTxO]) -> Vec<String> {
+        utxos
+            .iter()
+            .map(|utxo| outpoint(&utxo.input))
+            .filter(|key| !self.utxos.contains_key(key))
+            .collect()
+    }
+
+    /// Fails when any of `utxos` has been spent, rather than building a tx the head rejects
+    pub fn check_unspent(&self, utxos: &[&UTxO]) -> Result<(), WError> {
+        let stale = self.stale_inputs(utxos);
+        if stale.is_empty() {
+            return Ok(());
+        }
+        Err(WError::new(
+            "UtxoIndex - check_unspent",
+            &format!(
+                "Stale inputs, spent by snapshot {}: {}",
+                self.snapshot_number,
+                stale.join(", ")
+            ),
+        ))
+    }
+}
+
+/// The index file: the last snapshot's UTxO set, re-indexed on load
+#[derive(Serialize, Deserialize)]
+struct PersistedSnapshot {
+    snapshot_number: u64,
+    utxo: HydraUtxoSet,
+}
+
+/// The live [`UtxoIndex`] of the head, replaced on every snapshot and persisted when a
+/// path is set
+pub struct HeadUtxoIndex {
+    roles: Vec<DeploymentRoles>,
+    path: Option<PathBuf>,
+    index: RwLock<Option<Arc<UtxoIndex>>>,
+    reserved: Mutex<HashMap<String, Instant>>,
+}
+
+impl HeadUtxoIndex {
+    /// Starts from the persisted snapshot at `path` when there is one
+    pub fn new(roles: Vec<DeploymentRoles>, path: Option<PathBuf>) -> Result<Self, WError> {
+        let index = match path.as_deref().filter(|path| path.exists()) {
+            Some(path) => {
+                let persisted = read_snapshot_file(path)?;
+                Some(Arc::new(UtxoIndex::build(
+                    persisted.snapshot_number,
+                    &persisted.utxo,
+                    &roles,
+                )?))
+            }
+            None => None,
+        };
+        Ok(HeadUtxoIndex {
+            roles,
+            path,
+            index: RwLock::new(index),
+            reserved: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The index of the latest snapshot, `None` until one is seen
+    pub fn current(&self) -> Option<Arc<UtxoIndex>> {
+        self.index.read().unwrap().clone()
+    }
+
+    /// Replace the index with the UTxO set of a snapshot, in memory only
+    pub fn apply(&self, snapshot_number: u64, utxo: &HydraUtxoSet) -> Result<(), WError> {
+        let index = UtxoIndex::build(snapshot_number, utxo, &self.roles)?;
+        self.reserved
+            .lock()
+            .unwrap()
+            .retain(|key, _| index.utxos.contains_key(key));
+        *self.index.write().unwrap() = Some(Arc::new(index));
+        Ok(())
+    }
+
+    /// The snapshot an event replaces the index with; a newly opened head starts over at
+    /// snapshot 0
+    fn snapshot_of<'e>(&self, event: &'e HydraEvent) -> Option<(u64, &'e HydraUtxoSet)> {
+        let current = self.current().map(|index| index.snapshot_number);
+        match event {
+            HydraEvent::HeadIsOpen { utxo, .. } => Some((0, utxo)),
+            HydraEvent::SnapshotConfirmed {
+                number,
+                utxo: Some(utxo),
+                ..
+            } if current.is_none_or(|current| *number >= current) => Some((*number, utxo)),
+            HydraEvent::GetUTxOResponse { utxo } => Some((current.unwrap_or_default(), utxo)),
+            _ => None,
+        }
+    }
+
+    /// Apply the UTxO set an event carries
+    pub fn apply_event(&self, event: &HydraEvent) -> Result<(), WError> {
+        match self.snapshot_of(event) {
+            Some((snapshot_number, utxo)) => self.apply(snapshot_number, utxo),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a snapshot to the index file on a blocking thread, when a path is set
+    async fn persist(&self, snapshot_number: u64, utxo: HydraUtxoSet) -> Result<(), WError> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+        let snapshot = PersistedSnapshot {
+            snapshot_number,
+            utxo,
+        };
+        tokio::task::spawn_blocking(move || write_snapshot_file(&path, &snapshot))
+            .await
+            .map_err(WError::from_err("HeadUtxoIndex - persist"))?
+    }
+
+    /// Keep the index up to date with the events of a [`crate::hydra_node::HydraNodeClient`]
+    ///
+    /// Every snapshot carries the whole UTxO set, so events lost to lag are harmless.
+    /// Snapshots are persisted one after the other, off the async runtime's workers.
+    pub fn follow(self: Arc<Self>, mut events: broadcast::Receiver<HydraEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let Some((snapshot_number, utxo)) = self.snapshot_of(&event) else {
+                    continue;
+                };
+                if let Err(e) = self.apply(snapshot_number, utxo) {
+                    eprintln!("Failed to index the head's UTxO set: {}", e);
+                    continue;
+                }
+                if let Err(e) = self.persist(snapshot_number, utxo.clone()).await {
+                    eprintln!("Failed to persist the head's UTxO index: {}", e);
+                }
+            }
+        })
+    }
+
+    /// An empty UTxO of the deployment no other request holds, withheld from the others
+    /// until a snapshot spends it or the reservation lapses
+    pub fn take_empty_utxo(&self, deployment_id: &str) -> Result<UTxO, WError> {
+        let index = self.current().ok_or_else(|| {
+            WError::new(
+                "HeadUtxoIndex - take_empty_utxo",
+                "No snapshot of the head's UTxO set yet",
+            )
+        })?;
+        let now = Instant::now();
+        let mut reserved = self.reserved.lock().unwrap();
+        reserved.retain(|_, at| now.duration_since(*at) < EMPTY_UTXO_RESERVATION);
+        let utxo = index
+            .role_utxos(deployment_id, UtxoRole::Empty)
+            .into_iter()
+            .find(|utxo| !reserved.contains_key(&outpoint(&utxo.input)))
+            .ok_or_else(|| {
+                WError::new(
+                    "HeadUtxoIndex - take_empty_utxo",
+                    &format!(
+                        "No unreserved empty UTxO of deployment {} in snapshot {}",
+                        deployment_id, index.snapshot_number
+                    ),
+                )
+            })?;
+        reserved.insert(outpoint(&utxo.input), now);
+        Ok(utxo.clone())
+    }
+}
+
+fn read_snapshot_file(path: &Path) -> Result<PersistedSnapshot, WError> {
+    let json = fs::read_to_string(path).map_err(WError::from_err("HeadUtxoIndex - read index"))?;
+    serde_json::from_str(&json).map_err(WError::from_err("HeadUtxoIndex - parse index"))
+}
+
+/// Write via a temporary file and rename, so a crash never leaves a truncated index
+fn write_snapshot_file(path: &Path, snapshot: &PersistedSnapshot) -> Result<(), WError> {
+    let json =
+        serde_json::to_string(snapshot).map_err(WError::from_err("HeadUtxoIndex - serialize"))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, json).map_err(WError::from_err("HeadUtxoIndex - write"))?;
+    fs::rename(&tmp_path, path).map_err(WError::from_err("HeadUtxoIndex - rename"))
+}
+
+/// Resolves the UTxOs a request leaves out from the head's index, and rejects the ones
+/// it gives that the head has spent
+pub struct InputResolver<'a> {
+    head: Option<&'a HeadUtxoIndex>,
+    index: Option<Arc<UtxoIndex>>,
+    deployment_id: &'a str,
+}
+
+impl<'a> InputResolver<'a> {
+    pub fn new(head: Option<&'a HeadUtxoIndex>, deployment_id: &'a str) -> Self {
+        InputResolver {
+            head,
+            index: head.and_then(HeadUtxoIndex::current),
+            deployment_id,
+        }
+    }
+
+    fn index(&self, field: &str) -> Result<&UtxoIndex, WError> {
+        self.index.as_deref().ok_or_else(|| {
+            WError::new(
+                "InputResolver",
+                &format!("{} is required until the head's UTxO set is indexed", field),
+            )
+        })
+    }
+
+    /// `given`, else the deployment's UTxO of `role`; `field` names it in errors
+    pub fn utxo(&self, given: Option<UTxO>, role: UtxoRole, field: &str) -> Result<UTxO, WError> {
+        if let Some(utxo) = given {
+            return Ok(utxo);
+        }
+        let index = self.index(field)?;
+        match (role, self.head) {
+            // Handed to one request at a time, as each spends it
+            (UtxoRole::Empty, Some(head)) => head.take_empty_utxo(self.deployment_id),
+            _ => index.resolve(self.deployment_id, role),
+        }
+    }
+
+    /// `given` when not empty, else the account's balance UTxOs
+    pub fn account_balance_utxos(
+        &self,
+        given: Vec<UTxO>,
+        account: &impl PlutusDataJson,
+        field: &str,
+    ) -> Result<Vec<UTxO>, WError> {
+        if !given.is_empty() {
+            return Ok(given);
+        }
+        let utxos = self
+            .index(field)?
+            .account_balance_utxos(self.deployment_id, account)?;
+        if utxos.is_empty() {
+            return Err(WError::new(
+                "InputResolver - account_balance_utxos",
+                "The account has no balance UTxOs in the head",
+            ));
+        }
+        Ok(utxos.into_iter().cloned().collect())
+    }
+
+    /// Fails when the index shows any of `utxos` spent; passes while nothing is indexed
+    pub fn check_unspent(&self, utxos: &[&UTxO]) -> Result<(), WError> {
+        match &self.index {
+            Some(index) => index.check_unspent(utxos),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::deployment::DEFAULT_DEPLOYMENT_ID,
+        scripts::UserTradeAccount,
+        test_utils::{init_test_env, TEST_NETWORK_ID},
+    };
+    use hibiki_proto::services::AccountInfo;
+    use serde_json::json;
+
+    const ORACLE_NFT: &str = "9ee27af30bcbcf1a399bfa531f5d9aef63f18c9ea761d5ce96ab3d6d";
+    const OWNER_KEY_HASH: &str = "80cfd3864eb58098e5fe8baa3adfb77dcc71c6ec086168ed676dbbf7";
+    const OWNER_ADDRESS: &str = "addr1vxqvl5uxf66cpx89l6965wklka7ucuwxasyxz68dvakmhacjpuz0c";
+    const OWNER_BASE_ADDRESS: &str = "addr1qxqvl5uxf66cpx89l6965wklka7ucuwxasyxz68dvakmhamdcgjlk94f0wjhvgu8fx3evwzw8dcnretnnj42xyuw7guqpp4d7m";
+    const REF_TX: &str = "74590385acea755d91a0d4c4f4c0fc8d9e1fa0627d33735a95a7f727ca371af6";
+    const TX: &str = "522ef27241b80a8ea4f54ed5c1674fcf49638c3a4b65a0e21371a21a0d343956";
+    const ALICE: &str = "2953c409-63d0-4bee-abbe-f11e4c8be0ce";
+    const BOB: &str = "0b9a4a2d-2f4e-4a57-9f0e-5c9e1d6a7b31";
+    const CAROL: &str = "7d1e5b0c-3a8f-4c2d-b6e4-9a0f1c2d3e4f";
+
+    fn scripts() -> DeploymentScripts {
+        init_test_env();
+        DeploymentScripts::new(ORACLE_NFT, TEST_NETWORK_ID).unwrap()
+    }
+
+    fn roles(scripts: &DeploymentScripts) -> Vec<DeploymentRoles> {
+        vec![DeploymentRoles::from_scripts(
+            DEFAULT_DEPLOYMENT_ID,
+            scripts,
+            ORACLE_NFT,
+            OWNER_KEY_HASH,
+        )]
+    }
+
+    fn account(account_id: &str, scripts: &DeploymentScripts) -> UserTradeAccount {
+        let info = AccountInfo {
+            account_id: account_id.to_string(),
+            account_type: "spot_account".to_string(),
+            master_key: "75073cd8873918696e373264763422e5c4d4fe63b4e941c42a5381d6".to_string(),
+            is_script_master_key: false,
+            operation_key: "c472ce633c4acf91dda311f10cb45671d6dcfbae3bdc25d219f83159".to_string(),
+            is_script_operation_key: false,
+        };
+        UserTradeAccount::from_proto(&info, &scripts.hydra_order_book_withdrawal.hash)
+    }
+
+    fn utxo_set(scripts: &DeploymentScripts) -> HydraUtxoSet {
+        let account_address = &scripts.hydra_account_spend.address;
+        let alice: Value = serde_json::from_str(&account(ALICE, scripts).to_json_string()).unwrap();
+        let bob: Value = serde_json::from_str(&account(BOB, scripts).to_json_string()).unwrap();
+        let usdm = scripts.hydra_token_mint.hash.clone();
+        serde_json::from_value(json!({
+            format!("{}#0", REF_TX): {
+                "address": OWNER_ADDRESS,
+                "value": { "lovelace": 10000000 },
+            },
+            format!("{}#3", REF_TX): {
+                "address": OWNER_ADDRESS,
+                "value": { "lovelace": 0 },
+                "referenceScript": {
+                    "scriptLanguage": "PlutusScriptLanguage PlutusScriptV3",
+                    "script": {
+                        "type": "PlutusScriptV3",
+                        "cborHex": scripts.hydra_user_intent_mint.cbor,
+                    },
+                },
+            },
+            format!("{}#0", TX): {
+                "address": scripts.hydra_user_intent_spend.address,
+                "value": { "lovelace": 0, ORACLE_NFT: { "": 1 } },
+            },
+            format!("{}#1", TX): {
+                "address": OWNER_BASE_ADDRESS,
+                "value": { "lovelace": 0 },
+            },
+            format!("{}#2", TX): {
+                "address": OWNER_BASE_ADDRESS,
+                "value": { "lovelace": 0 },
+            },
+            format!("{}#3", TX): {
+                "address": account_address,
+                "value": { "lovelace": 0, usdm.clone(): { "5553444d": 25000000 } },
+                "inlineDatum": alice.clone(),
+            },
+            format!("{}#4", TX): {
+                "address": account_address,
+                "value": { "lovelace": 0, usdm.clone(): { "4e49474854": 7 } },
+                "inlineDatum": alice,
+            },
+            format!("{}#5", TX): {
+                "address": account_address,
+                "value": { "lovelace": 0, usdm: { "5553444d": 1 } },
+                "inlineDatum": bob,
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Alice's balance output as hydra-node reports it in a `SnapshotConfirmed`, with the
+    /// order book hash left as `{order_book}` since it depends on the compiled scripts
+    const HYDRA_NODE_ACCOUNT_OUTPUT: &str = r#"{
+        "address": "{address}",
+        "datum": null,
+        "inlineDatum": {
+            "constructor": 0,
+            "fields": [
+                {
+                    "constructor": 0,
+                    "fields": [
+                        { "bytes": "2953c40963d04beeabbef11e4c8be0ce" },
+                        { "constructor": 0, "fields": [{ "bytes": "75073cd8873918696e373264763422e5c4d4fe63b4e941c42a5381d6" }] },
+                        { "constructor": 0, "fields": [{ "bytes": "c472ce633c4acf91dda311f10cb45671d6dcfbae3bdc25d219f83159" }] }
+                    ]
+                },
+                { "bytes": "{order_book}" }
+            ]
+        },
+        "inlineDatumRaw": "d8799fd8799f502953c40963d04beeabbef11e4c8be0ced8799f581c75073cd8873918696e373264763422e5c4d4fe63b4e941c42a5381d6ffd8799f581cc472ce633c4acf91dda311f10cb45671d6dcfbae3bdc25d219f83159ffff581c{order_book}ff",
+        "inlineDatumhash": null,
+        "referenceScript": null,
+        "value": { "lovelace": 0 }
+    }"#;
+
+    fn hydra_node_account_output(scripts: &DeploymentScripts) -> HydraTxOut {
+        let json = HYDRA_NODE_ACCOUNT_OUTPUT
+            .replace("{address}", &scripts.hydra_account_spend.address)
+            .replace("{order_book}", &scripts.hydra_order_book_withdrawal.hash);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn temp_index_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "hibiki-utxo-index-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_index_by_role_account_and_unit() {
+        let scripts = scripts();
+        let index = UtxoIndex::build(7, &utxo_set(&scripts), &roles(&scripts)).unwrap();
+        assert_eq!(index.len(), 8);
+        let id = DEFAULT_DEPLOYMENT_ID;
+
+        let collateral = index.resolve(id, UtxoRole::Collateral).unwrap();
+        assert_eq!(outpoint(&collateral.input), format!("{}#0", REF_TX));
+        assert_eq!(index.role_utxos(id, UtxoRole::ReferenceScript).len(), 1);
+        assert_eq!(index.role_utxos(id, UtxoRole::Empty).len(), 2);
+        // The order book holds the oracle NFT wherever it sits
+        let order_book = index.resolve(id, UtxoRole::OrderBook).unwrap();
+        assert_eq!(outpoint(&order_book.input), format!("{}#0", TX));
+        assert!(index.resolve(id, UtxoRole::UserIntent).is_err());
+
+        let alice = index
+            .account_balance_utxos(id, &account(ALICE, &scripts))
+            .unwrap();
+        assert_eq!(alice.len(), 2);
+        let bob = index
+            .account_balance_utxos(id, &account(BOB, &scripts))
+            .unwrap();
+        assert_eq!(outpoint(&bob[0].input), format!("{}#5", TX));
+        assert!(index
+            .account_balance_utxos("other", &account(BOB, &scripts))
+            .unwrap()
+            .is_empty());
+
+        let usdm = format!("{}5553444d", scripts.hydra_token_mint.hash);
+        assert_eq!(index.unit_utxos(&usdm).len(), 2);
+    }
+
+    #[test]
+    fn test_index_hydra_node_inline_datum() {
+        let scripts = scripts();
+        let captured = hydra_node_account_output(&scripts);
+        let mut raw_only = captured.clone();
+        raw_only.inline_datum = None;
+        let mut utxo = HydraUtxoSet::new();
+        utxo.insert(format!("{}#0", TX), captured);
+        utxo.insert(format!("{}#1", TX), raw_only);
+
+        let index = UtxoIndex::build(1, &utxo, &roles(&scripts)).unwrap();
+        let alice = index
+            .account_balance_utxos(DEFAULT_DEPLOYMENT_ID, &account(ALICE, &scripts))
+            .unwrap();
+        assert_eq!(alice.len(), 2);
+    }
+
+    #[test]
+    fn test_stale_inputs() {
+        let scripts = scripts();
+        let roles = roles(&scripts);
+        let mut utxo = utxo_set(&scripts);
+        let index = UtxoIndex::build(1, &utxo, &roles).unwrap();
+        let empty = index
+            .resolve(DEFAULT_DEPLOYMENT_ID, UtxoRole::Empty)
+            .unwrap();
+        assert!(index.check_unspent(&[&empty]).is_ok());
+
+        utxo.remove(&outpoint(&empty.input));
+        let index = UtxoIndex::build(2, &utxo, &roles).unwrap();
+        assert_eq!(index.stale_inputs(&[&empty]), vec![outpoint(&empty.input)]);
+        let error = index.check_unspent(&[&empty]).unwrap_err();
+        assert!(format!("{:?}", error).contains("spent by snapshot 2"));
+    }
+
+    #[tokio::test]
+    async fn test_head_index_follows_snapshots_and_persists() {
+        let scripts = scripts();
+        let path = temp_index_path("persist");
+        let head = Arc::new(HeadUtxoIndex::new(roles(&scripts), Some(path.clone())).unwrap());
+        assert!(head.current().is_none());
+        let resolver = InputResolver::new(Some(head.as_ref()), DEFAULT_DEPLOYMENT_ID);
+        assert!(resolver
+            .utxo(None, UtxoRole::Collateral, "collateral_utxo")
+            .is_err());
+
+        let utxo = utxo_set(&scripts);
+        let (sender, events) = broadcast::channel(8);
+        let follower = head.clone().follow(events);
+        sender
+            .send(HydraEvent::HeadIsOpen {
+                head_id: "head".to_string(),
+                utxo: HydraUtxoSet::new(),
+            })
+            .unwrap();
+        sender
+            .send(HydraEvent::SnapshotConfirmed {
+                number: 3,
+                confirmed: Vec::new(),
+                utxo: Some(utxo.clone()),
+            })
+            .unwrap();
+        // An older snapshot replayed out of order is ignored
+        sender
+            .send(HydraEvent::SnapshotConfirmed {
+                number: 2,
+                confirmed: Vec::new(),
+                utxo: Some(HydraUtxoSet::new()),
+            })
+            .unwrap();
+        drop(sender);
+        follower.await.unwrap();
+        assert_eq!(head.current().unwrap().snapshot_number, 3);
+        assert_eq!(head.current().unwrap().len(), 8);
+
+        let reloaded = HeadUtxoIndex::new(roles(&scripts), Some(path.clone())).unwrap();
+        assert_eq!(reloaded.current().unwrap().snapshot_number, 3);
+        assert_eq!(reloaded.current().unwrap().len(), 8);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resolver_reserves_empty_utxos() {
+        let scripts = scripts();
+        let head = HeadUtxoIndex::new(roles(&scripts), None).unwrap();
+        head.apply(1, &utxo_set(&scripts)).unwrap();
+        let resolver = InputResolver::new(Some(&head), DEFAULT_DEPLOYMENT_ID);
+
+        let first = resolver.utxo(None, UtxoRole::Empty, "empty_utxo").unwrap();
+        let second = resolver.utxo(None, UtxoRole::Empty, "empty_utxo").unwrap();
+        assert_ne!(first.input.output_index, second.input.output_index);
+        assert!(resolver.utxo(None, UtxoRole::Empty, "empty_utxo").is_err());
+
+        let balances = resolver
+            .account_balance_utxos(
+                Vec::new(),
+                &account(ALICE, &scripts),
+                "account_balance_utxos",
+            )
+            .unwrap();
+        assert_eq!(balances.len(), 2);
+        assert!(resolver
+            .account_balance_utxos(
+                Vec::new(),
+                &account(CAROL, &scripts),
+                "account_balance_utxos"
+            )
+            .is_err());
+
+        // A snapshot spending the first drops its reservation
+        let mut utxo = utxo_set(&scripts);
+        utxo.remove(&outpoint(&first.input));
+        head.apply(2, &utxo).unwrap();
+        let resolver = InputResolver::new(Some(&head), DEFAULT_DEPLOYMENT_ID);
+        assert!(resolver.check_unspent(&[&first]).is_err());
+        assert!(resolver.check_unspent(&[&second]).is_ok());
+        assert!(InputResolver::new(None, DEFAULT_DEPLOYMENT_ID)
+            .check_unspent(&[&first])
+            .is_ok());
+    }
+}
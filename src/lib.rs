@@ -1,11 +1,18 @@
 pub use hibiki_proto::services;
 pub mod config;
 pub mod constant;
+pub mod deployment;
+pub mod ext_services {
+    tonic::include_proto!("hibiki_ext");
+}
 pub mod grpc_metrics_interceptor;
 pub mod handler;
+pub mod hydra_node;
 pub mod metrics;
 pub mod metrics_server;
 pub mod scripts;
+pub mod secret;
+pub mod signer;
 pub mod utils;
 
 #[cfg(test)]
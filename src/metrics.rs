@@ -1,6 +1,6 @@
 use lazy_static::lazy_static;
 use prometheus::{
-    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
 };
 
 lazy_static! {
@@ -25,6 +25,25 @@ lazy_static! {
         &["method", "status"]
     )
     .expect("Failed to create GRPC_REQUESTS_TOTAL metric");
+
+    // Signing key metrics
+    pub static ref SIGNING_KEY_INFO: IntGaugeVec = IntGaugeVec::new(
+        Opts::new(
+            "hibiki_signing_key_info",
+            "Active signing key hash per role (always 1)"
+        ),
+        &["role", "key_hash"]
+    )
+    .expect("Failed to create SIGNING_KEY_INFO metric");
+
+    pub static ref SIGNING_KEY_ROTATIONS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "hibiki_signing_key_rotations_total",
+            "Total number of signing key rotations that changed the key"
+        ),
+        &["role"]
+    )
+    .expect("Failed to create SIGNING_KEY_ROTATIONS_TOTAL metric");
 }
 
 /// Initialize the Prometheus registry with all metrics
@@ -36,6 +55,14 @@ pub fn init_metrics() {
     REGISTRY
         .register(Box::new(GRPC_REQUESTS_TOTAL.clone()))
         .expect("Failed to register GRPC_REQUESTS_TOTAL");
+
+    REGISTRY
+        .register(Box::new(SIGNING_KEY_INFO.clone()))
+        .expect("Failed to register SIGNING_KEY_INFO");
+
+    REGISTRY
+        .register(Box::new(SIGNING_KEY_ROTATIONS_TOTAL.clone()))
+        .expect("Failed to register SIGNING_KEY_ROTATIONS_TOTAL");
 }
 
 /// Gather metrics and encode them in Prometheus text format
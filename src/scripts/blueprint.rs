@@ -0,0 +1,285 @@
+use serde::Deserialize;
+use std::fs;
+use whisky::{
+    blockfrost::utils::normalize_plutus_script,
+    csl::{self, PlutusScript},
+    Blueprint, WError,
+};
+
+use crate::scripts::bar::{BLUEPRINT, BLUEPRINT_JSON, EXPECTED_VALIDATORS};
+
+/// Source reported for the `plutus.json` compiled into the binary
+pub const COMPILED_IN_SOURCE: &str = "compiled-in";
+
+/// Whether the blueprint title `<module>.<validator>.<purpose>` is the validator `title`
+pub fn is_title(blueprint_title: &str, title: &str) -> bool {
+    blueprint_title == title
+        || blueprint_title
+            .strip_suffix(title)
+            .is_some_and(|module| module.ends_with('.'))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BlueprintCompiler {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BlueprintPreamble {
+    pub title: String,
+    pub description: String,
+    pub version: String,
+    pub plutus_version: String,
+    pub compiler: BlueprintCompiler,
+    pub license: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BlueprintParameter {
+    pub title: String,
+    pub schema: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BlueprintValidator {
+    pub title: String,
+    pub compiled_code: String,
+    /// Hash of the unparameterized script, as written by the compiler
+    pub hash: String,
+    pub parameters: Vec<BlueprintParameter>,
+    /// Only spending validators have a datum
+    pub datum: Option<serde_json::Value>,
+}
+
+/// The `plutus.json` the scripts are built from, and where it was read
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LoadedBlueprint {
+    #[serde(skip)]
+    pub source: String,
+    #[serde(default)]
+    pub preamble: BlueprintPreamble,
+    #[serde(default)]
+    pub validators: Vec<BlueprintValidator>,
+    #[serde(skip)]
+    json: String,
+}
+
+impl LoadedBlueprint {
+    /// Read `plutus.json` from `path`, or use the compiled-in copy when no path is set
+    pub fn load(path: Option<&str>) -> Result<Self, WError> {
+        match path {
+            Some(path) => {
+                let json = fs::read_to_string(path).map_err(|e| {
+                    WError::new(
+                        "LoadedBlueprint - load",
+                        &format!("Failed to read {}: {}", path, e),
+                    )
+                })?;
+                Self::from_json(path, json)
+            }
+            None => Self::from_json(COMPILED_IN_SOURCE, BLUEPRINT_JSON.to_string()),
+        }
+    }
+
+    pub fn from_json(source: &str, json: String) -> Result<Self, WError> {
+        let mut blueprint: LoadedBlueprint = serde_json::from_str(&json).map_err(|e| {
+            WError::new(
+                "LoadedBlueprint - from_json",
+                &format!("Invalid blueprint {}: {}", source, e),
+            )
+        })?;
+        blueprint.source = source.to_string();
+        blueprint.json = json;
+        Ok(blueprint)
+    }
+
+    /// Validators whose compiled code does not hash to their recorded `hash`
+    pub fn hash_mismatches(&self) -> Vec<String> {
+        self.validators
+            .iter()
+            .filter_map(|validator| match script_hash(&validator.compiled_code) {
+                Ok(hash) if hash == validator.hash => None,
+                Ok(hash) => Some(format!(
+                    "{} hashes to {}, blueprint says {}",
+                    validator.title, hash, validator.hash
+                )),
+                Err(e) => Some(format!("{}: {}", validator.title, e)),
+            })
+            .collect()
+    }
+
+    /// Expected validators that are missing, ambiguous or have another parameter or
+    /// purpose schema
+    pub fn schema_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (title, parameter_types) in EXPECTED_VALIDATORS {
+            let matches: Vec<&BlueprintValidator> = self
+                .validators
+                .iter()
+                .filter(|validator| is_title(&validator.title, title))
+                .collect();
+            let validator = match matches.as_slice() {
+                [validator] => validator,
+                [] => {
+                    errors.push(format!("{} is missing", title));
+                    continue;
+                }
+                _ => {
+                    errors.push(format!("{} matches {} validators", title, matches.len()));
+                    continue;
+                }
+            };
+
+            // `$ref`s are JSON pointers like `#/definitions/cardano~1assets~1PolicyId`
+            let types: Vec<String> = validator
+                .parameters
+                .iter()
+                .map(|parameter| {
+                    let schema_ref = parameter.schema["$ref"].as_str().unwrap_or_default();
+                    let path = schema_ref.replace("~1", "/");
+                    path.rsplit('/').next().unwrap_or_default().to_string()
+                })
+                .collect();
+            if types != *parameter_types {
+                errors.push(format!(
+                    "{} takes ({}), expected ({})",
+                    title,
+                    types.join(", "),
+                    parameter_types.join(", ")
+                ));
+            }
+
+            let is_spend = title.ends_with(".spend");
+            if validator.datum.is_some() != is_spend {
+                errors.push(match is_spend {
+                    true => format!("{} has no datum, expected a spending validator", title),
+                    false => format!("{} has a datum, expected no spending validator", title),
+                });
+            }
+        }
+        errors
+    }
+
+    /// Make this the blueprint every script is built from
+    ///
+    /// Must run before any script is built, as the first build fixes the blueprint.
+    pub fn install(&self) -> Result<(), WError> {
+        let blueprint: Blueprint = serde_json::from_str(&self.json)
+            .map_err(WError::from_err("LoadedBlueprint - install"))?;
+        BLUEPRINT.set(blueprint).map_err(|_| {
+            WError::new(
+                "LoadedBlueprint - install",
+                "Scripts were built before the blueprint was loaded",
+            )
+        })
+    }
+}
+
+/// Hash of a Plutus V3 script given as compiled code
+pub fn script_hash(compiled_code: &str) -> Result<String, WError> {
+    let normalized = normalize_plutus_script(compiled_code)
+        .map_err(WError::from_err("script_hash - normalize_plutus_script"))?;
+    let script = PlutusScript::from_hex_with_version(&normalized, &csl::Language::new_plutus_v3())
+        .map_err(WError::from_err("script_hash - from_hex_with_version"))?;
+    Ok(script.hash().to_hex())
+}
+
+/// Install the blueprint loaded at startup and keep it for reporting
+pub fn init_loaded_blueprint(
+    blueprint: LoadedBlueprint,
+) -> Result<&'static LoadedBlueprint, WError> {
+    blueprint.install()?;
+    Ok(Box::leak(Box::new(blueprint)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripts::bar::HYDRA_TOKENS_MINT;
+
+    #[test]
+    fn test_compiled_in_blueprint_hashes_match() {
+        let blueprint = LoadedBlueprint::load(None).unwrap();
+        assert_eq!(blueprint.source, COMPILED_IN_SOURCE);
+        assert!(!blueprint.validators.is_empty());
+        assert_eq!(blueprint.hash_mismatches(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_is_title() {
+        assert!(is_title("hydra_user_intent.mint", "hydra_user_intent.mint"));
+        assert!(is_title(
+            "hydra_user_intent.hydra_user_intent.mint",
+            "hydra_user_intent.mint"
+        ));
+        assert!(!is_title(
+            "hydra.not_hydra_user_intent.mint",
+            "hydra_user_intent.mint"
+        ));
+        assert!(!is_title(
+            "hydra_order_book.hydra_order_book_publish.withdraw",
+            "hydra_order_book.withdraw"
+        ));
+    }
+
+    #[test]
+    fn test_compiled_in_blueprint_has_expected_validators() {
+        let blueprint = LoadedBlueprint::load(None).unwrap();
+        assert_eq!(blueprint.schema_errors(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        let policy_id = serde_json::json!({
+            "title": "oracle_nft",
+            "schema": { "$ref": "#/definitions/cardano~1assets~1PolicyId" },
+        });
+        let validators: Vec<serde_json::Value> = EXPECTED_VALIDATORS
+            .iter()
+            .filter(|(title, _)| *title != HYDRA_TOKENS_MINT)
+            .map(|(title, parameter_types)| {
+                let mut validator = serde_json::json!({
+                    "title": format!("module.{}", title),
+                    "parameters": vec![policy_id.clone(); parameter_types.len()],
+                });
+                if title.ends_with(".spend") {
+                    validator["datum"] = serde_json::json!({ "title": "datum" });
+                }
+                validator
+            })
+            .collect();
+        let json = serde_json::json!({ "validators": validators }).to_string();
+        let blueprint = LoadedBlueprint::from_json("test.json", json).unwrap();
+        assert_eq!(
+            blueprint.schema_errors(),
+            vec![
+                "oracle_nft.mint takes (PolicyId), expected (OutputReference)",
+                "hydra_tokens.mint is missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_mismatch_is_reported() {
+        let compiled_in = LoadedBlueprint::load(None).unwrap();
+        let validator = &compiled_in.validators[0];
+        let json = serde_json::json!({
+            "preamble": { "title": "test", "version": "0.0.0", "plutusVersion": "v3" },
+            "validators": [{
+                "title": validator.title,
+                "compiledCode": validator.compiled_code,
+                "hash": "00".repeat(28),
+            }],
+        })
+        .to_string();
+        let blueprint = LoadedBlueprint::from_json("test.json", json).unwrap();
+        assert_eq!(blueprint.preamble.plutus_version, "v3");
+        assert_eq!(blueprint.hash_mismatches().len(), 1);
+        assert!(LoadedBlueprint::load(Some("/nonexistent/plutus.json")).is_err());
+    }
+}
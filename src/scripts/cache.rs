@@ -0,0 +1,125 @@
+use std::ops::Deref;
+use whisky::{
+    data::PlutusDataJson, MintingBlueprint, SpendingBlueprint, WError, WithdrawalBlueprint,
+};
+
+use crate::utils::hydra::get_script_ref_hex;
+
+/// What the transaction builders need of a parameterized script
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedScript {
+    pub hash: String,
+    /// `None` for minting scripts
+    pub address: Option<String>,
+    pub cbor: String,
+    /// Hex of the script as a reference script, for reference UTxOs
+    pub script_ref_hex: String,
+}
+
+impl CachedScript {
+    pub fn new(hash: &str, address: Option<&str>, cbor: &str) -> Result<Self, WError> {
+        Ok(CachedScript {
+            hash: hash.to_string(),
+            address: address.map(str::to_string),
+            cbor: cbor.to_string(),
+            script_ref_hex: get_script_ref_hex(cbor)?,
+        })
+    }
+}
+
+/// A parameterized blueprint, whose script is computed once by [`CachedBlueprint::new`]
+pub trait ScriptBlueprint {
+    fn script(&self) -> Result<CachedScript, WError>;
+}
+
+impl<P, R> ScriptBlueprint for MintingBlueprint<P, R>
+where
+    P: PlutusDataJson + Clone,
+    R: PlutusDataJson + Clone,
+{
+    fn script(&self) -> Result<CachedScript, WError> {
+        CachedScript::new(&self.hash, None, &self.cbor)
+    }
+}
+
+impl<P, R, D> ScriptBlueprint for SpendingBlueprint<P, R, D>
+where
+    P: PlutusDataJson + Clone,
+    R: PlutusDataJson + Clone,
+    D: PlutusDataJson + Clone,
+{
+    fn script(&self) -> Result<CachedScript, WError> {
+        CachedScript::new(&self.hash, Some(&self.address), &self.cbor)
+    }
+}
+
+impl<P, R> ScriptBlueprint for WithdrawalBlueprint<P, R>
+where
+    P: PlutusDataJson + Clone,
+    R: PlutusDataJson + Clone,
+{
+    fn script(&self) -> Result<CachedScript, WError> {
+        CachedScript::new(&self.hash, Some(&self.address), &self.cbor)
+    }
+}
+
+/// A typed blueprint with its script computed once; derefs to the blueprint
+pub struct CachedBlueprint<B> {
+    pub blueprint: B,
+    pub script: CachedScript,
+}
+
+impl<B: ScriptBlueprint> CachedBlueprint<B> {
+    pub fn new(blueprint: B) -> Result<Self, WError> {
+        Ok(CachedBlueprint {
+            script: blueprint.script()?,
+            blueprint,
+        })
+    }
+}
+
+impl<B> CachedBlueprint<B> {
+    pub fn script_ref_hex(&self) -> &str {
+        &self.script.script_ref_hex
+    }
+}
+
+impl<B> Deref for CachedBlueprint<B> {
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        &self.blueprint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub(Result<&'static str, &'static str>);
+
+    impl ScriptBlueprint for Stub {
+        fn script(&self) -> Result<CachedScript, WError> {
+            let hash = self.0.map_err(|e| WError::new("test", e))?;
+            Ok(CachedScript {
+                hash: hash.to_string(),
+                address: None,
+                cbor: String::new(),
+                script_ref_hex: format!("ref-{}", hash),
+            })
+        }
+    }
+
+    #[test]
+    fn test_script_is_computed_with_the_blueprint() {
+        let cached = CachedBlueprint::new(Stub(Ok("a"))).unwrap();
+        assert_eq!(cached.script.hash, "a");
+        assert_eq!(cached.script_ref_hex(), "ref-a");
+        assert_eq!(cached.0, Ok("a"));
+    }
+
+    #[test]
+    fn test_failed_script_fails_the_blueprint() {
+        assert!(CachedBlueprint::new(Stub(Err("parameterization failed"))).is_err());
+    }
+}
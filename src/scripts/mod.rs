@@ -2,3 +2,5 @@ pub mod types;
 pub use types::*;
 pub mod bar;
 pub use bar::*;
+pub mod blueprint;
+pub mod cache;
@@ -1,11 +1,8 @@
-use whisky::data::{ByteString, Constr0, Constr1, Constr2, Credential, PolicyId, ScriptHash};
+use whisky::data::{ByteString, Constr0, Constr1, Constr2, Credential, ScriptHash};
 
-use crate::{
-    constant::{dex_oracle_nft, SCRIPTS},
-    scripts::{
-        bar::{Account, MintMasterIntent, TransferIntent, UserTradeAccount},
-        MValue, MasterIntent,
-    },
+use crate::scripts::{
+    bar::{Account, MintMasterIntent, TransferIntent, UserTradeAccount},
+    MValue, MasterIntent,
 };
 
 impl Account {
@@ -26,7 +23,11 @@ impl Account {
 }
 
 impl UserTradeAccount {
-    pub fn from_proto(account_info: &hibiki_proto::services::AccountInfo) -> Self {
+    /// `hydra_order_book_hash` is the deployment's `hydra_order_book.withdraw` script hash
+    pub fn from_proto(
+        account_info: &hibiki_proto::services::AccountInfo,
+        hydra_order_book_hash: &str,
+    ) -> Self {
         let clean_account_id = account_info.account_id.replace("-", "");
 
         let account = Account::new_from_keys(
@@ -38,9 +39,7 @@ impl UserTradeAccount {
             ),
         );
 
-        let policy_id = PolicyId::new(dex_oracle_nft());
-        let blueprint = (SCRIPTS.hydra_order_book.withdrawal)(&policy_id);
-        let script_hash = ScriptHash::new(&blueprint.hash);
+        let script_hash = ScriptHash::new(hydra_order_book_hash);
 
         match account_info.account_type.as_str() {
             "spot_account" => UserTradeAccount(Constr0::new(Box::new((account, script_hash)))),
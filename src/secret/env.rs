@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+use std::env::var;
+use whisky::WError;
+
+use crate::secret::SecretProvider;
+
+/// Reads secrets from environment variables of the same name
+pub struct EnvSecretProvider;
+
+#[async_trait]
+impl SecretProvider for EnvSecretProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<Option<String>, WError> {
+        Ok(var(name).ok())
+    }
+}
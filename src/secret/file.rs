@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use std::{env::var, io::ErrorKind, path::PathBuf};
+use whisky::WError;
+
+use crate::secret::SecretProvider;
+
+/// Reads each secret from a file named after it, as mounted by Docker or Kubernetes
+pub struct FileSecretProvider {
+    dir: PathBuf,
+}
+
+impl FileSecretProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileSecretProvider { dir: dir.into() }
+    }
+
+    /// Directory from `SECRETS_DIR`, default `/run/secrets`
+    pub fn from_env() -> Self {
+        Self::new(var("SECRETS_DIR").unwrap_or_else(|_| "/run/secrets".to_string()))
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileSecretProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<Option<String>, WError> {
+        match tokio::fs::read_to_string(self.dir.join(name)).await {
+            Ok(secret) => Ok(Some(secret.trim_end().to_string())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(WError::new(
+                "FileSecretProvider - get_secret",
+                &format!("Failed to read {}: {}", name, e),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_secret_provider() {
+        let dir = std::env::temp_dir().join(format!("hibiki-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("TEST_SEED_PHRASE"), "word word\n").unwrap();
+
+        let provider = FileSecretProvider::new(&dir);
+        assert_eq!(
+            provider.get_secret("TEST_SEED_PHRASE").await.unwrap(),
+            Some("word word".to_string())
+        );
+        assert_eq!(provider.get_secret("MISSING").await.unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use base64::Engine;
+use gouth::Builder;
+use serde::Deserialize;
+use std::env::var;
+use whisky::WError;
+
+use crate::secret::SecretProvider;
+
+// Define structures to match the Google Cloud Secret Manager API response
+#[derive(Debug, Deserialize)]
+struct SecretPayload {
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessSecretVersionResponse {
+    payload: SecretPayload,
+}
+
+/// Reads secrets from Google Cloud Secret Manager using GKE Workload Identity and Gouth
+///
+/// A secret `NAME` is looked up in project `NAME_SECRET_MANAGER_PROJECT_ID` (falling back
+/// to `GCP_SECRET_MANAGER_PROJECT_ID`) as secret `NAME_SECRET_MANAGER_SECRET_ID` (default
+/// `NAME`) at version `NAME_SECRET_MANAGER_VERSION_ID` (default `latest`). Secrets without
+/// a project are not held by this provider.
+pub struct GcpSecretProvider {
+    client: reqwest::Client,
+    base_url: String,
+    access_token: Option<String>,
+}
+
+impl GcpSecretProvider {
+    pub fn new() -> Self {
+        GcpSecretProvider {
+            client: reqwest::Client::new(),
+            base_url: "https://secretmanager.googleapis.com".to_string(),
+            access_token: None,
+        }
+    }
+
+    /// Talk to another endpoint (an emulator or test server) with a fixed access token
+    pub fn with_endpoint(base_url: &str, access_token: &str) -> Self {
+        GcpSecretProvider {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token: Some(access_token.to_string()),
+        }
+    }
+
+    async fn authorization(&self) -> Result<String, WError> {
+        if let Some(token) = &self.access_token {
+            return Ok(format!("Bearer {}", token));
+        }
+        // Gouth fetches tokens with blocking I/O
+        tokio::task::spawn_blocking(|| {
+            Builder::new()
+                .scopes(&["https://www.googleapis.com/auth/cloud-platform"])
+                .build()
+                .and_then(|token| token.header_value())
+                .map(|header| header.to_string())
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(WError::from_err("GcpSecretProvider - token"))?
+        .map_err(|e| WError::new("GcpSecretProvider - token", &e))
+    }
+}
+
+impl Default for GcpSecretProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SecretProvider for GcpSecretProvider {
+    fn name(&self) -> &'static str {
+        "gcp"
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<Option<String>, WError> {
+        let Ok(project_id) = var(format!("{}_SECRET_MANAGER_PROJECT_ID", name))
+            .or_else(|_| var("GCP_SECRET_MANAGER_PROJECT_ID"))
+        else {
+            return Ok(None);
+        };
+        let secret_id =
+            var(format!("{}_SECRET_MANAGER_SECRET_ID", name)).unwrap_or_else(|_| name.to_string());
+        let version_id = var(format!("{}_SECRET_MANAGER_VERSION_ID", name))
+            .unwrap_or_else(|_| "latest".to_string());
+
+        let url = format!(
+            "{}/v1/projects/{}/secrets/{}/versions/{}:access",
+            self.base_url, project_id, secret_id, version_id
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header(reqwest::header::AUTHORIZATION, self.authorization().await?)
+            .send()
+            .await
+            .map_err(WError::from_err("GcpSecretProvider - send"))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(WError::new(
+                "GcpSecretProvider - get_secret",
+                &format!(
+                    "Failed to access secret version: Status: {}",
+                    response.status()
+                ),
+            ));
+        }
+
+        let secret_response: AccessSecretVersionResponse = response
+            .json()
+            .await
+            .map_err(WError::from_err("GcpSecretProvider - json"))?;
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&secret_response.payload.data)
+            .map_err(WError::from_err("GcpSecretProvider - base64"))?;
+        let secret =
+            String::from_utf8(decoded).map_err(WError::from_err("GcpSecretProvider - utf8"))?;
+        Ok(Some(secret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::secret::mock_http::start_mock_http_server;
+
+    #[tokio::test]
+    async fn test_gcp_secret_provider() {
+        unsafe {
+            std::env::set_var("GCP_TEST_SECRET_SECRET_MANAGER_PROJECT_ID", "project");
+            std::env::set_var("GCP_TEST_SECRET_SECRET_MANAGER_SECRET_ID", "seed-phrase");
+        }
+        let url = start_mock_http_server(Arc::new(|_method, path, headers| {
+            let authorized = headers
+                .get("authorization")
+                .is_some_and(|value| value == "Bearer test-token");
+            match path {
+                "/v1/projects/project/secrets/seed-phrase/versions/latest:access" if authorized => {
+                    (
+                        StatusCode::OK,
+                        r#"{"name": "seed-phrase", "payload": {"data": "d29yZCB3b3Jk"}}"#
+                            .to_string(),
+                    )
+                }
+                _ => (StatusCode::NOT_FOUND, String::new()),
+            }
+        }))
+        .await;
+
+        let provider = GcpSecretProvider::with_endpoint(&url, "test-token");
+        assert_eq!(
+            provider.get_secret("GCP_TEST_SECRET").await.unwrap(),
+            Some("word word".to_string())
+        );
+        // No project configured for this name
+        assert_eq!(provider.get_secret("GCP_UNCONFIGURED").await.unwrap(), None);
+    }
+}
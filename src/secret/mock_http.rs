@@ -0,0 +1,35 @@
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, HeaderMap, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+/// Answers a request from its method, path and headers with a status and body
+pub type MockHandler = Arc<dyn Fn(&Method, &str, &HeaderMap) -> (StatusCode, String) + Send + Sync>;
+
+/// Serve `handler` on a random local port, standing in for Vault or Secret Manager
+///
+/// Returns the base URL of the server.
+pub async fn start_mock_http_server(handler: MockHandler) -> String {
+    let make_svc = make_service_fn(move |_conn| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let (status, body) = handler(req.method(), req.uri().path(), req.headers());
+                async move {
+                    Ok::<_, Infallible>(
+                        Response::builder()
+                            .status(status)
+                            .body(Body::from(body))
+                            .unwrap(),
+                    )
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
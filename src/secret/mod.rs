@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{str::FromStr, sync::OnceLock, time::Duration};
+use whisky::WError;
+
+use crate::config::AppConfig;
+
+pub mod env;
+pub mod file;
+pub mod gcp;
+#[cfg(test)]
+pub mod mock_http;
+pub mod vault;
+
+pub use env::EnvSecretProvider;
+pub use file::FileSecretProvider;
+pub use gcp::GcpSecretProvider;
+pub use vault::VaultSecretProvider;
+
+/// A store hibiki can read secrets (seed phrases, passphrases, ...) from
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Short name for logs, e.g. `vault`
+    fn name(&self) -> &'static str;
+
+    /// Fetch the secret stored under `name`, e.g. `APP_OWNER_SEED_PHRASE`
+    ///
+    /// `Ok(None)` means this provider does not hold the secret; errors are treated as
+    /// transient and retried.
+    async fn get_secret(&self, name: &str) -> Result<Option<String>, WError>;
+}
+
+/// A provider listed in `secret_providers`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretProviderKind {
+    Env,
+    File,
+    Gcp,
+    Vault,
+}
+
+impl FromStr for SecretProviderKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "env" => Ok(SecretProviderKind::Env),
+            "file" => Ok(SecretProviderKind::File),
+            "gcp" => Ok(SecretProviderKind::Gcp),
+            "vault" => Ok(SecretProviderKind::Vault),
+            other => Err(format!(
+                "Unknown secret provider {} (expected env, file, gcp or vault)",
+                other
+            )),
+        }
+    }
+}
+
+impl SecretProviderKind {
+    fn provider(self) -> Result<Box<dyn SecretProvider>, String> {
+        Ok(match self {
+            SecretProviderKind::Env => Box::new(EnvSecretProvider),
+            SecretProviderKind::File => Box::new(FileSecretProvider::from_env()),
+            SecretProviderKind::Gcp => Box::new(GcpSecretProvider::new()),
+            SecretProviderKind::Vault => Box::new(VaultSecretProvider::from_env()?),
+        })
+    }
+}
+
+/// Delay before the first retry of a lookup, doubled for each further one
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Secret providers tried in order, each lookup retried with exponential backoff
+pub struct Secrets {
+    providers: Vec<Box<dyn SecretProvider>>,
+    attempts: u32,
+    backoff: Duration,
+}
+
+impl Secrets {
+    pub fn new(providers: Vec<Box<dyn SecretProvider>>, attempts: u32, backoff: Duration) -> Self {
+        Secrets {
+            providers,
+            attempts: attempts.max(1),
+            backoff,
+        }
+    }
+
+    /// The providers of `secret_providers`, each lookup tried `secret_provider_attempts` times
+    pub fn from_config(config: &AppConfig) -> Result<Self, String> {
+        let providers = config
+            .secret_providers
+            .iter()
+            .map(|kind| kind.provider())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(
+            providers,
+            config.secret_provider_attempts,
+            RETRY_BACKOFF,
+        ))
+    }
+
+    /// The secret from the first provider holding it
+    pub async fn get(&self, name: &str) -> Result<String, WError> {
+        self.get_optional(name).await?.ok_or_else(|| {
+            WError::new(
+                "Secrets - get",
+                &format!("{} not found in any secret provider", name),
+            )
+        })
+    }
+
+    /// Like [`Secrets::get`], but `Ok(None)` when no provider holds the secret
+    pub async fn get_optional(&self, name: &str) -> Result<Option<String>, WError> {
+        for provider in &self.providers {
+            if let Some(secret) = self.get_with_retry(provider.as_ref(), name).await? {
+                return Ok(Some(secret));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_with_retry(
+        &self,
+        provider: &dyn SecretProvider,
+        name: &str,
+    ) -> Result<Option<String>, WError> {
+        let mut attempt = 1;
+        loop {
+            match provider.get_secret(name).await {
+                Ok(secret) => return Ok(secret),
+                Err(e) if attempt < self.attempts => {
+                    eprintln!(
+                        "Failed to read {} from {} (attempt {}/{}): {}",
+                        name,
+                        provider.name(),
+                        attempt,
+                        self.attempts,
+                        e
+                    );
+                    tokio::time::sleep(self.backoff * 2u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+static SECRETS: OnceLock<Secrets> = OnceLock::new();
+
+/// Install the providers configured at startup, before any key is loaded
+pub fn init_secrets(providers: Secrets) -> &'static Secrets {
+    if SECRETS.set(providers).is_err() {
+        eprintln!("Warning: secret providers already initialized, keeping the first ones");
+    }
+    secrets()
+}
+
+/// The providers installed at startup, else the environment alone
+pub fn secrets() -> &'static Secrets {
+    SECRETS.get_or_init(|| Secrets::new(vec![Box::new(EnvSecretProvider)], 1, RETRY_BACKOFF))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Fails `failures` times, then returns `secret` for every name
+    struct FlakyProvider {
+        failures: u32,
+        calls: Arc<AtomicU32>,
+        secret: Option<String>,
+    }
+
+    #[async_trait]
+    impl SecretProvider for FlakyProvider {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn get_secret(&self, _name: &str) -> Result<Option<String>, WError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(WError::new("FlakyProvider", "unavailable"));
+            }
+            Ok(self.secret.clone())
+        }
+    }
+
+    fn flaky(failures: u32, secret: Option<&str>) -> (Box<dyn SecretProvider>, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let provider = FlakyProvider {
+            failures,
+            calls: calls.clone(),
+            secret: secret.map(|secret| secret.to_string()),
+        };
+        (Box::new(provider), calls)
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        let (provider, calls) = flaky(2, Some("secret"));
+        let secrets = Secrets::new(vec![provider], 3, Duration::ZERO);
+        assert_eq!(secrets.get("NAME").await.unwrap(), "secret");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_attempts() {
+        let (provider, calls) = flaky(5, Some("secret"));
+        let secrets = Secrets::new(vec![provider], 2, Duration::ZERO);
+        assert!(secrets.get("NAME").await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_falls_through_to_next_provider() {
+        let (missing, missing_calls) = flaky(0, None);
+        let (holding, _) = flaky(0, Some("from second"));
+        let secrets = Secrets::new(vec![missing, holding], 3, Duration::ZERO);
+        assert_eq!(secrets.get("NAME").await.unwrap(), "from second");
+        assert_eq!(missing_calls.load(Ordering::SeqCst), 1);
+    }
+}
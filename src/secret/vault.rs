@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::env::var;
+use whisky::WError;
+
+use crate::secret::SecretProvider;
+
+/// Reads secrets from one HashiCorp Vault KV v2 entry, each secret a key of that entry
+pub struct VaultSecretProvider {
+    client: reqwest::Client,
+    addr: String,
+    token: String,
+    mount: String,
+    path: String,
+    namespace: Option<String>,
+}
+
+impl VaultSecretProvider {
+    pub fn new(addr: &str, token: &str, mount: &str, path: &str) -> Self {
+        VaultSecretProvider {
+            client: reqwest::Client::new(),
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            mount: mount.trim_matches('/').to_string(),
+            path: path.trim_matches('/').to_string(),
+            namespace: None,
+        }
+    }
+
+    /// Entry at `VAULT_ADDR`/v1/`VAULT_KV_MOUNT` (default `secret`)/data/`VAULT_SECRET_PATH`
+    /// (default `hibiki`), read with `VAULT_TOKEN` in `VAULT_NAMESPACE` if set
+    pub fn from_env() -> Result<Self, String> {
+        let addr = var("VAULT_ADDR").map_err(|_| "VAULT_ADDR not set in environment")?;
+        let token = var("VAULT_TOKEN").map_err(|_| "VAULT_TOKEN not set in environment")?;
+        let mount = var("VAULT_KV_MOUNT").unwrap_or_else(|_| "secret".to_string());
+        let path = var("VAULT_SECRET_PATH").unwrap_or_else(|_| "hibiki".to_string());
+
+        let mut provider = Self::new(&addr, &token, &mount, &path);
+        provider.namespace = var("VAULT_NAMESPACE").ok();
+        Ok(provider)
+    }
+}
+
+#[async_trait]
+impl SecretProvider for VaultSecretProvider {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<Option<String>, WError> {
+        let url = format!("{}/v1/{}/data/{}", self.addr, self.mount, self.path);
+        let mut request = self.client.get(&url).header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(WError::from_err("VaultSecretProvider - send"))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(WError::new(
+                "VaultSecretProvider - get_secret",
+                &format!("Failed to read {}: Status: {}", url, response.status()),
+            ));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(WError::from_err("VaultSecretProvider - json"))?;
+        Ok(body["data"]["data"][name]
+            .as_str()
+            .map(|secret| secret.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::secret::{mock_http::start_mock_http_server, Secrets};
+
+    #[tokio::test]
+    async fn test_vault_secret_provider() {
+        let url = start_mock_http_server(Arc::new(|_method, path, headers| {
+            let authorized = headers
+                .get("x-vault-token")
+                .is_some_and(|value| value == "root");
+            match path {
+                "/v1/secret/data/hibiki" if authorized => (
+                    StatusCode::OK,
+                    r#"{"data": {"data": {"APP_OWNER_SEED_PHRASE": "word word"}, "metadata": {}}}"#
+                        .to_string(),
+                ),
+                _ if !authorized => (StatusCode::FORBIDDEN, String::new()),
+                _ => (StatusCode::NOT_FOUND, String::new()),
+            }
+        }))
+        .await;
+
+        let provider = VaultSecretProvider::new(&url, "root", "secret", "hibiki");
+        assert_eq!(
+            provider.get_secret("APP_OWNER_SEED_PHRASE").await.unwrap(),
+            Some("word word".to_string())
+        );
+        assert_eq!(provider.get_secret("MISSING").await.unwrap(), None);
+
+        let unauthorized = VaultSecretProvider::new(&url, "wrong", "secret", "hibiki");
+        assert!(unauthorized
+            .get_secret("APP_OWNER_SEED_PHRASE")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vault_retried_until_available() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let url = start_mock_http_server(Arc::new(move |_method, _path, _headers| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                return (StatusCode::SERVICE_UNAVAILABLE, String::new());
+            }
+            (
+                StatusCode::OK,
+                r#"{"data": {"data": {"SECRET": "value"}}}"#.to_string(),
+            )
+        }))
+        .await;
+
+        let secrets = Secrets::new(
+            vec![Box::new(VaultSecretProvider::new(
+                &url, "root", "secret", "hibiki",
+            ))],
+            3,
+            std::time::Duration::ZERO,
+        );
+        assert_eq!(secrets.get("SECRET").await.unwrap(), "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,28 +1,200 @@
 use dotenv::dotenv;
-use std::env;
-use std::sync::Arc;
-use whisky::{calculate_tx_hash, Wallet};
+use std::{path::PathBuf, sync::Arc};
+use whisky::calculate_tx_hash;
 
 use hibiki::{
+    config::{
+        hydra::{init_hydra_params, HydraParams},
+        init_app_config,
+        self_check::{evaluate_script, run_self_check},
+        signing_keys::{APP_OWNER_ROLE, FEE_COLLECTOR_ROLE},
+        signing_policy::FeeCollectorPolicy,
+        AppConfig,
+    },
+    deployment::{Deployment, Deployments, DEPLOYMENT_METADATA_KEY},
+    ext_services::{
+        self,
+        hibiki_ext_server::{HibikiExt, HibikiExtServer},
+    },
     grpc_metrics_interceptor::MetricsLayer,
     handler::{
-        internal_transfer, process_transfer, serialize_transfer_intent_datum, sign_transaction,
-        sign_transaction_with_fee_collector,
+        add_token, convert_amounts, get_account_balance, get_deployment_info, internal_transfer,
+        list_tokens, merge_transaction_witnesses, process_transfer, remove_token,
+        rotate_signing_keys, serialize_transfer_intent_datum,
+        sign_transaction::{self, authorize_role},
+        sign_transaction_with_fee_collector, sign_transaction_with_role, sign_transaction_witness,
+        submit_and_confirm_tx, verify_transaction_signatures,
+    },
+    hydra_node::{
+        utxo_index::{DeploymentRoles, HeadUtxoIndex},
+        HydraNodeClient, HydraNodeConfig,
     },
     metrics, metrics_server,
+    scripts::blueprint::{init_loaded_blueprint, LoadedBlueprint},
+    secret::{init_secrets, Secrets},
     services::{
         self,
         hibiki_server::{Hibiki, HibikiServer},
         TxHashResponse,
     },
-    utils::wallet::{get_app_owner_wallet, get_fee_collector_wallet},
+    signer::{
+        registry::{RPC_SIGN_TRANSACTION_WITH_ROLE, RPC_SIGN_TRANSACTION_WITNESS},
+        KeyRegistry, RotatingSigner, Signer,
+    },
+    utils::{
+        audit_log::{open_signing_audit_log, AuditLog, Caller, SigningContext},
+        built_tx_registry::{BuiltTxRegistry, BUILD_TOKEN_METADATA_KEY},
+        wallet::{get_app_owner_signer, get_fee_collector_signer},
+    },
 };
 use std::time::Instant;
-use tonic::{transport::Server, Request, Response, Status};
+use tokio::signal::unix::{signal, SignalKind};
+use tonic::{metadata::MetadataMap, transport::Server, Request, Response, Status};
 
+const CALLER_METADATA_KEY: &str = "x-hibiki-caller";
+const ADMIN_TOKEN_METADATA_KEY: &str = "x-hibiki-admin-token";
+
+#[derive(Clone)]
 pub struct HibikiService {
-    pub app_owner_wallet: Arc<Wallet>,
-    pub fee_collector_wallet: Arc<Wallet>,
+    pub deployments: Arc<Deployments>,
+    pub config: &'static AppConfig,
+    pub blueprint: &'static LoadedBlueprint,
+    pub signing_keys: Arc<KeyRegistry>,
+    pub fee_collector_policy: Arc<FeeCollectorPolicy>,
+    pub built_txs: Arc<BuiltTxRegistry>,
+    pub hydra_node: Option<Arc<HydraNodeClient>>,
+    pub utxo_index: Option<Arc<HeadUtxoIndex>>,
+    /// Where signing attempts are audited; `None` when explicitly disabled
+    pub audit_log: Option<Arc<AuditLog>>,
+}
+
+impl HibikiService {
+    /// The deployment named by `x-hibiki-deployment` metadata, else the default one
+    fn deployment<T>(&self, request: &Request<T>) -> Result<Arc<Deployment>, Status> {
+        let id = request
+            .metadata()
+            .get(DEPLOYMENT_METADATA_KEY)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        self.deployments
+            .get(id)
+            .map_err(|e| Status::not_found(e.to_string()))
+    }
+
+    /// Record a tx hibiki built and attach its build token to the response metadata
+    fn record_built_tx<T>(&self, reply: T, tx_hash: &str) -> Response<T> {
+        let token = self.built_txs.record(tx_hash);
+        let mut response = Response::new(reply);
+        if let Some(token) = token.and_then(|token| token.parse().ok()) {
+            response
+                .metadata_mut()
+                .insert(BUILD_TOKEN_METADATA_KEY, token);
+        }
+        response
+    }
+
+    /// Context of a signing RPC, audited to the service's log
+    fn signing_context(&self, method: &str, caller: Caller, role: &str) -> SigningContext {
+        SigningContext::new(method, caller, role, self.audit_log.clone())
+    }
+
+    /// Resolve the key for `role` (app owner when empty) and run its signing policy,
+    /// auditing any rejection
+    async fn authorize_role(
+        &self,
+        deployment: &Deployment,
+        rpc: &str,
+        role: &str,
+        tx_hex: &str,
+        build_token: Option<&str>,
+        caller: Caller,
+    ) -> Result<(Arc<RotatingSigner>, SigningContext), Status> {
+        let role = if role.is_empty() {
+            APP_OWNER_ROLE
+        } else {
+            role
+        };
+        let signer = self
+            .signing_keys
+            .resolve(role, rpc)
+            .map_err(|e| Status::permission_denied(e.to_string()))?;
+        // Built-in roles sign with the selected deployment's own keys
+        let signer = deployment.signer(role).unwrap_or(signer);
+        let ctx = self.signing_context(rpc, caller, role);
+
+        let utxos = self.utxo_index.as_ref().and_then(|index| index.current());
+        authorize_role(
+            role,
+            &*signer,
+            tx_hex,
+            &self.built_txs,
+            build_token,
+            &self.fee_collector_policy,
+            utxos.as_deref(),
+        )
+        .await
+        .or_else(|e| ctx.audited(tx_hex, Err(e)))
+        .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        Ok((signer, ctx))
+    }
+
+    /// Every rotating signer: the registered roles and each deployment's own keys
+    fn signers(&self) -> Vec<&RotatingSigner> {
+        let mut signers = self.signing_keys.signers();
+        signers.extend(self.deployments.own_signers());
+        signers
+    }
+
+    /// Rotate every signing key, logging rather than failing on errors
+    async fn rotate_all_signing_keys(&self) {
+        for signer in self.signers() {
+            match signer.rotate().await {
+                Ok(rotation) => println!(
+                    "Rotated {} signing key: {} -> {}",
+                    rotation.role, rotation.previous_key_hash, rotation.key_hash
+                ),
+                Err(e) => eprintln!("Failed to rotate {} signing key: {}", signer.role(), e),
+            }
+        }
+    }
+}
+
+/// Admin RPCs require `admin_api_token` to be configured and presented in metadata
+fn authorize_admin<T>(config: &AppConfig, request: &Request<T>) -> Result<(), Status> {
+    let Some(expected) = &config.admin_api_token else {
+        return Err(Status::permission_denied("Admin RPCs are disabled"));
+    };
+    let presented = request
+        .metadata()
+        .get(ADMIN_TOKEN_METADATA_KEY)
+        .and_then(|value| value.to_str().ok());
+    if presented != Some(expected.as_str()) {
+        return Err(Status::unauthenticated("Invalid admin token"));
+    }
+    Ok(())
+}
+
+/// Caller for the audit log: the peer address, and the `x-hibiki-caller` metadata kept
+/// apart as the client's unverified claim
+fn caller<T>(request: &Request<T>) -> Caller {
+    Caller {
+        address: request
+            .remote_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+        claimed: request
+            .metadata()
+            .get(CALLER_METADATA_KEY)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    }
+}
+
+fn build_token(metadata: &MetadataMap) -> Option<String> {
+    metadata
+        .get(BUILD_TOKEN_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
 }
 
 #[tonic::async_trait]
@@ -41,31 +213,50 @@ impl Hibiki for HibikiService {
         &self,
         request: Request<services::InternalTransferRequest>,
     ) -> Result<Response<services::IntentTxResponse>, Status> {
+        let deployment = self.deployment(&request)?;
         let request_result = request.into_inner();
         println!("Got a request - internal_transfer {:?}", request_result);
 
-        let reply = match internal_transfer::handler(request_result).await {
+        let reply = match internal_transfer::handler(
+            request_result,
+            &deployment,
+            self.utxo_index.as_deref(),
+        )
+        .await
+        {
             Ok(value) => value,
             Err(e) => {
                 return Err(Status::failed_precondition(e.to_string()));
             }
         };
-        Ok(Response::new(reply))
+        let tx_hash = reply.tx_hash.clone();
+        Ok(self.record_built_tx(reply, &tx_hash))
     }
 
     async fn process_transfer(
         &self,
         request: Request<services::ProcessTransferRequest>,
     ) -> Result<Response<services::ProcessTransferResponse>, Status> {
+        let deployment = self.deployment(&request)?;
+        let ctx = self.signing_context("process_transfer", caller(&request), APP_OWNER_ROLE);
         let request_result = request.into_inner();
         println!("Got a request - process_transfer {:?}", request_result);
-        let reply = match process_transfer::handler(request_result, &self.app_owner_wallet).await {
+        let reply = match process_transfer::handler(
+            request_result,
+            &*deployment.app_owner_signer,
+            &ctx,
+            &deployment,
+            self.utxo_index.as_deref(),
+        )
+        .await
+        {
             Ok(value) => value,
             Err(e) => {
                 return Err(Status::failed_precondition(e.to_string()));
             }
         };
-        Ok(Response::new(reply))
+        let tx_hash = reply.tx_hash.clone();
+        Ok(self.record_built_tx(reply, &tx_hash))
     }
 
     async fn serialize_transferal_intent_datum(
@@ -73,8 +264,9 @@ impl Hibiki for HibikiService {
         request: Request<services::SerializeTransferalIntentDatumRequest>,
     ) -> Result<Response<services::SerializeDatumResponse>, Status> {
         println!("Got a request - serialize_transferal_intent_datum");
+        let deployment = self.deployment(&request)?;
         let request_result = request.into_inner();
-        let reply = match serialize_transfer_intent_datum::handler(request_result) {
+        let reply = match serialize_transfer_intent_datum::handler(request_result, &deployment) {
             Ok(value) => value,
             Err(e) => {
                 return Err(Status::failed_precondition(e.to_string()));
@@ -89,8 +281,19 @@ impl Hibiki for HibikiService {
     ) -> Result<Response<services::SignTransactionResponse>, Status> {
         let start = Instant::now();
         println!("Got a request - sign_transaction");
+        let deployment = self.deployment(&request)?;
+        let token = build_token(request.metadata());
+        let ctx = self.signing_context("sign_transaction", caller(&request), APP_OWNER_ROLE);
         let request_result = request.into_inner();
-        let reply = match sign_transaction::handler(request_result, &self.app_owner_wallet) {
+        let reply = match sign_transaction::handler(
+            request_result,
+            &*deployment.app_owner_signer,
+            &self.built_txs,
+            token.as_deref(),
+            &ctx,
+        )
+        .await
+        {
             Ok(value) => value,
             Err(e) => {
                 return Err(Status::failed_precondition(e.to_string()));
@@ -106,11 +309,23 @@ impl Hibiki for HibikiService {
     ) -> Result<Response<services::SignTransactionResponse>, Status> {
         let start = Instant::now();
         println!("Got a request - sign_transaction_with_fee_collector");
+        let deployment = self.deployment(&request)?;
+        let ctx = self.signing_context(
+            "sign_transaction_with_fee_collector",
+            caller(&request),
+            FEE_COLLECTOR_ROLE,
+        );
         let request_result = request.into_inner();
+        let utxos = self.utxo_index.as_ref().and_then(|index| index.current());
         let reply = match sign_transaction_with_fee_collector::handler(
             request_result,
-            &self.fee_collector_wallet,
-        ) {
+            &*deployment.fee_collector_signer,
+            &self.fee_collector_policy,
+            utxos.as_deref(),
+            &ctx,
+        )
+        .await
+        {
             Ok(value) => value,
             Err(e) => {
                 return Err(Status::failed_precondition(e.to_string()));
@@ -139,6 +354,243 @@ impl Hibiki for HibikiService {
     }
 }
 
+#[tonic::async_trait]
+impl HibikiExt for HibikiService {
+    async fn sign_transaction_with_role(
+        &self,
+        request: Request<ext_services::SignTransactionWithRoleRequest>,
+    ) -> Result<Response<ext_services::SignTransactionWithRoleResponse>, Status> {
+        println!("Got a request - sign_transaction_with_role");
+        let deployment = self.deployment(&request)?;
+        let token = build_token(request.metadata());
+        let caller = caller(&request);
+        let request_result = request.into_inner();
+        let (signer, ctx) = self
+            .authorize_role(
+                &deployment,
+                RPC_SIGN_TRANSACTION_WITH_ROLE,
+                &request_result.role,
+                &request_result.tx_hex,
+                token.as_deref(),
+                caller,
+            )
+            .await?;
+        let reply = match sign_transaction_with_role::handler(request_result, &*signer, &ctx).await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn sign_transaction_witness(
+        &self,
+        request: Request<ext_services::SignTransactionWitnessRequest>,
+    ) -> Result<Response<ext_services::SignTransactionWitnessResponse>, Status> {
+        println!("Got a request - sign_transaction_witness");
+        let deployment = self.deployment(&request)?;
+        let token = build_token(request.metadata());
+        let caller = caller(&request);
+        let request_result = request.into_inner();
+        let (signer, ctx) = self
+            .authorize_role(
+                &deployment,
+                RPC_SIGN_TRANSACTION_WITNESS,
+                &request_result.role,
+                &request_result.tx_hex,
+                token.as_deref(),
+                caller,
+            )
+            .await?;
+        let reply = match sign_transaction_witness::handler(request_result, &*signer, &ctx).await {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn merge_transaction_witnesses(
+        &self,
+        request: Request<ext_services::MergeTransactionWitnessesRequest>,
+    ) -> Result<Response<ext_services::MergeTransactionWitnessesResponse>, Status> {
+        println!("Got a request - merge_transaction_witnesses");
+        let request_result = request.into_inner();
+        let reply = match merge_transaction_witnesses::handler(request_result) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn verify_transaction_signatures(
+        &self,
+        request: Request<ext_services::VerifyTransactionSignaturesRequest>,
+    ) -> Result<Response<ext_services::VerifyTransactionSignaturesResponse>, Status> {
+        println!("Got a request - verify_transaction_signatures");
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match verify_transaction_signatures::handler(
+            request_result,
+            &*deployment.app_owner_signer,
+            &*deployment.fee_collector_signer,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn rotate_signing_keys(
+        &self,
+        request: Request<ext_services::RotateSigningKeysRequest>,
+    ) -> Result<Response<ext_services::RotateSigningKeysResponse>, Status> {
+        println!("Got a request - rotate_signing_keys");
+        authorize_admin(self.config, &request)?;
+        let request_result = request.into_inner();
+        let reply = match rotate_signing_keys::handler(request_result, &self.signers()).await {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn list_tokens(
+        &self,
+        request: Request<ext_services::ListTokensRequest>,
+    ) -> Result<Response<ext_services::ListTokensResponse>, Status> {
+        println!("Got a request - list_tokens");
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match list_tokens::handler(request_result, &deployment.tokens) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn add_token(
+        &self,
+        request: Request<ext_services::AddTokenRequest>,
+    ) -> Result<Response<ext_services::AddTokenResponse>, Status> {
+        println!("Got a request - add_token");
+        authorize_admin(self.config, &request)?;
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match add_token::handler(request_result, &deployment.tokens) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn remove_token(
+        &self,
+        request: Request<ext_services::RemoveTokenRequest>,
+    ) -> Result<Response<ext_services::RemoveTokenResponse>, Status> {
+        println!("Got a request - remove_token");
+        authorize_admin(self.config, &request)?;
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match remove_token::handler(request_result, &deployment.tokens) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn convert_amounts(
+        &self,
+        request: Request<ext_services::ConvertAmountsRequest>,
+    ) -> Result<Response<ext_services::ConvertAmountsResponse>, Status> {
+        println!("Got a request - convert_amounts");
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match convert_amounts::handler(request_result, &deployment.tokens) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn get_deployment_info(
+        &self,
+        request: Request<ext_services::GetDeploymentInfoRequest>,
+    ) -> Result<Response<ext_services::GetDeploymentInfoResponse>, Status> {
+        println!("Got a request - get_deployment_info");
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match get_deployment_info::handler(request_result, &deployment, self.blueprint)
+        {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn submit_and_confirm_tx(
+        &self,
+        request: Request<ext_services::SubmitAndConfirmTxRequest>,
+    ) -> Result<Response<ext_services::SubmitAndConfirmTxResponse>, Status> {
+        println!("Got a request - submit_and_confirm_tx");
+        let request_result = request.into_inner();
+        let reply = match submit_and_confirm_tx::handler(
+            request_result,
+            self.hydra_node.as_deref(),
+            self.config,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn get_account_balance(
+        &self,
+        request: Request<ext_services::GetAccountBalanceRequest>,
+    ) -> Result<Response<ext_services::GetAccountBalanceResponse>, Status> {
+        println!("Got a request - get_account_balance");
+        let deployment = self.deployment(&request)?;
+        let request_result = request.into_inner();
+        let reply = match get_account_balance::handler(
+            request_result,
+            &deployment,
+            self.utxo_index.as_deref(),
+        ) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(Status::failed_precondition(e.to_string()));
+            }
+        };
+        Ok(Response::new(reply))
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
@@ -146,16 +598,113 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize Prometheus metrics
     metrics::init_metrics();
 
-    let grpc_port = env::var("PORT").unwrap_or_else(|_| "50051".to_string());
-    let metrics_port: u16 = env::var("METRICS_PORT")
-        .unwrap_or_else(|_| "9090".to_string())
-        .parse()
-        .expect("METRICS_PORT must be a valid port number");
+    // Load and validate the config once, before anything reads it
+    let config = init_app_config(AppConfig::load()?);
+    let grpc_port = config.port;
+    let metrics_port = config.metrics_port;
+    // Install the blueprint before any script is built from it
+    let blueprint = LoadedBlueprint::load(config.plutus_json_path.as_deref())
+        .and_then(init_loaded_blueprint)
+        .map_err(|e| e.to_string())?;
+    // Build and evaluate txs as the head does
+    let hydra = HydraParams::load(
+        config.hydra_protocol_parameters_path.as_deref(),
+        &config.hydra_protocol_parameters,
+    )
+    .map(init_hydra_params)
+    .map_err(|e| e.to_string())?;
+    // Secrets are read from the configured providers from here on
+    init_secrets(Secrets::from_config(config)?);
+    let built_txs = BuiltTxRegistry::from_config(config);
+    let audit_log = open_signing_audit_log(config).map_err(|e| e.to_string())?;
+    match &audit_log {
+        Some(log) => println!("Auditing signatures to {}", log.path().display()),
+        None => println!("Warning: signing audit log disabled, signatures are not audited"),
+    }
 
     let grpc_addr = format!("0.0.0.0:{}", grpc_port).parse()?;
+    let app_owner_signer = get_app_owner_signer(config)
+        .await
+        .map_err(|e| e.to_string())?;
+    let fee_collector_signer = get_fee_collector_signer(config)
+        .await
+        .map_err(|e| e.to_string())?;
+    let signing_keys = KeyRegistry::load(
+        config,
+        app_owner_signer.clone(),
+        fee_collector_signer.clone(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let deployments = Deployments::load(config, app_owner_signer, fee_collector_signer)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Refuse to start with keys that could never fully sign the txs we build
+    let mut key_hashes = Vec::new();
+    for signer in signing_keys.signers() {
+        key_hashes.push((
+            signer.role().to_string(),
+            signer.key_hash().await.map_err(|e| e.to_string())?,
+        ));
+    }
+    let mut app_owners = Vec::new();
+    for deployment in deployments.all() {
+        app_owners.push((
+            deployment.id.clone(),
+            deployment
+                .app_owner_signer
+                .key_hash()
+                .await
+                .map_err(|e| e.to_string())?,
+        ));
+    }
+    // Run a script with the loaded cost models and limits
+    let script_evaluation = evaluate_script(hydra, config.network_id).await;
+    let self_check = run_self_check(
+        config,
+        blueprint,
+        hydra,
+        &key_hashes,
+        &app_owners,
+        script_evaluation,
+    );
+    print!("{}", self_check);
+    if !self_check.passed() {
+        return Err("Startup self-check failed".into());
+    }
+
+    let mut hydra_node = None;
+    let mut utxo_index = None;
+    if let Some(url) = &config.hydra_node_url {
+        println!("Connecting to hydra-node at {}...", url);
+        let (client, events) = HydraNodeClient::connect(HydraNodeConfig::new(url));
+        let roles = deployments
+            .all()
+            .iter()
+            .map(|deployment| DeploymentRoles::new(deployment))
+            .collect();
+        let index = HeadUtxoIndex::new(
+            roles,
+            config.hydra_utxo_index_path.as_ref().map(PathBuf::from),
+        )
+        .map_err(|e| e.to_string())?;
+        let index = Arc::new(index);
+        index.clone().follow(events);
+        hydra_node = Some(Arc::new(client));
+        utxo_index = Some(index);
+    }
+
     let transactions = HibikiService {
-        app_owner_wallet: Arc::new(get_app_owner_wallet()),
-        fee_collector_wallet: Arc::new(get_fee_collector_wallet()),
+        deployments: Arc::new(deployments),
+        config,
+        blueprint,
+        signing_keys: Arc::new(signing_keys),
+        fee_collector_policy: Arc::new(FeeCollectorPolicy::from_config(config)),
+        built_txs: Arc::new(built_txs),
+        hydra_node,
+        utxo_index,
+        audit_log: audit_log.map(Arc::new),
     };
 
     println!("gRPC Server listening on port {}...", grpc_port);
@@ -168,10 +717,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Rotate signing keys on SIGHUP
+    let rotation_service = transactions.clone();
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            println!("Received SIGHUP, rotating signing keys...");
+            rotation_service.rotate_all_signing_keys().await;
+        }
+    });
+
     // Start gRPC server with metrics layer
     Server::builder()
         .layer(MetricsLayer)
-        .add_service(HibikiServer::new(transactions))
+        .add_service(HibikiServer::new(transactions.clone()))
+        .add_service(HibikiExtServer::new(transactions))
         .serve(grpc_addr)
         .await?;
 
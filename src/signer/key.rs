@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use whisky::{csl, WError};
+
+use crate::signer::Signer;
+
+/// Signs with a raw ed25519 key held in process memory
+///
+/// Used for keys whisky wallets cannot express: BIP39 passphrases, non-payment roles,
+/// Ledger master keys, root keys and cardano-cli `.skey` files. The witness is added
+/// without re-serializing the transaction body.
+pub struct KeySigner {
+    key: csl::PrivateKey,
+    key_hash: String,
+}
+
+impl KeySigner {
+    pub fn new(key: csl::PrivateKey) -> Self {
+        let key_hash = key.to_public().hash().to_hex();
+        KeySigner { key, key_hash }
+    }
+}
+
+#[async_trait]
+impl Signer for KeySigner {
+    async fn key_hash(&self) -> Result<String, WError> {
+        Ok(self.key_hash.clone())
+    }
+
+    async fn sign_tx(&self, tx_hex: &str) -> Result<String, WError> {
+        let mut tx = csl::FixedTransaction::from_hex(tx_hex)
+            .map_err(WError::from_err("KeySigner - from_hex"))?;
+        let witness = csl::make_vkey_witness(&tx.transaction_hash(), &self.key);
+        tx.add_vkey_witness(&witness);
+        Ok(tx.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::witness::verify_signatures;
+
+    // Unsigned tx requiring no signers
+    const TX: &str = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca0f5d90103a0";
+
+    #[tokio::test]
+    async fn test_key_signer_adds_valid_witness() {
+        let key = csl::PrivateKey::from_normal_bytes(&[7u8; 32]).unwrap();
+        let signer = KeySigner::new(key);
+
+        let signed = signer.sign_tx(TX).await.unwrap();
+        let report = verify_signatures(&signed).unwrap();
+        assert!(report.invalid_witnesses.is_empty());
+        assert_eq!(
+            report.extraneous_witnesses,
+            vec![signer.key_hash().await.unwrap()]
+        );
+    }
+}
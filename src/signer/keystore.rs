@@ -0,0 +1,324 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use whisky::WError;
+
+const KEYSTORE_VERSION: u8 = 2;
+const CIPHER: &str = "chacha20-poly1305";
+
+/// scrypt cost parameters, stored alongside the ciphertext
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        ScryptParams {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// Argon2id cost parameters, stored alongside the ciphertext
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Argon2Params {
+    /// Memory in KiB
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Argon2Params {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// Key derivation function stretching the passphrase into the cipher key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum Kdf {
+    Scrypt(ScryptParams),
+    Argon2id(Argon2Params),
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::Scrypt(ScryptParams::default())
+    }
+}
+
+impl Kdf {
+    fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; 32], WError> {
+        let mut key = [0u8; 32];
+        match self {
+            Kdf::Scrypt(params) => {
+                let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p, 32)
+                    .map_err(WError::from_err("Keystore - scrypt params"))?;
+                scrypt::scrypt(passphrase.as_bytes(), salt, &scrypt_params, &mut key)
+                    .map_err(WError::from_err("Keystore - scrypt"))?;
+            }
+            Kdf::Argon2id(params) => {
+                let argon2_params =
+                    argon2::Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+                        .map_err(WError::from_err("Keystore - argon2 params"))?;
+                argon2::Argon2::new(
+                    argon2::Algorithm::Argon2id,
+                    argon2::Version::V0x13,
+                    argon2_params,
+                )
+                .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                .map_err(WError::from_err("Keystore - argon2"))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Passphrase-encrypted seed phrase, stored as JSON
+///
+/// The passphrase is stretched with scrypt or Argon2id into a ChaCha20-Poly1305 key, so a
+/// tampered file or wrong passphrase fails authentication instead of yielding a bad key.
+/// The key hash is kept in the clear for inspection and authenticated as associated data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "KeystoreFile")]
+pub struct Keystore {
+    pub version: u8,
+    pub cipher: String,
+    pub kdf: Kdf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_hash: Option<String>,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// On-disk layout of every keystore version; version 1 stored bare scrypt params
+#[derive(Deserialize)]
+struct KeystoreFile {
+    version: u8,
+    cipher: String,
+    kdf: Option<Kdf>,
+    scrypt: Option<ScryptParams>,
+    key_hash: Option<String>,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl TryFrom<KeystoreFile> for Keystore {
+    type Error = String;
+
+    fn try_from(file: KeystoreFile) -> Result<Self, Self::Error> {
+        let kdf = match (file.version, file.kdf, file.scrypt) {
+            (1, _, Some(scrypt)) => Kdf::Scrypt(scrypt),
+            (KEYSTORE_VERSION, Some(kdf), _) => kdf,
+            (version, _, _) => {
+                return Err(format!(
+                    "Unsupported or malformed keystore version {}",
+                    version
+                ))
+            }
+        };
+        Ok(Keystore {
+            version: file.version,
+            cipher: file.cipher,
+            kdf,
+            key_hash: file.key_hash,
+            salt: file.salt,
+            nonce: file.nonce,
+            ciphertext: file.ciphertext,
+        })
+    }
+}
+
+impl Keystore {
+    /// Encrypt `mnemonic`, recording `key_hash` (the wallet's payment key hash) in the clear
+    pub fn encrypt(
+        mnemonic: &str,
+        passphrase: &str,
+        kdf: &Kdf,
+        key_hash: Option<&str>,
+    ) -> Result<Keystore, WError> {
+        let mut salt = [0u8; 32];
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let key = kdf.derive_key(passphrase, &salt)?;
+        let payload = Payload {
+            msg: mnemonic.as_bytes(),
+            aad: key_hash.unwrap_or_default().as_bytes(),
+        };
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(WError::from_err("Keystore - encrypt"))?;
+
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            cipher: CIPHER.to_string(),
+            kdf: kdf.clone(),
+            key_hash: key_hash.map(|key_hash| key_hash.to_string()),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<String, WError> {
+        if self.cipher != CIPHER {
+            return Err(WError::new(
+                "Keystore - decrypt",
+                &format!("Unsupported keystore cipher {}", self.cipher),
+            ));
+        }
+
+        let salt = hex::decode(&self.salt).map_err(WError::from_err("Keystore - salt"))?;
+        let nonce = hex::decode(&self.nonce).map_err(WError::from_err("Keystore - nonce"))?;
+        let ciphertext =
+            hex::decode(&self.ciphertext).map_err(WError::from_err("Keystore - ciphertext"))?;
+        if nonce.len() != 12 {
+            return Err(WError::new("Keystore - nonce", "Nonce must be 12 bytes"));
+        }
+
+        let key = self.kdf.derive_key(passphrase, &salt)?;
+        // Version 1 did not authenticate a key hash
+        let aad = match self.version {
+            1 => "",
+            _ => self.key_hash.as_deref().unwrap_or_default(),
+        };
+        let payload = Payload {
+            msg: ciphertext.as_slice(),
+            aad: aad.as_bytes(),
+        };
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| {
+                WError::new(
+                    "Keystore - decrypt",
+                    "Wrong passphrase or corrupted keystore",
+                )
+            })?;
+
+        String::from_utf8(plaintext).map_err(WError::from_err("Keystore - from_utf8"))
+    }
+
+    /// Decrypt with `passphrase` and encrypt again under `new_passphrase` and `kdf`,
+    /// keeping the key hash
+    pub fn reencrypt(
+        &self,
+        passphrase: &str,
+        new_passphrase: &str,
+        kdf: &Kdf,
+    ) -> Result<Keystore, WError> {
+        let mnemonic = self.decrypt(passphrase)?;
+        Keystore::encrypt(&mnemonic, new_passphrase, kdf, self.key_hash.as_deref())
+    }
+
+    pub fn read(path: &str) -> Result<Keystore, WError> {
+        let content = std::fs::read_to_string(path).map_err(WError::from_err("Keystore - read"))?;
+        serde_json::from_str(&content).map_err(WError::from_err("Keystore - parse"))
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), WError> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(WError::from_err("Keystore - serialize"))?;
+        std::fs::write(path, content).map_err(WError::from_err("Keystore - write"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer";
+    const KEY_HASH: &str = "fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c";
+
+    fn test_kdf() -> Kdf {
+        Kdf::Scrypt(ScryptParams {
+            log_n: 10,
+            r: 8,
+            p: 1,
+        })
+    }
+
+    fn test_argon2_kdf() -> Kdf {
+        Kdf::Argon2id(Argon2Params {
+            m_cost: 1024,
+            t_cost: 1,
+            p_cost: 1,
+        })
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        for kdf in [test_kdf(), test_argon2_kdf()] {
+            let keystore =
+                Keystore::encrypt(MNEMONIC, "correct horse", &kdf, Some(KEY_HASH)).unwrap();
+            let json = serde_json::to_string(&keystore).unwrap();
+            let parsed: Keystore = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, keystore);
+            assert_eq!(parsed.decrypt("correct horse").unwrap(), MNEMONIC);
+        }
+    }
+
+    #[test]
+    fn test_keystore_wrong_passphrase() {
+        let keystore = Keystore::encrypt(MNEMONIC, "correct horse", &test_kdf(), None).unwrap();
+        assert!(keystore.decrypt("battery staple").is_err());
+    }
+
+    #[test]
+    fn test_keystore_tampered_ciphertext() {
+        let mut keystore = Keystore::encrypt(MNEMONIC, "correct horse", &test_kdf(), None).unwrap();
+        let mut ciphertext = hex::decode(&keystore.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        keystore.ciphertext = hex::encode(ciphertext);
+        assert!(keystore.decrypt("correct horse").is_err());
+    }
+
+    #[test]
+    fn test_keystore_tampered_key_hash() {
+        let mut keystore =
+            Keystore::encrypt(MNEMONIC, "correct horse", &test_kdf(), Some(KEY_HASH)).unwrap();
+        keystore.key_hash = Some("00".repeat(28));
+        assert!(keystore.decrypt("correct horse").is_err());
+    }
+
+    #[test]
+    fn test_keystore_reencrypt() {
+        let keystore = Keystore::encrypt(MNEMONIC, "old", &test_kdf(), Some(KEY_HASH)).unwrap();
+        let reencrypted = keystore
+            .reencrypt("old", "new", &test_argon2_kdf())
+            .unwrap();
+        assert_eq!(reencrypted.key_hash.as_deref(), Some(KEY_HASH));
+        assert_eq!(reencrypted.kdf, test_argon2_kdf());
+        assert!(reencrypted.decrypt("old").is_err());
+        assert_eq!(reencrypted.decrypt("new").unwrap(), MNEMONIC);
+    }
+
+    #[test]
+    fn test_keystore_reads_version_1() {
+        // Version 1 layout: bare scrypt params, no key hash
+        let v1 = Keystore::encrypt(MNEMONIC, "correct horse", &test_kdf(), None).unwrap();
+        let json = format!(
+            r#"{{"version":1,"cipher":"{}","scrypt":{{"log_n":10,"r":8,"p":1}},"salt":"{}","nonce":"{}","ciphertext":"{}"}}"#,
+            v1.cipher, v1.salt, v1.nonce, v1.ciphertext
+        );
+        let parsed: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.kdf, test_kdf());
+        assert_eq!(parsed.decrypt("correct horse").unwrap(), MNEMONIC);
+    }
+}
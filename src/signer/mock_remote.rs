@@ -0,0 +1,65 @@
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use crate::signer::{
+    remote::{RemoteKeyHashResponse, RemoteSignRequest, RemoteSignResponse},
+    Signer,
+};
+
+/// Serve `signer` with the remote signer HTTP API on a random local port
+///
+/// Returns the base URL to pass to [`crate::signer::RemoteSigner::new`].
+pub async fn start_mock_remote_signer(signer: Arc<dyn Signer>) -> String {
+    let make_svc = make_service_fn(move |_conn| {
+        let signer = signer.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let signer = signer.clone();
+                async move { Ok::<_, Infallible>(route(signer, req).await) }
+            }))
+        }
+    });
+
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+async fn route(signer: Arc<dyn Signer>, req: Request<Body>) -> Response<Body> {
+    let result =
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/key_hash") => signer.key_hash().await.map(|key_hash| {
+                serde_json::to_string(&RemoteKeyHashResponse { key_hash }).unwrap()
+            }),
+            (&Method::POST, "/sign") => {
+                let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                let request: RemoteSignRequest = serde_json::from_slice(&body).unwrap();
+                signer.sign_tx(&request.tx_hex).await.map(|signed_tx| {
+                    serde_json::to_string(&RemoteSignResponse { signed_tx }).unwrap()
+                })
+            }
+            _ => {
+                return Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::from("Not Found"))
+                    .unwrap()
+            }
+        };
+
+    match result {
+        Ok(body) => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(e) => Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(e.to_string()))
+            .unwrap(),
+    }
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use whisky::WError;
+
+use crate::{
+    config::{get_mnemonic, signer::SignerBackend, signing_keys::Derivation},
+    secret::secrets,
+    utils::hd_key::{
+        derive_signing_key, root_key_from_mnemonic, root_key_from_str, signing_key_from_cli_skey,
+    },
+};
+
+pub mod key;
+pub mod keystore;
+#[cfg(test)]
+pub mod mock_remote;
+pub mod registry;
+pub mod remote;
+pub mod rotating;
+pub mod wallet;
+
+pub use key::KeySigner;
+pub use keystore::{Kdf, Keystore};
+pub use registry::KeyRegistry;
+pub use remote::RemoteSigner;
+pub use rotating::{RotatingSigner, SignerLoader};
+pub use wallet::WalletSigner;
+
+/// A key hibiki can sign transactions with, independent of where the key is held
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Hex encoded hash of the verification key, as used in `required_signers`
+    async fn key_hash(&self) -> Result<String, WError>;
+
+    /// Every key hash this signer will currently sign for (more than one mid-rotation)
+    async fn active_key_hashes(&self) -> Result<Vec<String>, WError> {
+        Ok(vec![self.key_hash().await?])
+    }
+
+    /// Add this key's vkey witness to the transaction and return the signed tx hex
+    async fn sign_tx(&self, tx_hex: &str) -> Result<String, WError>;
+}
+
+/// Build the signer of the key with env prefix `prefix`, held by `backend` and derived
+/// along `derivation` (see [`crate::config::AppConfig::signer_backend`])
+///
+/// The seed phrase is only fetched for the mnemonic backend, so keystore and remote
+/// deployments never need it in a secret store.
+pub async fn load_signer(
+    prefix: &str,
+    backend: SignerBackend,
+    derivation: Derivation,
+) -> Result<Arc<dyn Signer>, WError> {
+    let signer: Arc<dyn Signer> = match backend {
+        SignerBackend::Mnemonic => {
+            let mnemonic = get_mnemonic(prefix).await?;
+            Arc::new(mnemonic_signer(prefix, &mnemonic, derivation).await?)
+        }
+        SignerBackend::Keystore { path } => {
+            let passphrase = secrets()
+                .get(&format!("{}_KEYSTORE_PASSPHRASE", prefix))
+                .await?;
+            let keystore = Keystore::read(&path)?;
+            let signer =
+                mnemonic_signer(prefix, &keystore.decrypt(&passphrase)?, derivation).await?;
+            if let Some(expected) = &keystore.key_hash {
+                let key_hash = signer.key_hash().await?;
+                if &key_hash != expected {
+                    return Err(WError::new(
+                        "load_signer",
+                        &format!(
+                            "Keystore {} was created for key hash {}, derivation gives {}",
+                            path, expected, key_hash
+                        ),
+                    ));
+                }
+            }
+            Arc::new(signer)
+        }
+        SignerBackend::RootKey => {
+            let root_key = secrets().get(&format!("{}_ROOT_KEY", prefix)).await?;
+            let root = root_key_from_str(&root_key)?;
+            Arc::new(KeySigner::new(derive_signing_key(&root, derivation)))
+        }
+        SignerBackend::CliSkey { path } => {
+            let skey = std::fs::read_to_string(&path)
+                .map_err(WError::from_err("load_signer - read skey"))?;
+            Arc::new(KeySigner::new(signing_key_from_cli_skey(&skey)?))
+        }
+        SignerBackend::Remote { url, auth_token } => Arc::new(RemoteSigner::new(&url, auth_token)),
+    };
+    Ok(signer)
+}
+
+/// Signer for a mnemonic, with the optional `<PREFIX>_BIP39_PASSPHRASE` secret
+async fn mnemonic_signer(
+    prefix: &str,
+    mnemonic: &str,
+    derivation: Derivation,
+) -> Result<KeySigner, WError> {
+    let passphrase = secrets()
+        .get_optional(&format!("{}_BIP39_PASSPHRASE", prefix))
+        .await?
+        .unwrap_or_default();
+    let root = root_key_from_mnemonic(mnemonic, &passphrase, derivation.master_key)?;
+    Ok(KeySigner::new(derive_signing_key(&root, derivation)))
+}
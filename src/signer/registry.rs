@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use whisky::WError;
+
+use crate::{
+    config::{signing_keys::ANY_RPC, AppConfig},
+    signer::RotatingSigner,
+    utils::wallet::load_rotating_signer,
+};
+
+/// RPC names used in `allowed_rpcs`
+pub const RPC_SIGN_TRANSACTION_WITH_ROLE: &str = "sign_transaction_with_role";
+pub const RPC_SIGN_TRANSACTION_WITNESS: &str = "sign_transaction_witness";
+
+struct RegisteredKey {
+    signer: Arc<RotatingSigner>,
+    allowed_rpcs: Vec<String>,
+}
+
+/// Every signing key hibiki holds, looked up by role name
+///
+/// The app owner and fee collector keys are always registered and usable from every
+/// role-based RPC; further keys come from `signing_keys` of the [`AppConfig`].
+pub struct KeyRegistry {
+    keys: Vec<RegisteredKey>,
+}
+
+impl KeyRegistry {
+    pub async fn load(
+        config: &AppConfig,
+        app_owner_signer: Arc<RotatingSigner>,
+        fee_collector_signer: Arc<RotatingSigner>,
+    ) -> Result<Self, WError> {
+        let mut keys = vec![
+            RegisteredKey {
+                signer: app_owner_signer,
+                allowed_rpcs: vec![ANY_RPC.to_string()],
+            },
+            RegisteredKey {
+                signer: fee_collector_signer,
+                allowed_rpcs: vec![ANY_RPC.to_string()],
+            },
+        ];
+
+        for key in &config.signing_keys {
+            let signer =
+                load_rotating_signer(config, &key.role, key.env_prefix(), key.derivation).await?;
+            keys.push(RegisteredKey {
+                signer,
+                allowed_rpcs: key.allowed_rpcs.clone(),
+            });
+        }
+
+        Ok(KeyRegistry { keys })
+    }
+
+    /// The signer for `role`, if it may be used from `rpc`
+    pub fn resolve(&self, role: &str, rpc: &str) -> Result<Arc<RotatingSigner>, WError> {
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.signer.role() == role)
+            .ok_or_else(|| {
+                WError::new("KeyRegistry - resolve", &format!("Unknown role: {}", role))
+            })?;
+
+        if !key
+            .allowed_rpcs
+            .iter()
+            .any(|allowed| allowed == ANY_RPC || allowed == rpc)
+        {
+            return Err(WError::new(
+                "KeyRegistry - resolve",
+                &format!("Role {} may not sign via {}", role, rpc),
+            ));
+        }
+        Ok(key.signer.clone())
+    }
+
+    pub fn signers(&self) -> Vec<&RotatingSigner> {
+        self.keys.iter().map(|key| &*key.signer).collect()
+    }
+}
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use whisky::{calculate_tx_hash, WError};
+
+use crate::signer::Signer;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteSignRequest {
+    pub tx_hex: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteSignResponse {
+    pub signed_tx: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteKeyHashResponse {
+    pub key_hash: String,
+}
+
+/// Signs through an external signer service over HTTP
+///
+/// The service exposes `GET {url}/key_hash` and `POST {url}/sign`. The returned tx is
+/// rejected if its body hash differs from the one we sent, so the remote can only add
+/// witnesses.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    auth_token: Option<String>,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, auth_token: Option<String>) -> Self {
+        RemoteSigner {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            auth_token,
+        }
+    }
+
+    fn with_auth(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn key_hash(&self) -> Result<String, WError> {
+        let response = self
+            .with_auth(self.client.get(format!("{}/key_hash", self.url)))
+            .send()
+            .await
+            .map_err(WError::from_err("RemoteSigner - key_hash"))?
+            .error_for_status()
+            .map_err(WError::from_err("RemoteSigner - key_hash"))?
+            .json::<RemoteKeyHashResponse>()
+            .await
+            .map_err(WError::from_err("RemoteSigner - key_hash response"))?;
+        Ok(response.key_hash)
+    }
+
+    async fn sign_tx(&self, tx_hex: &str) -> Result<String, WError> {
+        let response = self
+            .with_auth(self.client.post(format!("{}/sign", self.url)))
+            .json(&RemoteSignRequest {
+                tx_hex: tx_hex.to_string(),
+            })
+            .send()
+            .await
+            .map_err(WError::from_err("RemoteSigner - sign_tx"))?
+            .error_for_status()
+            .map_err(WError::from_err("RemoteSigner - sign_tx"))?
+            .json::<RemoteSignResponse>()
+            .await
+            .map_err(WError::from_err("RemoteSigner - sign_tx response"))?;
+
+        if calculate_tx_hash(&response.signed_tx)? != calculate_tx_hash(tx_hex)? {
+            return Err(WError::new(
+                "RemoteSigner - sign_tx",
+                "Remote signer returned a transaction with a different body",
+            ));
+        }
+        Ok(response.signed_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use whisky::NetworkId;
+
+    use super::*;
+    use crate::{
+        signer::{mock_remote::start_mock_remote_signer, WalletSigner},
+        test_utils::init_test_env,
+        utils::wallet::get_app_owner_wallet,
+    };
+
+    #[tokio::test]
+    async fn test_remote_signer_matches_local_signer() {
+        init_test_env();
+        let local_signer: Arc<dyn Signer> =
+            Arc::new(WalletSigner::new(get_app_owner_wallet(NetworkId::Preprod).await).unwrap());
+        let url = start_mock_remote_signer(local_signer.clone()).await;
+        let remote_signer = RemoteSigner::new(&url, None);
+
+        assert_eq!(
+            remote_signer.key_hash().await.unwrap(),
+            local_signer.key_hash().await.unwrap()
+        );
+
+        let tx_hex = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca10081825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402f5d90103a0";
+        assert_eq!(
+            remote_signer.sign_tx(tx_hex).await.unwrap(),
+            local_signer.sign_tx(tx_hex).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_unreachable() {
+        let remote_signer = RemoteSigner::new("http://127.0.0.1:1", None);
+        assert!(remote_signer.key_hash().await.is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use whisky::WError;
+
+use crate::{
+    metrics::{SIGNING_KEY_INFO, SIGNING_KEY_ROTATIONS_TOTAL},
+    signer::Signer,
+    utils::witness::required_signers,
+};
+
+/// Builds a fresh signer from the current secret, called at startup and on every rotation
+pub type SignerLoader =
+    Arc<dyn Fn() -> BoxFuture<'static, Result<Arc<dyn Signer>, WError>> + Send + Sync>;
+
+#[derive(Clone)]
+struct ActiveKey {
+    signer: Arc<dyn Signer>,
+    key_hash: String,
+}
+
+struct Keys {
+    current: ActiveKey,
+    /// The key replaced by the last rotation, and when it was replaced
+    previous: Option<(ActiveKey, Instant)>,
+}
+
+/// Outcome of [`RotatingSigner::rotate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rotation {
+    pub role: String,
+    pub key_hash: String,
+    pub previous_key_hash: String,
+}
+
+impl Rotation {
+    pub fn changed(&self) -> bool {
+        self.key_hash != self.previous_key_hash
+    }
+}
+
+/// A signer whose key can be swapped at runtime by re-running its loader
+///
+/// After a rotation the previous key keeps signing transactions that require it
+/// (and not the new key) for `grace_period`, so txs built just before the swap
+/// still go through.
+pub struct RotatingSigner {
+    role: String,
+    loader: SignerLoader,
+    grace_period: Duration,
+    keys: RwLock<Keys>,
+}
+
+impl RotatingSigner {
+    pub async fn new(
+        role: &str,
+        loader: SignerLoader,
+        grace_period: Duration,
+    ) -> Result<Self, WError> {
+        let current = load_key(&loader).await?;
+        SIGNING_KEY_INFO
+            .with_label_values(&[role, current.key_hash.as_str()])
+            .set(1);
+        Ok(RotatingSigner {
+            role: role.to_string(),
+            loader,
+            grace_period,
+            keys: RwLock::new(Keys {
+                current,
+                previous: None,
+            }),
+        })
+    }
+
+    pub fn role(&self) -> &str {
+        &self.role
+    }
+
+    /// Re-run the loader and atomically swap in the new key
+    pub async fn rotate(&self) -> Result<Rotation, WError> {
+        let next = load_key(&self.loader).await?;
+
+        let mut keys = self.keys.write().unwrap();
+        let previous_key_hash = keys.current.key_hash.clone();
+        if next.key_hash != previous_key_hash {
+            let _ = SIGNING_KEY_INFO
+                .remove_label_values(&[self.role.as_str(), previous_key_hash.as_str()]);
+            SIGNING_KEY_INFO
+                .with_label_values(&[self.role.as_str(), next.key_hash.as_str()])
+                .set(1);
+            SIGNING_KEY_ROTATIONS_TOTAL
+                .with_label_values(&[self.role.as_str()])
+                .inc();
+
+            let retired = std::mem::replace(&mut keys.current, next);
+            keys.previous = Some((retired, Instant::now()));
+        }
+
+        Ok(Rotation {
+            role: self.role.clone(),
+            key_hash: keys.current.key_hash.clone(),
+            previous_key_hash,
+        })
+    }
+
+    fn active_keys(&self) -> Vec<ActiveKey> {
+        let keys = self.keys.read().unwrap();
+        let mut active = vec![keys.current.clone()];
+        if let Some((previous, retired_at)) = &keys.previous {
+            if retired_at.elapsed() < self.grace_period {
+                active.push(previous.clone());
+            }
+        }
+        active
+    }
+}
+
+async fn load_key(loader: &SignerLoader) -> Result<ActiveKey, WError> {
+    let signer = loader().await?;
+    let key_hash = signer.key_hash().await?;
+    Ok(ActiveKey { signer, key_hash })
+}
+
+#[async_trait]
+impl Signer for RotatingSigner {
+    async fn key_hash(&self) -> Result<String, WError> {
+        Ok(self.keys.read().unwrap().current.key_hash.clone())
+    }
+
+    async fn active_key_hashes(&self) -> Result<Vec<String>, WError> {
+        Ok(self
+            .active_keys()
+            .into_iter()
+            .map(|key| key.key_hash)
+            .collect())
+    }
+
+    async fn sign_tx(&self, tx_hex: &str) -> Result<String, WError> {
+        let active = self.active_keys();
+        let required = required_signers(tx_hex)?;
+        let key = active
+            .iter()
+            .find(|key| required.contains(&key.key_hash))
+            .unwrap_or(&active[0]);
+        key.signer.sign_tx(tx_hex).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use whisky::NetworkId;
+
+    use super::*;
+    use crate::{
+        signer::WalletSigner, test_utils::init_test_env, utils::wallet::wallet_from_mnemonic,
+    };
+
+    const MNEMONICS: [&str; 2] = [
+        "summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer",
+        "trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade trade",
+    ];
+
+    fn wallet_signer(mnemonic: &str) -> Result<Arc<dyn Signer>, WError> {
+        let wallet = wallet_from_mnemonic(mnemonic, NetworkId::Preprod)?;
+        Ok(Arc::new(WalletSigner::new(wallet)?))
+    }
+
+    /// Loader that returns the next mnemonic's wallet on every call
+    fn cycling_loader() -> SignerLoader {
+        let calls = AtomicUsize::new(0);
+        Arc::new(move || {
+            let index = calls.fetch_add(1, Ordering::SeqCst) % MNEMONICS.len();
+            Box::pin(async move { wallet_signer(MNEMONICS[index]) })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_rotate_swaps_key() {
+        init_test_env();
+        let signer = RotatingSigner::new("test_swap", cycling_loader(), Duration::ZERO)
+            .await
+            .unwrap();
+        let first = signer.key_hash().await.unwrap();
+
+        let rotation = signer.rotate().await.unwrap();
+        assert!(rotation.changed());
+        assert_eq!(rotation.previous_key_hash, first);
+        assert_eq!(signer.key_hash().await.unwrap(), rotation.key_hash);
+        assert_eq!(
+            signer.active_key_hashes().await.unwrap(),
+            vec![rotation.key_hash]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_previous_key_active_during_grace_period() {
+        init_test_env();
+        let signer = RotatingSigner::new("test_grace", cycling_loader(), Duration::from_secs(60))
+            .await
+            .unwrap();
+        let rotation = signer.rotate().await.unwrap();
+
+        let active = signer.active_key_hashes().await.unwrap();
+        assert_eq!(active, vec![rotation.key_hash, rotation.previous_key_hash]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_keeps_current_key() {
+        init_test_env();
+        let loaded = Arc::new(AtomicUsize::new(0));
+        let counter = loaded.clone();
+        let loader: SignerLoader = Arc::new(move || {
+            let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+            Box::pin(async move {
+                if !first {
+                    return Err(WError::new("test", "secret unavailable"));
+                }
+                wallet_signer(MNEMONICS[0])
+            })
+        });
+        let signer = RotatingSigner::new("test_failed", loader, Duration::ZERO)
+            .await
+            .unwrap();
+        let before = signer.key_hash().await.unwrap();
+
+        assert!(signer.rotate().await.is_err());
+        assert_eq!(signer.key_hash().await.unwrap(), before);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_tx_is_not_signed() {
+        init_test_env();
+        let signer =
+            RotatingSigner::new("test_malformed", cycling_loader(), Duration::from_secs(60))
+                .await
+                .unwrap();
+        signer.rotate().await.unwrap();
+
+        assert!(signer.sign_tx("not a tx").await.is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use whisky::{WError, Wallet};
+
+use crate::{signer::Signer, utils::wallet::wallet_key_hash};
+
+/// Signs with a whisky wallet held in process memory
+pub struct WalletSigner {
+    wallet: Wallet,
+    key_hash: String,
+}
+
+impl WalletSigner {
+    pub fn new(wallet: Wallet) -> Result<Self, WError> {
+        let key_hash = wallet_key_hash(&wallet)?;
+        Ok(WalletSigner { wallet, key_hash })
+    }
+}
+
+#[async_trait]
+impl Signer for WalletSigner {
+    async fn key_hash(&self) -> Result<String, WError> {
+        Ok(self.key_hash.clone())
+    }
+
+    async fn sign_tx(&self, tx_hex: &str) -> Result<String, WError> {
+        self.wallet
+            .sign_tx(tx_hex)
+            .map_err(WError::from_err("WalletSigner - sign_tx"))
+    }
+}
@@ -1,7 +1,27 @@
-use std::sync::Once;
+use std::{
+    sync::{Arc, Once},
+    time::Duration,
+};
+use whisky::csl;
+
+use crate::{
+    config::{
+        deployment::DeploymentConfig,
+        signing_keys::{APP_OWNER_ROLE, FEE_COLLECTOR_ROLE},
+    },
+    deployment::Deployment,
+    signer::{KeySigner, RotatingSigner, SignerLoader},
+    utils::token_registry::TokenEntry,
+};
 
 static INIT: Once = Once::new();
 
+/// Network the tests build scripts and wallets for
+pub const TEST_NETWORK_ID: u8 = 0;
+
+/// L1 unit of USDM, the one token of `test_deployment`
+pub const TEST_USDM_UNIT: &str = "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d";
+
 /// Initialize test environment variables globally.
 /// This function is safe to call multiple times - it only runs once.
 pub fn init_test_env() {
@@ -48,3 +68,50 @@ pub fn init_test_env() {
         }
     });
 }
+
+/// Hydra token policy of the test environment's `DEX_ORACLE_NFT`
+pub fn test_hydra_token_hash() -> String {
+    init_test_env();
+    let oracle_nft = std::env::var("DEX_ORACLE_NFT").unwrap();
+    let scripts = crate::deployment::DeploymentScripts::new(&oracle_nft, TEST_NETWORK_ID).unwrap();
+    scripts.hydra_token_mint.hash.clone()
+}
+
+/// A signer for `role` holding a fixed key, never rotated
+pub async fn test_signer(role: &str) -> Arc<RotatingSigner> {
+    let loader: SignerLoader = Arc::new(|| {
+        Box::pin(async {
+            let key = csl::PrivateKey::from_normal_bytes(&[7u8; 32]).unwrap();
+            Ok(Arc::new(KeySigner::new(key)) as Arc<dyn crate::signer::Signer>)
+        })
+    });
+    Arc::new(
+        RotatingSigner::new(role, loader, Duration::ZERO)
+            .await
+            .unwrap(),
+    )
+}
+
+/// A deployment listing USDM with 6 decimals, signed by `test_signer`s
+pub async fn test_deployment(id: &str, dex_oracle_nft: &str) -> Deployment {
+    init_test_env();
+    let config = DeploymentConfig {
+        id: id.to_string(),
+        dex_oracle_nft: dex_oracle_nft.to_string(),
+        tokens: vec![TokenEntry {
+            unit: TEST_USDM_UNIT.to_string(),
+            ticker: "USDM".to_string(),
+            decimals: 6,
+            ..TokenEntry::default()
+        }],
+        ..DeploymentConfig::default()
+    };
+    Deployment::new(
+        &config,
+        TEST_NETWORK_ID,
+        test_signer(APP_OWNER_ROLE).await,
+        test_signer(FEE_COLLECTOR_ROLE).await,
+        false,
+    )
+    .unwrap()
+}
@@ -0,0 +1,87 @@
+/// Raw quantity of a decimal amount like `12.5` for a token with `decimals` decimals
+///
+/// Rejects amounts with more fractional digits than the token supports.
+pub fn parse_decimal(amount: &str, decimals: u8) -> Result<u64, String> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(format!("Invalid amount: {}", amount));
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "Amount {} has more than {} decimal places",
+            amount, decimals
+        ));
+    }
+    let digits = format!(
+        "{}{}{}",
+        whole,
+        fraction,
+        "0".repeat(decimals as usize - fraction.len())
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    digits
+        .parse()
+        .map_err(|_| format!("Amount {} is too large", amount))
+}
+
+/// Decimal string of a raw quantity, without trailing zeros (`12500000`, 6 -> `12.5`)
+pub fn format_decimal(quantity: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", quantity, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Split a display amount like `12.5 USDM` into its decimal amount and ticker
+pub fn split_display_amount(amount: &str) -> Option<(&str, &str)> {
+    let (value, ticker) = amount.trim().split_once(char::is_whitespace)?;
+    let ticker = ticker.trim();
+    (!ticker.is_empty()).then_some((value, ticker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("12.5", 6), Ok(12_500_000));
+        assert_eq!(parse_decimal("12", 6), Ok(12_000_000));
+        assert_eq!(parse_decimal(".5", 1), Ok(5));
+        assert_eq!(parse_decimal("0.000", 0), Ok(0));
+        assert_eq!(parse_decimal("1.50", 1), Ok(15));
+        assert!(parse_decimal("1.05", 1).is_err());
+        assert!(parse_decimal("-1", 6).is_err());
+        assert!(parse_decimal("1e6", 6).is_err());
+        assert!(parse_decimal(".", 6).is_err());
+        assert!(parse_decimal("18446744073709551616", 0).is_err());
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(12_500_000, 6), "12.5");
+        assert_eq!(format_decimal(5, 6), "0.000005");
+        assert_eq!(format_decimal(0, 6), "0");
+        assert_eq!(format_decimal(42, 0), "42");
+        assert_eq!(
+            parse_decimal(&format_decimal(123_456_789, 6), 6),
+            Ok(123_456_789)
+        );
+    }
+
+    #[test]
+    fn test_split_display_amount() {
+        assert_eq!(split_display_amount("12.5 USDM"), Some(("12.5", "USDM")));
+        assert_eq!(split_display_amount("12.5"), None);
+    }
+}
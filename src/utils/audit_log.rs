@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use whisky::{calculate_tx_hash, WError};
+
+use crate::config::AppConfig;
+
+/// `prev_hash` of the first record in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecordBody {
+    pub timestamp: String,
+    pub method: String,
+    /// Peer address of the request
+    pub caller: String,
+    /// `x-hibiki-caller` metadata as sent by the client, not verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_caller: Option<String>,
+    pub role: String,
+    pub tx_hash: String,
+    /// `signed` or `rejected`
+    pub decision: String,
+    pub reason: Option<String>,
+    pub prev_hash: String,
+}
+
+impl AuditRecordBody {
+    /// Hex sha256 of the record's JSON serialization
+    pub fn hash(&self) -> String {
+        let json = serde_json::to_vec(self).expect("audit record serializes");
+        hex::encode(Sha256::digest(json))
+    }
+}
+
+/// One JSON line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    #[serde(flatten)]
+    pub body: AuditRecordBody,
+    pub hash: String,
+}
+
+/// Append-only signing audit log, each record chained to the previous one by hash
+pub struct AuditLog {
+    path: PathBuf,
+    state: Mutex<(File, String)>,
+}
+
+impl AuditLog {
+    /// Open (or create) the log at `path`, refusing to continue a tampered chain
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, WError> {
+        let path = path.as_ref().to_path_buf();
+        let last_hash = if path.exists() {
+            verify_file(&path)?.1
+        } else {
+            GENESIS_HASH.to_string()
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(WError::from_err("AuditLog - open"))?;
+        Ok(AuditLog {
+            path,
+            state: Mutex::new((file, last_hash)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(
+        &self,
+        method: &str,
+        caller: &Caller,
+        role: &str,
+        tx_hash: &str,
+        reason: Option<&str>,
+    ) -> Result<AuditRecord, WError> {
+        let mut state = self.state.lock().unwrap();
+        let (file, last_hash) = &mut *state;
+
+        let body = AuditRecordBody {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            method: method.to_string(),
+            caller: caller.address.clone(),
+            claimed_caller: caller.claimed.clone(),
+            role: role.to_string(),
+            tx_hash: tx_hash.to_string(),
+            decision: if reason.is_none() {
+                "signed"
+            } else {
+                "rejected"
+            }
+            .to_string(),
+            reason: reason.map(|reason| reason.to_string()),
+            prev_hash: last_hash.clone(),
+        };
+        let record = AuditRecord {
+            hash: body.hash(),
+            body,
+        };
+
+        let mut line = serde_json::to_string(&record).expect("audit record serializes");
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(WError::from_err("AuditLog - append"))?;
+        *last_hash = record.hash.clone();
+        Ok(record)
+    }
+}
+
+/// Check every record's hash and chain link, returning the record count and last hash
+pub fn verify_file(path: impl AsRef<Path>) -> Result<(usize, String), WError> {
+    let file = File::open(path.as_ref()).map_err(WError::from_err("verify_file - open"))?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut count = 0;
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.map_err(WError::from_err("verify_file - read"))?;
+        let record: AuditRecord = serde_json::from_str(&line).map_err(|e| {
+            WError::new(
+                "verify_file",
+                &format!("Line {}: malformed record: {}", line_no, e),
+            )
+        })?;
+        if record.body.prev_hash != prev_hash {
+            return Err(WError::new(
+                "verify_file",
+                &format!("Line {}: chain broken, prev_hash does not match", line_no),
+            ));
+        }
+        if record.body.hash() != record.hash {
+            return Err(WError::new(
+                "verify_file",
+                &format!("Line {}: record hash mismatch, contents altered", line_no),
+            ));
+        }
+        prev_hash = record.hash;
+        count += 1;
+    }
+
+    Ok((count, prev_hash))
+}
+
+/// Open the signing audit log of the config, before serving
+///
+/// Signing unaudited must be chosen with `signing_audit_log_disabled`; without it an
+/// unset `signing_audit_log_path` is an error.
+pub fn open_signing_audit_log(config: &AppConfig) -> Result<Option<AuditLog>, WError> {
+    match (
+        &config.signing_audit_log_path,
+        config.signing_audit_log_disabled,
+    ) {
+        (Some(path), false) => AuditLog::open(path).map(Some),
+        (None, true) => Ok(None),
+        (Some(_), true) => Err(WError::new(
+            "open_signing_audit_log",
+            "signing_audit_log_path is set but signing_audit_log_disabled is true",
+        )),
+        (None, false) => Err(WError::new(
+            "open_signing_audit_log",
+            "signing_audit_log_path is not set, set signing_audit_log_disabled to sign unaudited",
+        )),
+    }
+}
+
+/// Who sent a signing request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Caller {
+    /// Peer address, as seen by the server
+    pub address: String,
+    /// `x-hibiki-caller` metadata, as sent by the client
+    pub claimed: Option<String>,
+}
+
+/// Who asked for a signature, and with which key, for the audit log
+#[derive(Clone)]
+pub struct SigningContext {
+    pub method: String,
+    pub caller: Caller,
+    pub role: String,
+    audit_log: Option<Arc<AuditLog>>,
+}
+
+impl SigningContext {
+    /// Context auditing to `audit_log`; `None` when auditing is disabled
+    pub fn new(method: &str, caller: Caller, role: &str, audit_log: Option<Arc<AuditLog>>) -> Self {
+        SigningContext {
+            method: method.to_string(),
+            caller,
+            role: role.to_string(),
+            audit_log,
+        }
+    }
+
+    /// Audit the outcome of a signing attempt on `tx_hex`, passing the outcome through
+    ///
+    /// Failing to write the record fails the request, so no signature leaves unaudited.
+    pub fn audited<T>(&self, tx_hex: &str, outcome: Result<T, WError>) -> Result<T, WError> {
+        let Some(log) = &self.audit_log else {
+            return outcome;
+        };
+        let tx_hash = calculate_tx_hash(tx_hex).unwrap_or_default();
+        let reason = outcome.as_ref().err().map(|e| e.to_string());
+        log.append(
+            &self.method,
+            &self.caller,
+            &self.role,
+            &tx_hash,
+            reason.as_deref(),
+        )?;
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(address: &str) -> Caller {
+        Caller {
+            address: address.to_string(),
+            claimed: None,
+        }
+    }
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "hibiki-audit-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_audit_log_chain_verifies_across_reopen() {
+        let path = temp_log_path("reopen");
+        let log = AuditLog::open(&path).unwrap();
+        let first = log
+            .append(
+                "sign_transaction",
+                &peer("127.0.0.1"),
+                "app_owner",
+                "aa",
+                None,
+            )
+            .unwrap();
+        assert_eq!(first.body.prev_hash, GENESIS_HASH);
+        drop(log);
+
+        let log = AuditLog::open(&path).unwrap();
+        let second = log
+            .append(
+                "sign_transaction",
+                &peer("127.0.0.1"),
+                "app_owner",
+                "bb",
+                Some("nope"),
+            )
+            .unwrap();
+        assert_eq!(second.body.prev_hash, first.hash);
+        assert_eq!(second.body.decision, "rejected");
+
+        assert_eq!(verify_file(&path).unwrap(), (2, second.hash));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log_detects_tampering() {
+        let path = temp_log_path("tamper");
+        let log = AuditLog::open(&path).unwrap();
+        log.append("sign_transaction", &peer("a"), "app_owner", "aa", None)
+            .unwrap();
+        log.append("sign_transaction", &peer("a"), "app_owner", "bb", None)
+            .unwrap();
+        drop(log);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replacen("\"aa\"", "\"cc\"", 1)).unwrap();
+        assert!(verify_file(&path).is_err());
+        assert!(AuditLog::open(&path).is_err());
+
+        let lines: Vec<&str> = contents.lines().collect();
+        std::fs::write(&path, format!("{}\n", lines[1])).unwrap();
+        assert!(verify_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log_keeps_claimed_caller_apart() {
+        let path = temp_log_path("claimed");
+        let log = Arc::new(AuditLog::open(&path).unwrap());
+        let caller = Caller {
+            address: "10.0.0.7:51234".to_string(),
+            claimed: Some("settlement-worker".to_string()),
+        };
+        let ctx = SigningContext::new("sign_transaction", caller, "app_owner", Some(log));
+        let outcome: Result<(), WError> = Err(WError::new("test", "rejected"));
+        assert!(ctx.audited("", outcome).is_err());
+
+        let line = std::fs::read_to_string(&path).unwrap();
+        let record: AuditRecord = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(record.body.caller, "10.0.0.7:51234");
+        assert_eq!(
+            record.body.claimed_caller.as_deref(),
+            Some("settlement-worker")
+        );
+        // Records without a claimed caller hash as before the field existed
+        let unclaimed = AuditRecordBody {
+            claimed_caller: None,
+            ..record.body
+        };
+        assert!(!serde_json::to_string(&unclaimed)
+            .unwrap()
+            .contains("claimed_caller"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_signing_audit_log_must_be_configured() {
+        let path = temp_log_path("config");
+        let mut config = AppConfig::default();
+        assert!(open_signing_audit_log(&config).is_err());
+
+        config.signing_audit_log_disabled = true;
+        assert!(open_signing_audit_log(&config).unwrap().is_none());
+
+        config.signing_audit_log_path = Some(path.to_string_lossy().to_string());
+        assert!(open_signing_audit_log(&config).is_err());
+
+        config.signing_audit_log_disabled = false;
+        assert!(open_signing_audit_log(&config).unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
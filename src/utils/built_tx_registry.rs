@@ -0,0 +1,231 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use whisky::WError;
+
+use crate::config::AppConfig;
+
+/// gRPC metadata key carrying the build token between a build RPC and `sign_transaction`
+pub const BUILD_TOKEN_METADATA_KEY: &str = "x-hibiki-build-token";
+
+/// Which transactions the app owner key may sign, from `sign_binding_mode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignBindingMode {
+    /// Sign any transaction (default)
+    #[default]
+    Off,
+    /// Sign only transactions built by this hibiki process
+    Registry,
+    /// Sign transactions built by this process, or carrying a valid build token
+    /// (needed when builds and signatures hit different replicas)
+    #[serde(rename = "token")]
+    RegistryOrToken,
+}
+
+impl FromStr for SignBindingMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "off" => Ok(SignBindingMode::Off),
+            "registry" => Ok(SignBindingMode::Registry),
+            "token" => Ok(SignBindingMode::RegistryOrToken),
+            _ => Err(format!(
+                "Unknown sign binding mode {} (expected off, registry or token)",
+                mode
+            )),
+        }
+    }
+}
+
+/// Hashes of transactions hibiki built recently, used to bind signing to building
+pub struct BuiltTxRegistry {
+    mode: SignBindingMode,
+    ttl: Duration,
+    hmac_key: Option<Vec<u8>>,
+    entries: Mutex<HashMap<String, Instant>>,
+}
+
+impl BuiltTxRegistry {
+    pub fn new(mode: SignBindingMode, ttl: Duration, hmac_key: Option<Vec<u8>>) -> Self {
+        BuiltTxRegistry {
+            mode,
+            ttl,
+            hmac_key,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registry for the configured mode and token key, checked by [`AppConfig::validate`]
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(
+            config.sign_binding_mode,
+            Duration::from_secs(config.sign_binding_ttl_secs),
+            config
+                .sign_binding_hmac_key
+                .as_ref()
+                .map(|key| key.clone().into_bytes()),
+        )
+    }
+
+    /// Record a freshly built transaction, returning a build token when tokens are enabled
+    pub fn record(&self, tx_hash: &str) -> Option<String> {
+        if self.mode == SignBindingMode::Off {
+            return None;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, built_at| now.duration_since(*built_at) < self.ttl);
+        entries.insert(tx_hash.to_string(), now);
+
+        match (self.mode, &self.hmac_key) {
+            (SignBindingMode::RegistryOrToken, Some(key)) => {
+                let expires_at = unix_now() + self.ttl.as_secs();
+                Some(issue_token(key, tx_hash, expires_at))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check that `tx_hash` may be signed under the configured mode
+    pub fn check(&self, tx_hash: &str, build_token: Option<&str>) -> Result<(), WError> {
+        if self.mode == SignBindingMode::Off {
+            return Ok(());
+        }
+
+        let recorded = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(tx_hash)
+            .is_some_and(|built_at| built_at.elapsed() < self.ttl);
+        if recorded {
+            return Ok(());
+        }
+
+        if let (SignBindingMode::RegistryOrToken, Some(key), Some(token)) =
+            (self.mode, &self.hmac_key, build_token)
+        {
+            return verify_token(key, tx_hash, token, unix_now());
+        }
+
+        Err(WError::new(
+            "BuiltTxRegistry - check",
+            &format!("Transaction {} was not built by hibiki", tx_hash),
+        ))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn token_mac(key: &[u8], tx_hash: &str, expires_at: u64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}.{}", tx_hash, expires_at).as_bytes());
+    mac
+}
+
+/// Token format: `<expires_at unix secs>.<hex hmac-sha256(key, "<tx_hash>.<expires_at>")>`
+fn issue_token(key: &[u8], tx_hash: &str, expires_at: u64) -> String {
+    let signature = token_mac(key, tx_hash, expires_at).finalize().into_bytes();
+    format!("{}.{}", expires_at, hex::encode(signature))
+}
+
+fn verify_token(key: &[u8], tx_hash: &str, token: &str, now: u64) -> Result<(), WError> {
+    let invalid = || WError::new("BuiltTxRegistry - verify_token", "Invalid build token");
+
+    let (expires_at, signature) = token.split_once('.').ok_or_else(invalid)?;
+    let expires_at: u64 = expires_at.parse().map_err(|_| invalid())?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+
+    token_mac(key, tx_hash, expires_at)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+    if expires_at < now {
+        return Err(WError::new(
+            "BuiltTxRegistry - verify_token",
+            "Build token expired",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TX_HASH: &str = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030c";
+    const OTHER_TX_HASH: &str = "c9ce910cccbc274cc3d17e1447570eeb86f7f04a3a04c1f0955f8a71d08cfd5e";
+
+    #[test]
+    fn test_binding_off_signs_anything() {
+        let registry = BuiltTxRegistry::new(SignBindingMode::Off, Duration::from_secs(60), None);
+        assert!(registry.record(TX_HASH).is_none());
+        assert!(registry.check(OTHER_TX_HASH, None).is_ok());
+    }
+
+    #[test]
+    fn test_registry_only_signs_recorded_hashes() {
+        let registry =
+            BuiltTxRegistry::new(SignBindingMode::Registry, Duration::from_secs(60), None);
+        registry.record(TX_HASH);
+        assert!(registry.check(TX_HASH, None).is_ok());
+        assert!(registry.check(OTHER_TX_HASH, None).is_err());
+    }
+
+    #[test]
+    fn test_registry_entries_expire() {
+        let registry = BuiltTxRegistry::new(SignBindingMode::Registry, Duration::ZERO, None);
+        registry.record(TX_HASH);
+        assert!(registry.check(TX_HASH, None).is_err());
+    }
+
+    #[test]
+    fn test_build_token_from_other_replica() {
+        let key = b"shared-secret".to_vec();
+        let builder = BuiltTxRegistry::new(
+            SignBindingMode::RegistryOrToken,
+            Duration::from_secs(60),
+            Some(key.clone()),
+        );
+        let signer = BuiltTxRegistry::new(
+            SignBindingMode::RegistryOrToken,
+            Duration::from_secs(60),
+            Some(key),
+        );
+
+        let token = builder.record(TX_HASH).unwrap();
+        assert!(signer.check(TX_HASH, Some(&token)).is_ok());
+        assert!(signer.check(OTHER_TX_HASH, Some(&token)).is_err());
+        assert!(signer.check(TX_HASH, None).is_err());
+    }
+
+    #[test]
+    fn test_sign_binding_mode_from_str() {
+        assert_eq!("registry".parse(), Ok(SignBindingMode::Registry));
+        assert_eq!("token".parse(), Ok(SignBindingMode::RegistryOrToken));
+        assert!("regsitry".parse::<SignBindingMode>().is_err());
+        assert!("".parse::<SignBindingMode>().is_err());
+    }
+
+    #[test]
+    fn test_build_token_rejects_wrong_key_and_expiry() {
+        let token = issue_token(b"key", TX_HASH, 1_000);
+        assert!(verify_token(b"key", TX_HASH, &token, 999).is_ok());
+        assert!(verify_token(b"key", TX_HASH, &token, 1_001).is_err());
+        assert!(verify_token(b"other key", TX_HASH, &token, 999).is_err());
+        assert!(verify_token(b"key", TX_HASH, "not-a-token", 999).is_err());
+    }
+}
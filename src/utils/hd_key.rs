@@ -0,0 +1,205 @@
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Sha256, Sha512};
+use whisky::{csl, WError};
+
+use crate::config::signing_keys::{Derivation, MasterKeyScheme, HARDENED};
+
+const PURPOSE: u32 = 1852;
+const COIN_TYPE: u32 = 1815;
+
+/// BIP32-Ed25519 root key of a mnemonic, with an optional BIP39 passphrase
+pub fn root_key_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+    scheme: MasterKeyScheme,
+) -> Result<csl::Bip32PrivateKey, WError> {
+    let parsed = Mnemonic::parse_in_normalized(Language::English, mnemonic)
+        .map_err(WError::from_err("root_key_from_mnemonic - parse"))?;
+    match scheme {
+        MasterKeyScheme::Icarus => Ok(csl::Bip32PrivateKey::from_bip39_entropy(
+            &parsed.to_entropy(),
+            passphrase.as_bytes(),
+        )),
+        MasterKeyScheme::Ledger => {
+            csl::Bip32PrivateKey::from_bytes(&ledger_master_key(&parsed.to_string(), passphrase))
+                .map_err(WError::from_err("root_key_from_mnemonic - from_bytes"))
+        }
+    }
+}
+
+/// Ledger master key generation from CIP-3: `kL || kR || chain code`
+fn ledger_master_key(mnemonic: &str, passphrase: &str) -> [u8; 96] {
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2_hmac::<Sha512>(
+        mnemonic.as_bytes(),
+        format!("mnemonic{}", passphrase).as_bytes(),
+        2048,
+        &mut seed,
+    );
+
+    // Re-hash until the third highest bit of kL is clear
+    let mut digest = hmac_sha512(b"ed25519 seed", &seed);
+    while digest[31] & 0b0010_0000 != 0 {
+        digest = hmac_sha512(b"ed25519 seed", &digest);
+    }
+    digest[0] &= 0b1111_1000;
+    digest[31] &= 0b0111_1111;
+    digest[31] |= 0b0100_0000;
+
+    let mut chain_code = Hmac::<Sha256>::new_from_slice(b"ed25519 seed").expect("any key size");
+    chain_code.update(&[1]);
+    chain_code.update(&seed);
+
+    let mut key = [0u8; 96];
+    key[..64].copy_from_slice(&digest);
+    key[64..].copy_from_slice(&chain_code.finalize().into_bytes());
+    key
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("any key size");
+    mac.update(data);
+    let mut digest = [0u8; 64];
+    digest.copy_from_slice(&mac.finalize().into_bytes());
+    digest
+}
+
+/// Root key from a bech32 extended private key (`xprv1...`, `root_xsk1...`) or its 96 byte hex
+pub fn root_key_from_str(root_key: &str) -> Result<csl::Bip32PrivateKey, WError> {
+    let root_key = root_key.trim();
+    match hex::decode(root_key) {
+        Ok(bytes) => csl::Bip32PrivateKey::from_bytes(&bytes)
+            .map_err(WError::from_err("root_key_from_str - from_bytes")),
+        Err(_) => csl::Bip32PrivateKey::from_bech32(root_key)
+            .map_err(WError::from_err("root_key_from_str - from_bech32")),
+    }
+}
+
+/// Signing key at the CIP-1852 path of `derivation`
+pub fn derive_signing_key(root: &csl::Bip32PrivateKey, derivation: Derivation) -> csl::PrivateKey {
+    root.derive(HARDENED | PURPOSE)
+        .derive(HARDENED | COIN_TYPE)
+        .derive(HARDENED | derivation.account_index)
+        .derive(derivation.role)
+        .derive(derivation.key_index)
+        .to_raw_key()
+}
+
+/// A cardano-cli `.skey` text envelope
+#[derive(Debug, Deserialize)]
+pub struct CliSigningKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    #[serde(rename = "cborHex")]
+    pub cbor_hex: String,
+}
+
+/// Signing key of a cardano-cli `.skey` file, normal or BIP32 extended
+pub fn signing_key_from_cli_skey(skey_json: &str) -> Result<csl::PrivateKey, WError> {
+    let skey: CliSigningKey = serde_json::from_str(skey_json)
+        .map_err(WError::from_err("signing_key_from_cli_skey - parse"))?;
+    let cbor = hex::decode(&skey.cbor_hex)
+        .map_err(WError::from_err("signing_key_from_cli_skey - cborHex"))?;
+
+    // CBOR byte string header, then the key bytes
+    match (cbor.first(), cbor.get(1), cbor.len()) {
+        (Some(0x58), Some(0x20), 34) => csl::PrivateKey::from_normal_bytes(&cbor[2..]).map_err(
+            WError::from_err("signing_key_from_cli_skey - from_normal_bytes"),
+        ),
+        // Extended keys are kL || kR || public key || chain code
+        (Some(0x58), Some(0x80), 130) => csl::PrivateKey::from_extended_bytes(&cbor[2..66])
+            .map_err(WError::from_err(
+                "signing_key_from_cli_skey - from_extended_bytes",
+            )),
+        _ => Err(WError::new(
+            "signing_key_from_cli_skey",
+            &format!("Unsupported signing key type {}", skey.key_type),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::init_test_env,
+        utils::wallet::{wallet_from_mnemonic, wallet_key_hash},
+    };
+    use whisky::NetworkId;
+
+    const MNEMONIC: &str = "summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer summer";
+
+    fn key_hash(key: &csl::PrivateKey) -> String {
+        key.to_public().hash().to_hex()
+    }
+
+    #[test]
+    fn test_icarus_derivation_matches_wallet() {
+        init_test_env();
+        let root = root_key_from_mnemonic(MNEMONIC, "", MasterKeyScheme::Icarus).unwrap();
+        let wallet = wallet_from_mnemonic(MNEMONIC, NetworkId::Preprod).unwrap();
+        assert_eq!(
+            key_hash(&derive_signing_key(&root, Derivation::default())),
+            wallet_key_hash(&wallet).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_passphrase_and_scheme_change_key() {
+        let derivation = Derivation::default();
+        let plain = root_key_from_mnemonic(MNEMONIC, "", MasterKeyScheme::Icarus).unwrap();
+        let with_passphrase =
+            root_key_from_mnemonic(MNEMONIC, "hunter2", MasterKeyScheme::Icarus).unwrap();
+        let ledger = root_key_from_mnemonic(MNEMONIC, "", MasterKeyScheme::Ledger).unwrap();
+
+        let hashes = [plain, with_passphrase, ledger]
+            .iter()
+            .map(|root| key_hash(&derive_signing_key(root, derivation)))
+            .collect::<Vec<_>>();
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+    }
+
+    #[test]
+    fn test_ledger_master_key_cip3_vector() {
+        // Test vector 3 of CIP-3 Ledger, whose first digest needs re-hashing
+        let mnemonic = "correct cherry mammal bubble want mandate polar hazard crater better craft exotic choice fun tourist census gap lottery neglect address glow carry old business";
+        let root = root_key_from_mnemonic(mnemonic, "", MasterKeyScheme::Ledger).unwrap();
+        assert_eq!(
+            hex::encode(root.as_bytes()),
+            "587c6774357ecbf840d4db6404ff7af016dace0400769751ad2abfc77b9a3844cc71702520ef1a4d1b68b91187787a9b8faab0a9bb6b160de541b6ee62469901fc0beda0975fe4763beabd83b7051a5fd5cbce5b88e82c4bbaca265014e524bd"
+        );
+    }
+
+    #[test]
+    fn test_root_key_bech32_round_trip() {
+        let root = root_key_from_mnemonic(MNEMONIC, "", MasterKeyScheme::Icarus).unwrap();
+        let derivation = Derivation::default();
+        for encoded in [root.to_bech32(), hex::encode(root.as_bytes())] {
+            let parsed = root_key_from_str(&encoded).unwrap();
+            assert_eq!(
+                key_hash(&derive_signing_key(&parsed, derivation)),
+                key_hash(&derive_signing_key(&root, derivation))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cli_skey() {
+        let skey = r#"{
+            "type": "PaymentSigningKeyShelley_ed25519",
+            "description": "Payment Signing Key",
+            "cborHex": "5820a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90"
+        }"#;
+        let key = signing_key_from_cli_skey(skey).unwrap();
+        assert_eq!(
+            hex::encode(key.as_bytes()),
+            "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90"
+        );
+
+        let truncated = r#"{"type": "PaymentSigningKeyShelley_ed25519", "cborHex": "5820a1b2"}"#;
+        assert!(signing_key_from_cli_skey(truncated).is_err());
+    }
+}
@@ -1,10 +1,10 @@
 use whisky::{
     blockfrost::utils::{normalize_plutus_script, to_script_ref, ScriptType},
     csl::{self, PlutusScript, ScriptRef},
-    OfflineTxEvaluator, TxBuilder, TxBuilderParam, WError,
+    Network, OfflineTxEvaluator, TxBuilder, TxBuilderParam, WError,
 };
 
-use crate::config::hydra::get_hydra_pp;
+use crate::config::hydra::{get_hydra_cost_model, get_hydra_pp};
 
 pub fn get_script_ref_hex(cbor: &str) -> Result<String, WError> {
     let normalized =
@@ -24,5 +24,7 @@ pub fn get_hydra_tx_builder() -> TxBuilder {
         params: Some(get_hydra_pp()),
     });
     tx_builder.serializer.tx_evaluation_multiplier_percentage = 150;
+    // Evaluate scripts with the head's cost models rather than mainnet's
+    tx_builder.network(Network::Custom(get_hydra_cost_model()));
     tx_builder
 }
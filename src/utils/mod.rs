@@ -1,5 +1,10 @@
-pub mod gcp_secret_manager;
+pub mod amount;
+pub mod audit_log;
+pub mod built_tx_registry;
+pub mod hd_key;
 pub mod hydra;
 pub mod proto;
 pub mod token;
+pub mod token_registry;
 pub mod wallet;
+pub mod witness;
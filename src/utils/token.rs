@@ -2,11 +2,11 @@ use blake2::{
     digest::{Update, VariableOutput},
     Blake2bVar,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use whisky::Asset;
 
-pub fn hydra_to_l1_token_map(units: &[&str]) -> HashMap<String, String> {
-    let hydra_token_hash = crate::constant::hydra_token_hash();
+/// Hydra unit to L1 unit of each of `units`, for the Hydra token policy `hydra_token_hash`
+pub fn hydra_to_l1_token_map(units: &[&str], hydra_token_hash: &str) -> HashMap<String, String> {
     let mut map = HashMap::with_capacity(units.len());
 
     for unit in units {
@@ -45,9 +45,34 @@ pub fn to_l1_assets(
         .collect()
 }
 
-pub fn to_hydra_token(assets: &[Asset]) -> Vec<Asset> {
-    let hydra_token_hash = crate::constant::hydra_token_hash();
+/// What is left of `balance` once `amount` is taken out, both in Hydra units, leaving out
+/// emptied units and plain lovelace; fails when `balance` holds less than `amount` of a unit
+pub fn remaining_balance(balance: &[Asset], amount: &[Asset]) -> Result<Vec<Asset>, String> {
+    let mut remaining: BTreeMap<String, i128> = BTreeMap::new();
+    for asset in balance {
+        *remaining.entry(asset.unit()).or_default() += quantity(asset)?;
+    }
+    for asset in amount {
+        *remaining.entry(asset.unit()).or_default() -= quantity(asset)?;
+    }
+    if let Some((unit, quantity)) = remaining.iter().find(|(_, quantity)| **quantity < 0) {
+        return Err(format!("Balance is short of {} {}", -quantity, unit));
+    }
+    Ok(remaining
+        .into_iter()
+        .filter(|(unit, quantity)| *quantity > 0 && unit != "lovelace")
+        .map(|(unit, quantity)| Asset::new_from_str(&unit, &quantity.to_string()))
+        .collect())
+}
 
+fn quantity(asset: &Asset) -> Result<i128, String> {
+    asset
+        .quantity()
+        .parse()
+        .map_err(|_| format!("Invalid quantity {} of {}", asset.quantity(), asset.unit()))
+}
+
+pub fn to_hydra_token(assets: &[Asset], hydra_token_hash: &str) -> Vec<Asset> {
     assets
         .iter()
         .map(|asset| {
@@ -83,13 +108,13 @@ pub fn blake2b_256_hex(hex_input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::init_test_env;
+    use crate::test_utils::test_hydra_token_hash;
 
     #[test]
     fn test_to_hydra_token_lovelace() {
-        init_test_env();
+        let hydra_token_hash = test_hydra_token_hash();
         let assets = vec![Asset::new_from_str("lovelace", "1000000")];
-        let hydra_assets = to_hydra_token(&assets);
+        let hydra_assets = to_hydra_token(&assets, &hydra_token_hash);
 
         assert_eq!(hydra_assets.len(), 1);
         assert!(hydra_assets[0].unit().len() == 56);
@@ -98,18 +123,38 @@ mod tests {
 
     #[test]
     fn test_to_hydra_token_custom_asset() {
-        init_test_env();
+        let hydra_token_hash = test_hydra_token_hash();
         let assets = vec![Asset::new_from_str(
             "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d",
             "100",
         )];
-        let hydra_assets = to_hydra_token(&assets);
+        let hydra_assets = to_hydra_token(&assets, &hydra_token_hash);
 
         assert_eq!(hydra_assets.len(), 1);
         assert!(hydra_assets[0].unit().len() > 56);
         assert_eq!(hydra_assets[0].quantity(), "100");
     }
 
+    #[test]
+    fn test_remaining_balance() {
+        let usdm = format!("{}5553444d", test_hydra_token_hash());
+        let night = format!("{}4e49474854", test_hydra_token_hash());
+        let balance = vec![
+            Asset::new_from_str("lovelace", "0"),
+            Asset::new_from_str(&usdm, "25"),
+            Asset::new_from_str(&usdm, "5"),
+            Asset::new_from_str(&night, "7"),
+        ];
+
+        let remaining = remaining_balance(&balance, &[Asset::new_from_str(&night, "7")]).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].unit(), usdm);
+        assert_eq!(remaining[0].quantity(), "30");
+
+        let error = remaining_balance(&balance, &[Asset::new_from_str(&usdm, "31")]).unwrap_err();
+        assert!(error.contains("short of 1"));
+    }
+
     #[test]
     fn test_blake2b_256_hex() {
         let input = "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d";
@@ -124,7 +169,7 @@ mod tests {
 
     #[test]
     fn test_to_hydra_token_multiple_assets() {
-        init_test_env();
+        let hydra_token_hash = test_hydra_token_hash();
         let assets = vec![
             Asset::new_from_str("lovelace", "1000000"),
             Asset::new_from_str(
@@ -132,7 +177,7 @@ mod tests {
                 "100",
             ),
         ];
-        let hydra_assets = to_hydra_token(&assets);
+        let hydra_assets = to_hydra_token(&assets, &hydra_token_hash);
 
         assert_eq!(hydra_assets.len(), 2);
         assert_eq!(hydra_assets[0].unit().len(), 56);
@@ -141,32 +186,31 @@ mod tests {
 
     #[test]
     fn test_hydra_to_l1_token_map() {
-        init_test_env();
+        let hydra_token_hash = test_hydra_token_hash();
         let units = vec![
             "",
             "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d",
         ];
-        let map = hydra_to_l1_token_map(&units);
+        let map = hydra_to_l1_token_map(&units, &hydra_token_hash);
 
         assert_eq!(map.len(), 2);
         // Check that lovelace mapping exists
-        let hydra_token_hash = crate::constant::hydra_token_hash();
-        assert_eq!(map.get(hydra_token_hash), Some(&"lovelace".to_string()));
+        assert_eq!(map.get(&hydra_token_hash), Some(&"lovelace".to_string()));
     }
 
     #[test]
     fn test_to_l1_assets() {
-        init_test_env();
+        let hydra_token_hash = test_hydra_token_hash();
         let usdm_unit = "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d";
         let units = vec!["", usdm_unit];
-        let map = hydra_to_l1_token_map(&units);
+        let map = hydra_to_l1_token_map(&units, &hydra_token_hash);
 
         // Convert L1 assets to hydra assets
         let l1_assets = vec![
             Asset::new_from_str("lovelace", "1000000"),
             Asset::new_from_str(usdm_unit, "500"),
         ];
-        let hydra_assets = to_hydra_token(&l1_assets);
+        let hydra_assets = to_hydra_token(&l1_assets, &hydra_token_hash);
 
         // Convert back to L1 assets (lovelace is now filtered out)
         let result = to_l1_assets(&hydra_assets, &map);
@@ -181,9 +225,8 @@ mod tests {
 
     #[test]
     fn test_to_l1_assets_unknown_unit() {
-        init_test_env();
-
-        let map = hydra_to_l1_token_map(&[""]); // Only lovelace registered
+        let hydra_token_hash = test_hydra_token_hash();
+        let map = hydra_to_l1_token_map(&[""], &hydra_token_hash); // Only lovelace registered
         let unknown_hydra_assets = vec![Asset::new_from_str("unknown_hash_12345", "100")];
 
         let result = to_l1_assets(&unknown_hydra_assets, &map);
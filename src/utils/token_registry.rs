@@ -0,0 +1,473 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use whisky::{Asset, WError};
+
+use crate::utils::{
+    amount::{format_decimal, parse_decimal, split_display_amount},
+    token::{blake2b_256_hex, hydra_to_l1_token_map},
+};
+
+/// A token listed on the DEX, identified by its L1 unit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenEntry {
+    /// `policy id + asset name` hex
+    pub unit: String,
+    /// Short symbol used in display amounts like `12.5 USDM`; optional
+    #[serde(default)]
+    pub ticker: String,
+    /// Decimal places of one whole token
+    #[serde(default)]
+    pub decimals: u8,
+    #[serde(default)]
+    pub display_name: String,
+    /// Smallest raw quantity a transfer may move
+    #[serde(default)]
+    pub min_transfer_amount: u64,
+    /// Removed by `RemoveToken`: no longer transferable, but balances still held in the
+    /// head keep converting to L1
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delisted: bool,
+}
+
+impl TokenEntry {
+    /// Metadata of lovelace, which is always registered
+    pub fn lovelace() -> Self {
+        TokenEntry {
+            unit: "lovelace".to_string(),
+            ticker: "ADA".to_string(),
+            decimals: 6,
+            display_name: "Cardano".to_string(),
+            min_transfer_amount: 0,
+            delisted: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let bytes =
+            hex::decode(&self.unit).map_err(|_| format!("Token unit {} is not hex", self.unit))?;
+        if !(28..=60).contains(&bytes.len()) {
+            return Err(format!(
+                "Token unit {} must be a 28 byte policy id and an asset name of up to 32 bytes",
+                self.unit
+            ));
+        }
+        if !self.ticker.is_empty()
+            && (self.ticker.len() > 16 || !self.ticker.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            return Err(format!(
+                "Ticker {} of token {} must be up to 16 letters or digits",
+                self.ticker, self.unit
+            ));
+        }
+        // u64::MAX has 20 digits
+        if self.decimals > 19 {
+            return Err(format!(
+                "Token {} has {} decimals, at most 19 are supported",
+                self.unit, self.decimals
+            ));
+        }
+        Ok(())
+    }
+
+    /// Hydra unit of the token: hydra token policy + blake2b-256 of the L1 unit
+    pub fn hydra_unit(&self, hydra_token_hash: &str) -> String {
+        format!("{}{}", hydra_token_hash, blake2b_256_hex(&self.unit))
+    }
+
+    /// `quantity` as a display amount like `12.5 USDM`, falling back to the unit without a ticker
+    pub fn display_amount(&self, quantity: u64) -> String {
+        let symbol = if self.ticker.is_empty() {
+            &self.unit
+        } else {
+            &self.ticker
+        };
+        format!("{} {}", format_decimal(quantity, self.decimals), symbol)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegistryFile {
+    tokens: Vec<TokenEntry>,
+}
+
+struct RegistryState {
+    tokens: Vec<TokenEntry>,
+    hydra_to_l1: Arc<HashMap<String, String>>,
+}
+
+/// Tokens hibiki converts between L1 and Hydra units, editable at runtime
+///
+/// Seeded from the config's `tokens`, unless a registry file at `path` exists, which then
+/// holds the current list including runtime additions and removals. Lovelace is always
+/// registered. Removed tokens stay listed as delisted, keeping their Hydra unit mapping.
+pub struct TokenRegistry {
+    path: Option<PathBuf>,
+    /// Policy of the deployment's Hydra tokens, the prefix of every Hydra unit
+    hydra_token_hash: String,
+    state: RwLock<RegistryState>,
+}
+
+impl TokenRegistry {
+    pub fn new(
+        seed: Vec<TokenEntry>,
+        path: Option<PathBuf>,
+        hydra_token_hash: String,
+    ) -> Result<Self, WError> {
+        let tokens = match &path {
+            Some(path) if path.exists() => read_registry_file(path)?,
+            _ => seed,
+        };
+        for (index, token) in tokens.iter().enumerate() {
+            check_new_token(&tokens[..index], token)
+                .map_err(WError::from_err("TokenRegistry - validate"))?;
+        }
+
+        Ok(TokenRegistry {
+            path,
+            state: RwLock::new(RegistryState {
+                hydra_to_l1: Arc::new(build_hydra_to_l1(&tokens, &hydra_token_hash)),
+                tokens,
+            }),
+            hydra_token_hash,
+        })
+    }
+
+    pub fn hydra_token_hash(&self) -> &str {
+        &self.hydra_token_hash
+    }
+
+    pub fn list(&self) -> Vec<TokenEntry> {
+        self.state.read().unwrap().tokens.clone()
+    }
+
+    /// Hydra unit to L1 unit of every registered token, as built by `hydra_to_l1_token_map`
+    pub fn hydra_to_l1_map(&self) -> Arc<HashMap<String, String>> {
+        self.state.read().unwrap().hydra_to_l1.clone()
+    }
+
+    /// Registered token (or lovelace) by unit or ticker
+    pub fn find(&self, unit_or_ticker: &str) -> Option<TokenEntry> {
+        let lovelace = TokenEntry::lovelace();
+        if unit_or_ticker.is_empty()
+            || unit_or_ticker == lovelace.unit
+            || unit_or_ticker.eq_ignore_ascii_case(&lovelace.ticker)
+        {
+            return Some(lovelace);
+        }
+        self.state
+            .read()
+            .unwrap()
+            .tokens
+            .iter()
+            .find(|token| {
+                token.unit == unit_or_ticker
+                    || (!token.ticker.is_empty()
+                        && token.ticker.eq_ignore_ascii_case(unit_or_ticker))
+            })
+            .cloned()
+    }
+
+    /// Token and raw quantity of a raw or display amount
+    ///
+    /// A quantity like `12.5 USDM` is read as a display amount, in which case `unit` may be
+    /// empty or name the same token. Any other quantity must be a raw integer quantity of
+    /// `unit`, which may also be given as a ticker.
+    pub fn resolve_amount(&self, unit: &str, quantity: &str) -> Result<(TokenEntry, u64), WError> {
+        match split_display_amount(quantity) {
+            Some((amount, ticker)) => {
+                let token = self.find_or_err(ticker)?;
+                if !unit.is_empty() && self.find(unit).as_ref() != Some(&token) {
+                    return Err(WError::new(
+                        "TokenRegistry - resolve_amount",
+                        &format!("Amount {} does not match unit {}", quantity, unit),
+                    ));
+                }
+                let raw = parse_decimal(amount, token.decimals)
+                    .map_err(WError::from_err("TokenRegistry - resolve_amount"))?;
+                Ok((token, raw))
+            }
+            None => {
+                let token = self.find_or_err(unit)?;
+                let raw = quantity.parse::<u64>().map_err(|_| {
+                    WError::new(
+                        "TokenRegistry - resolve_amount",
+                        &format!(
+                            "Quantity {} of {} is not a raw integer quantity",
+                            quantity, unit
+                        ),
+                    )
+                })?;
+                Ok((token, raw))
+            }
+        }
+    }
+
+    /// Resolve the amounts of a transfer to raw quantities of registered tokens, each at
+    /// least the token's minimum transfer amount
+    pub fn validate_transfer(&self, assets: &[Asset]) -> Result<Vec<Asset>, WError> {
+        let mut resolved = Vec::with_capacity(assets.len());
+        for asset in assets {
+            let (token, quantity) = self.resolve_amount(&asset.unit(), &asset.quantity())?;
+            if token.delisted {
+                return Err(WError::new(
+                    "TokenRegistry - validate_transfer",
+                    &format!("Token {} is delisted", token.unit),
+                ));
+            }
+            if quantity == 0 || quantity < token.min_transfer_amount {
+                return Err(WError::new(
+                    "TokenRegistry - validate_transfer",
+                    &format!(
+                        "Transfer of {} is below the minimum of {}",
+                        token.display_amount(quantity),
+                        token.display_amount(token.min_transfer_amount.max(1))
+                    ),
+                ));
+            }
+            resolved.push(Asset::new_from_str(&token.unit, &quantity.to_string()));
+        }
+        Ok(resolved)
+    }
+
+    fn find_or_err(&self, unit_or_ticker: &str) -> Result<TokenEntry, WError> {
+        self.find(unit_or_ticker).ok_or_else(|| {
+            WError::new(
+                "TokenRegistry",
+                &format!("Token {} is not registered", unit_or_ticker),
+            )
+        })
+    }
+
+    /// Register `token`, relisting it with the new metadata when it was delisted
+    pub fn add(&self, token: TokenEntry) -> Result<(), WError> {
+        let mut state = self.state.write().unwrap();
+        let mut tokens = state.tokens.clone();
+        tokens.retain(|other| !(other.delisted && other.unit == token.unit));
+        check_new_token(&tokens, &token).map_err(WError::from_err("TokenRegistry - add"))?;
+        tokens.push(token);
+        self.replace(&mut state, tokens)
+    }
+
+    /// Delist a token; its Hydra unit keeps mapping to L1 so balances still held convert
+    pub fn remove(&self, unit: &str) -> Result<TokenEntry, WError> {
+        let mut state = self.state.write().unwrap();
+        let Some(index) = state
+            .tokens
+            .iter()
+            .position(|token| token.unit == unit && !token.delisted)
+        else {
+            return Err(WError::new(
+                "TokenRegistry - remove",
+                &format!("Token {} is not registered", unit),
+            ));
+        };
+        let mut tokens = state.tokens.clone();
+        tokens[index].delisted = true;
+        let removed = tokens[index].clone();
+        self.replace(&mut state, tokens)?;
+        Ok(removed)
+    }
+
+    /// Persist `tokens`, then swap them in, so a failed write leaves the registry unchanged
+    fn replace(&self, state: &mut RegistryState, tokens: Vec<TokenEntry>) -> Result<(), WError> {
+        if let Some(path) = &self.path {
+            write_registry_file(path, &tokens)?;
+        }
+        state.hydra_to_l1 = Arc::new(build_hydra_to_l1(&tokens, &self.hydra_token_hash));
+        state.tokens = tokens;
+        Ok(())
+    }
+}
+
+/// `token` is valid and its unit and ticker are not yet taken by `registered` or lovelace
+pub(crate) fn check_new_token(registered: &[TokenEntry], token: &TokenEntry) -> Result<(), String> {
+    token.validate()?;
+    if registered.iter().any(|other| other.unit == token.unit) {
+        return Err(format!("Token {} is already registered", token.unit));
+    }
+    let ticker_taken = |other: &TokenEntry| {
+        !token.ticker.is_empty() && other.ticker.eq_ignore_ascii_case(&token.ticker)
+    };
+    if ticker_taken(&TokenEntry::lovelace()) || registered.iter().any(ticker_taken) {
+        return Err(format!("Ticker {} is already registered", token.ticker));
+    }
+    Ok(())
+}
+
+fn build_hydra_to_l1(tokens: &[TokenEntry], hydra_token_hash: &str) -> HashMap<String, String> {
+    let mut units = vec!["lovelace"];
+    units.extend(tokens.iter().map(|token| token.unit.as_str()));
+    hydra_to_l1_token_map(&units, hydra_token_hash)
+}
+
+fn read_registry_file(path: &Path) -> Result<Vec<TokenEntry>, WError> {
+    let json =
+        fs::read_to_string(path).map_err(WError::from_err("TokenRegistry - read registry"))?;
+    let file: RegistryFile =
+        serde_json::from_str(&json).map_err(WError::from_err("TokenRegistry - parse registry"))?;
+    Ok(file.tokens)
+}
+
+/// Write via a temporary file and rename, so a crash never leaves a truncated registry
+fn write_registry_file(path: &Path, tokens: &[TokenEntry]) -> Result<(), WError> {
+    let json = serde_json::to_string_pretty(&RegistryFile {
+        tokens: tokens.to_vec(),
+    })
+    .map_err(WError::from_err("TokenRegistry - serialize"))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, json).map_err(WError::from_err("TokenRegistry - write"))?;
+    fs::rename(&tmp_path, path).map_err(WError::from_err("TokenRegistry - rename"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::token::to_l1_assets;
+
+    const HYDRA_TOKEN_HASH: &str = "0b5d1c6a3fc2b7ab93aec3fc6e2b58d3d1c9b3f0c4e4e3c1a6d6c7e3";
+    const USDM: &str = "c69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c9135553444d";
+    const SNEK: &str = "378f9732c755ed6f4fc8d406f1461d0cca95d7d2e69416784684df39534e454b";
+
+    fn token(unit: &str) -> TokenEntry {
+        TokenEntry {
+            unit: unit.to_string(),
+            ..TokenEntry::default()
+        }
+    }
+
+    fn usdm() -> TokenEntry {
+        TokenEntry {
+            ticker: "USDM".to_string(),
+            decimals: 6,
+            display_name: "Mehen USD".to_string(),
+            min_transfer_amount: 1_000_000,
+            ..token(USDM)
+        }
+    }
+
+    fn new_registry(
+        tokens: Vec<TokenEntry>,
+        path: Option<PathBuf>,
+    ) -> Result<TokenRegistry, WError> {
+        TokenRegistry::new(tokens, path, HYDRA_TOKEN_HASH.to_string())
+    }
+
+    fn temp_registry_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "hibiki-tokens-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_registry_map_matches_hydra_to_l1_token_map() {
+        let registry = new_registry(vec![token(USDM)], None).unwrap();
+        assert_eq!(
+            *registry.hydra_to_l1_map(),
+            hydra_to_l1_token_map(&["lovelace", USDM], HYDRA_TOKEN_HASH)
+        );
+    }
+
+    #[test]
+    fn test_add_and_remove_persist() {
+        let path = temp_registry_path("persist");
+        let registry = new_registry(vec![token(USDM)], Some(path.clone())).unwrap();
+
+        registry.add(token(SNEK)).unwrap();
+        assert!(registry.add(token(SNEK)).is_err());
+        let hydra_unit = token(SNEK).hydra_unit(HYDRA_TOKEN_HASH);
+        assert_eq!(
+            registry
+                .hydra_to_l1_map()
+                .get(&hydra_unit)
+                .map(String::as_str),
+            Some(SNEK)
+        );
+
+        let removed = registry.remove(USDM).unwrap();
+        assert!(removed.delisted);
+        assert!(registry.remove(USDM).is_err());
+
+        // The file wins over the seed on the next start
+        let reloaded = new_registry(vec![token(USDM)], Some(path.clone())).unwrap();
+        assert_eq!(reloaded.list(), vec![removed, token(SNEK)]);
+
+        // Relisting replaces the delisted entry
+        reloaded.add(usdm()).unwrap();
+        assert_eq!(reloaded.list(), vec![token(SNEK), usdm()]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_removed_token_balances_still_convert() {
+        let registry = new_registry(vec![usdm()], None).unwrap();
+        let hydra_unit = usdm().hydra_unit(HYDRA_TOKEN_HASH);
+        let balance = [Asset::new_from_str(&hydra_unit, "12500000")];
+        registry.remove(USDM).unwrap();
+
+        let l1 = to_l1_assets(&balance, &registry.hydra_to_l1_map()).unwrap();
+        assert_eq!(
+            (l1[0].unit(), l1[0].quantity()),
+            (USDM.to_string(), "12500000".to_string())
+        );
+        // Delisted tokens still display, but can no longer be transferred
+        assert_eq!(
+            registry.resolve_amount(USDM, "12500000").unwrap().1,
+            12_500_000
+        );
+        assert!(registry
+            .validate_transfer(&[Asset::new_from_str(USDM, "12500000")])
+            .is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_units() {
+        assert!(new_registry(vec![token("lovelace")], None).is_err());
+        assert!(new_registry(vec![token(USDM), token(USDM)], None).is_err());
+
+        let ada_ticker = TokenEntry {
+            ticker: "ada".to_string(),
+            ..token(SNEK)
+        };
+        assert!(new_registry(vec![ada_ticker], None).is_err());
+    }
+
+    #[test]
+    fn test_validate_transfer_amounts() {
+        let registry = new_registry(vec![usdm(), token(SNEK)], None).unwrap();
+        let transfer = |unit: &str, quantity: &str| {
+            registry
+                .validate_transfer(&[Asset::new_from_str(unit, quantity)])
+                .map(|assets| (assets[0].unit(), assets[0].quantity()))
+        };
+
+        let raw = (USDM.to_string(), "12500000".to_string());
+        assert_eq!(transfer("", "12.5 USDM").unwrap(), raw);
+        assert_eq!(transfer(USDM, "12.5 usdm").unwrap(), raw);
+        assert_eq!(transfer("USDM", "12500000").unwrap(), raw);
+        assert_eq!(
+            transfer("", "2 ADA").unwrap(),
+            ("lovelace".to_string(), "2000000".to_string())
+        );
+
+        // Below minimum, beyond precision, mismatched or unknown tokens
+        assert!(transfer(USDM, "999999").is_err());
+        assert!(transfer("", "1.0000001 USDM").is_err());
+        assert!(transfer(USDM, "12.5").is_err());
+        assert!(transfer(SNEK, "12.5 USDM").is_err());
+        assert!(transfer(SNEK, "0").is_err());
+        assert!(transfer("", "1 HOSKY").is_err());
+
+        assert_eq!(usdm().display_amount(12_500_000), "12.5 USDM");
+        assert_eq!(token(SNEK).display_amount(7), format!("7 {}", SNEK));
+    }
+}
@@ -1,38 +1,84 @@
-use crate::config::AppConfig;
-use whisky::{NetworkId, Wallet};
-
-pub fn get_app_owner_wallet() -> Wallet {
-    let app_config = AppConfig::new();
-    let owner_mnemonic = app_config.app_owner_mnemonic;
-    let network_id = match app_config
-        .network_id
-        .parse::<u8>()
-        .expect("Failed to parse network_id")
-    {
-        0 => NetworkId::Preprod,
-        1 => NetworkId::Mainnet,
-        _ => NetworkId::Preprod, // Default to Preprod
-    };
-
-    Wallet::new_mnemonic(&owner_mnemonic)
-        .expect("Failed to create app owner wallet")
-        .with_network_id(network_id)
+use std::sync::Arc;
+
+use crate::{
+    config::{
+        get_mnemonic,
+        signing_keys::{
+            Derivation, APP_OWNER_PREFIX, APP_OWNER_ROLE, FEE_COLLECTOR_PREFIX, FEE_COLLECTOR_ROLE,
+        },
+        AppConfig,
+    },
+    signer::{load_signer, RotatingSigner, SignerLoader},
+};
+use whisky::{NetworkId, WError, Wallet};
+
+pub fn wallet_from_mnemonic(mnemonic: &str, network: NetworkId) -> Result<Wallet, WError> {
+    let wallet =
+        Wallet::new_mnemonic(mnemonic).map_err(WError::from_err("wallet_from_mnemonic"))?;
+    Ok(wallet.with_network_id(network))
+}
+
+/// Hex encoded hash of the wallet's payment verification key
+pub fn wallet_key_hash(wallet: &Wallet) -> Result<String, WError> {
+    let account = wallet
+        .get_account()
+        .map_err(WError::from_err("wallet_key_hash - get_account"))?;
+    Ok(account.public_key.hash().to_hex())
+}
+
+pub async fn get_app_owner_wallet(network: NetworkId) -> Wallet {
+    let mnemonic = get_mnemonic(APP_OWNER_PREFIX)
+        .await
+        .expect("Failed to get app owner mnemonic");
+    wallet_from_mnemonic(&mnemonic, network).expect("Failed to create app owner wallet")
+}
+
+pub async fn get_fee_collector_wallet(network: NetworkId) -> Wallet {
+    let mnemonic = get_mnemonic(FEE_COLLECTOR_PREFIX)
+        .await
+        .expect("Failed to get fee collector mnemonic");
+    wallet_from_mnemonic(&mnemonic, network).expect("Failed to create fee collector wallet")
+}
+
+/// A rotating signer for `role` whose key is configured under `prefix`, derived along
+/// `derivation` unless its `[signers]` entry sets a path
+pub async fn load_rotating_signer(
+    config: &AppConfig,
+    role: &str,
+    prefix: String,
+    derivation: Derivation,
+) -> Result<Arc<RotatingSigner>, WError> {
+    let backend = config
+        .signer_backend(&prefix)
+        .map_err(WError::from_err("load_rotating_signer - backend"))?;
+    let derivation = config
+        .derivation(&prefix, derivation)
+        .map_err(WError::from_err("load_rotating_signer - derivation"))?;
+    let loader: SignerLoader = Arc::new(move || {
+        let prefix = prefix.clone();
+        let backend = backend.clone();
+        Box::pin(async move { load_signer(&prefix, backend, derivation).await })
+    });
+    let signer = RotatingSigner::new(role, loader, config.signing_key_grace_period()).await?;
+    Ok(Arc::new(signer))
+}
+
+pub async fn get_app_owner_signer(config: &AppConfig) -> Result<Arc<RotatingSigner>, WError> {
+    load_rotating_signer(
+        config,
+        APP_OWNER_ROLE,
+        APP_OWNER_PREFIX.to_string(),
+        Derivation::default(),
+    )
+    .await
 }
 
-pub fn get_fee_collector_wallet() -> Wallet {
-    let app_config = AppConfig::new();
-    let owner_mnemonic = app_config.fee_collector_mnemonic;
-    let network_id = match app_config
-        .network_id
-        .parse::<u8>()
-        .expect("Failed to parse network_id")
-    {
-        0 => NetworkId::Preprod,
-        1 => NetworkId::Mainnet,
-        _ => NetworkId::Preprod, // Default to Preprod
-    };
-
-    Wallet::new_mnemonic(&owner_mnemonic)
-        .expect("Failed to create fee collector wallet")
-        .with_network_id(network_id)
+pub async fn get_fee_collector_signer(config: &AppConfig) -> Result<Arc<RotatingSigner>, WError> {
+    load_rotating_signer(
+        config,
+        FEE_COLLECTOR_ROLE,
+        FEE_COLLECTOR_PREFIX.to_string(),
+        Derivation::default(),
+    )
+    .await
 }
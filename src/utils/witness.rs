@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use whisky::{csl, CSLParser, WError};
+
+/// Breakdown of a transaction's vkey witnesses against its `required_signers`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureReport {
+    /// Result of `CSLParser::check_all_required_signers`
+    pub fully_signed: bool,
+    /// Required signer hashes with a valid witness
+    pub present_signers: Vec<String>,
+    /// Required signer hashes without a valid witness
+    pub missing_signers: Vec<String>,
+    /// Key hashes whose witness signature does not match the body hash
+    pub invalid_witnesses: Vec<String>,
+    /// Key hashes with a valid witness that are not listed in `required_signers`
+    /// (these may still be needed to spend their own inputs)
+    pub extraneous_witnesses: Vec<String>,
+}
+
+/// Hex encoded key hashes listed in the transaction's `required_signers`
+pub fn required_signers(tx_hex: &str) -> Result<Vec<String>, WError> {
+    let tx = csl::FixedTransaction::from_hex(tx_hex)
+        .map_err(WError::from_err("required_signers - from_hex"))?;
+    Ok(required_signers_of(&tx.body()))
+}
+
+fn required_signers_of(body: &csl::TransactionBody) -> Vec<String> {
+    let mut signers = Vec::new();
+    if let Some(key_hashes) = body.required_signers() {
+        for index in 0..key_hashes.len() {
+            signers.push(key_hashes.get(index).to_hex());
+        }
+    }
+    signers
+}
+
+pub fn verify_signatures(tx_hex: &str) -> Result<SignatureReport, WError> {
+    let tx = csl::FixedTransaction::from_hex(tx_hex)
+        .map_err(WError::from_err("verify_signatures - from_hex"))?;
+    let tx_hash = tx.transaction_hash().to_bytes();
+    let required = required_signers_of(&tx.body());
+
+    let mut valid_signers = HashSet::new();
+    let mut invalid_witnesses = Vec::new();
+    let mut extraneous_witnesses = Vec::new();
+    if let Some(vkeys) = tx.witness_set().vkeys() {
+        for index in 0..vkeys.len() {
+            let witness = vkeys.get(index);
+            let public_key = witness.vkey().public_key();
+            let key_hash = public_key.hash().to_hex();
+            if !public_key.verify(&tx_hash, &witness.signature()) {
+                invalid_witnesses.push(key_hash);
+                continue;
+            }
+            if !required.contains(&key_hash) {
+                extraneous_witnesses.push(key_hash.clone());
+            }
+            valid_signers.insert(key_hash);
+        }
+    }
+
+    let (present_signers, missing_signers): (Vec<String>, Vec<String>) = required
+        .into_iter()
+        .partition(|key_hash| valid_signers.contains(key_hash));
+
+    let fully_signed = CSLParser::new_with_body(tx_hex)?
+        .check_all_required_signers()
+        .map_err(WError::from_err(
+            "verify_signatures - check_all_required_signers",
+        ))?;
+
+    Ok(SignatureReport {
+        fully_signed,
+        present_signers,
+        missing_signers,
+        invalid_witnesses,
+        extraneous_witnesses,
+    })
+}
+
+/// CBOR hex of every vkey witness present in `signed_tx_hex` but not in `tx_hex`
+pub fn extract_new_vkey_witnesses(
+    tx_hex: &str,
+    signed_tx_hex: &str,
+) -> Result<Vec<String>, WError> {
+    let existing: HashSet<String> = vkey_witnesses(tx_hex)?.into_iter().collect();
+    Ok(vkey_witnesses(signed_tx_hex)?
+        .into_iter()
+        .filter(|witness| !existing.contains(witness))
+        .collect())
+}
+
+/// CBOR hex of every vkey witness in the transaction
+pub fn vkey_witnesses(tx_hex: &str) -> Result<Vec<String>, WError> {
+    let tx = csl::FixedTransaction::from_hex(tx_hex)
+        .map_err(WError::from_err("vkey_witnesses - from_hex"))?;
+    let mut witnesses = Vec::new();
+    if let Some(vkeys) = tx.witness_set().vkeys() {
+        for index in 0..vkeys.len() {
+            witnesses.push(vkeys.get(index).to_hex());
+        }
+    }
+    Ok(witnesses)
+}
+
+/// Add vkey witnesses to a transaction without re-serializing its body
+///
+/// Every witness must be a valid signature of the transaction body hash.
+pub fn merge_vkey_witnesses(tx_hex: &str, witness_hexes: &[String]) -> Result<String, WError> {
+    let mut tx = csl::FixedTransaction::from_hex(tx_hex)
+        .map_err(WError::from_err("merge_vkey_witnesses - from_hex"))?;
+    let tx_hash = tx.transaction_hash().to_bytes();
+
+    for (index, witness_hex) in witness_hexes.iter().enumerate() {
+        let witness = csl::Vkeywitness::from_hex(witness_hex)
+            .map_err(WError::from_err("merge_vkey_witnesses - witness from_hex"))?;
+        if !witness
+            .vkey()
+            .public_key()
+            .verify(&tx_hash, &witness.signature())
+        {
+            return Err(WError::new(
+                "merge_vkey_witnesses - verify",
+                &format!(
+                    "Witness {} by key {} is not a valid signature of the transaction",
+                    index,
+                    witness.vkey().public_key().hash().to_hex()
+                ),
+            ));
+        }
+        tx.add_vkey_witness(&witness);
+    }
+
+    Ok(tx.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Transaction carrying a single valid vkey witness
+    const SIGNED_TX: &str = "84a400d9010281825820e05c49b5f7bfb05ce2cb589e0e652dbf1967398fda55718c427693d4be4e9fd80101828258390004845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66e0464447c1f51adaefe1ebfb0dd485a349a70479ced1d198cbdf7fe7821a05f5e100a1581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1a05f5e100825839001434db8b992d8bd16ed71c521d0def5811f637d6509d8d4f620ad03ee1041668e7fd16cb2fd97e1995e1016c37f5766f387333c38185f66b821a35654c3aa2581cb80aa257a376c9ae7aa0c7a323db88d236e11e0a5ed5e10142da9ea0a14a000de14074657374313101581cc69b981db7a65e339a6d783755f85a2e03afa1cece9714c55fe4c913a1445553444d1b00000001d6e06f00021a0002aee1075820bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8059a030ca10081825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402f5d90103a0";
+    // The vkey witness of SIGNED_TX
+    const WITNESS: &str = "825820ee9f3061a3a5756ad2e25629e69b927b890b137e68d1c25947fc8b7da3b4445258402fcd7eb1279ca347eb139229c75f56d1c88bd1234038d22c0c3194307c0e4ebd124d415f6a86a4c3f0f2dccfa487e4f4996d7c8b661807bccff659973bc8a402";
+    // Unrelated transaction without vkey witnesses
+    const OTHER_TX: &str = "84a800d9010281825820e38178967c200c81b4d7052e81de78a32c22a1ca26c1737f04643d5ee237ad9419020a0182a300581d70eb0a5938244e92fd172560f530bf959724b10353a26f276ea8bbb3cc018200a1581c463e70d04718e253757523698184cb7090b0430e89dc025c4c8e392ca14001028201d81858c7d87c9fd8799fd8799f50fb73c3bd256949a480c47abaad3dfa4fd8799f581c04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66ffd8799f581c71052cf0c2562d16eedd20e6e22e5b82173630f9a45ff8d42c38e29fffffffd8799fd8799f50e0e1622f99434f2c867afc3dcb6732b3d8799f581cfdeb4bf0e8c077114a4553f1e05395e9fb7114db177f02f7b65c8de4ffd8799f581c229d96e64aa5878fc93ba2ee9081126052d62974da032f1e5998be5dffffffa140a1401a000f4240ff82583900fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c89a2f36d3033bf4be236847143916e2e237de49069844934ac88f4e500020009a1581c463e70d04718e253757523698184cb7090b0430e89dc025c4c8e392ca140010b58208ba3b26901576dfc1757835eca10292d9d0324e3779e9b15b908e4f7459edcb90dd9010281825820e38178967c200c81b4d7052e81de78a32c22a1ca26c1737f04643d5ee237ad941903e80ed9010282581cfa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c581c04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b6612d9010281825820ace128c7ab85836aed1f4f188df6a85e6b103d21518af570fa81deaef6018ff400a207d901028158b558b30101009800aba2a6011e581cfa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c00a6010746382d6d696e740048c8c8c8c88c88966002646464646464660020026eb0c038c03cc03cc03cc03cc03cc03cc03cc03cc030dd5180718061baa0072259800800c52844c96600266e3cdd71808001005c528c4cc00c00c00500d1808000a01c300c300d002300b001300b002300900130063754003149a26cac8028dd7000ab9a5573caae7d5d0905a182010082d87f9fd8799fd8799f50fb73c3bd256949a480c47abaad3dfa4fd8799f581c04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66ffd8799f581c71052cf0c2562d16eedd20e6e22e5b82173630f9a45ff8d42c38e29fffffffd8799fd8799f50e0e1622f99434f2c867afc3dcb6732b3d8799f581cfdeb4bf0e8c077114a4553f1e05395e9fb7114db177f02f7b65c8de4ffd8799f581c229d96e64aa5878fc93ba2ee9081126052d62974da032f1e5998be5dffffffa140a1401a000f4240ff820000f5f6";
+
+    #[test]
+    fn test_vkey_witnesses() {
+        assert_eq!(
+            vkey_witnesses(SIGNED_TX).unwrap(),
+            vec![WITNESS.to_string()]
+        );
+        assert!(vkey_witnesses(OTHER_TX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_new_vkey_witnesses() {
+        assert!(extract_new_vkey_witnesses(SIGNED_TX, SIGNED_TX)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_merge_valid_witness() {
+        let merged = merge_vkey_witnesses(SIGNED_TX, &[WITNESS.to_string()]).unwrap();
+        assert_eq!(vkey_witnesses(&merged).unwrap(), vec![WITNESS.to_string()]);
+    }
+
+    #[test]
+    fn test_required_signers() {
+        assert!(required_signers(SIGNED_TX).unwrap().is_empty());
+        let signers = required_signers(OTHER_TX).unwrap();
+        assert_eq!(signers.len(), 2);
+        assert!(signers
+            .contains(&"fa5136e9e9ecbc9071da73eeb6c9a4ff73cbf436105cf8380d1c525c".to_string()));
+        assert!(signers
+            .contains(&"04845038ee499ee8bc0afe56f688f27b2dd76f230d3698a9afcc1b66".to_string()));
+    }
+
+    #[test]
+    fn test_verify_signatures_missing_signers() {
+        let report = verify_signatures(OTHER_TX).unwrap();
+        assert!(!report.fully_signed);
+        assert!(report.present_signers.is_empty());
+        assert_eq!(report.missing_signers.len(), 2);
+        assert!(report.invalid_witnesses.is_empty());
+    }
+
+    #[test]
+    fn test_verify_signatures_extraneous_witness() {
+        let report = verify_signatures(SIGNED_TX).unwrap();
+        assert!(report.missing_signers.is_empty());
+        assert!(report.invalid_witnesses.is_empty());
+        assert_eq!(report.extraneous_witnesses.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_rejects_witness_for_other_body() {
+        assert!(merge_vkey_witnesses(OTHER_TX, &[WITNESS.to_string()]).is_err());
+    }
+}